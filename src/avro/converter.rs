@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value};
+
+use crate::diagnostics::{Diagnostic, Span};
+use crate::schemata::type_mapping;
+use crate::schemata::types::{SchemataEnum, SchemataField, SchemataNamespace, SchemataSchema};
+
+/// Maps Avro JSON schemas (`.avsc`) and protocols (`.avpr`) onto Schemata.
+///
+/// Avro named types are global, so records and enums declared inline in a field are hoisted to
+/// top-level declarations in their own namespace and referenced by name. References to types in
+/// another namespace are written fully qualified.
+pub struct AvroConverter {
+    namespaces: Vec<SchemataNamespace>,
+    /// Full name of every named type seen so far, so later references can be resolved
+    named: HashMap<String, NamedType>,
+}
+
+#[derive(Clone)]
+enum NamedType {
+    Record,
+    Enum,
+    Fixed(u64),
+}
+
+/// A Schemata field type derived from an Avro schema, before it is attached to a field.
+struct FieldType {
+    type_name: String,
+    nullable: bool,
+    annotations: Vec<String>,
+    inline_schema: Option<SchemataSchema>,
+}
+
+impl FieldType {
+    fn basic(type_name: &str) -> Self {
+        Self { type_name: type_name.to_string(), nullable: false, annotations: Vec::new(), inline_schema: None }
+    }
+
+    fn annotated(type_name: &str, annotation: String) -> Self {
+        Self { annotations: vec![annotation], ..Self::basic(type_name) }
+    }
+}
+
+impl AvroConverter {
+    /// Converts a JSON document holding a single schema, a list of schemas, or a protocol
+    /// with a `types` list.
+    pub fn convert(input: &str) -> Result<Vec<SchemataNamespace>, Box<dyn std::error::Error>> {
+        let value: Value = serde_json::from_str(input).map_err(|e| Self::json_error(e, input))?;
+        Self::convert_value(&value)
+    }
+
+    pub fn convert_value(value: &Value) -> Result<Vec<SchemataNamespace>, Box<dyn std::error::Error>> {
+        let mut converter = Self { namespaces: Vec::new(), named: HashMap::new() };
+
+        match value {
+            Value::Object(object) if object.contains_key("protocol") => {
+                let namespace = object.get("namespace").and_then(Value::as_str).unwrap_or("");
+                let types = object.get("types").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+                for schema in types {
+                    converter.declare(namespace, schema)?;
+                }
+            },
+            Value::Array(schemas) => {
+                for schema in schemas {
+                    converter.declare("", schema)?;
+                }
+            },
+            schema => converter.declare("", schema)?,
+        }
+
+        Ok(converter.namespaces)
+    }
+
+    /// Registers a top-level schema. Anything other than a named type has no Schemata
+    /// equivalent at the top level and is skipped.
+    fn declare(&mut self, namespace: &str, schema: &Value) -> Result<(), Box<dyn std::error::Error>> {
+        match schema {
+            Value::Object(object) if matches!(Self::type_of(object), Some("record" | "error" | "enum" | "fixed")) => {
+                self.named_type(namespace, object)?;
+            },
+            Value::String(name) => log::warn!("Skipping top-level reference to `{}`", name),
+            _ => log::warn!("Skipping top-level Avro schema that is not a record, enum or fixed"),
+        }
+        Ok(())
+    }
+
+    /// Declares a record, enum or fixed type and returns its full name.
+    fn named_type(&mut self, namespace: &str, object: &Map<String, Value>) -> Result<String, Box<dyn std::error::Error>> {
+        let (namespace, name) = Self::full_name(namespace, object)?;
+        let full_name = Self::join(&namespace, &name);
+        let comment = Self::doc(object);
+
+        match Self::type_of(object) {
+            Some("enum") => {
+                let values = object.get("symbols")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| format!("Enum `{}` has no symbols", full_name))?
+                    .iter()
+                    .filter_map(|symbol| symbol.as_str().map(str::to_string))
+                    .collect();
+                self.named.insert(full_name.clone(), NamedType::Enum);
+                self.namespace_entry(&namespace).enums.push(SchemataEnum {
+                    name,
+                    annotations: String::new(),
+                    comment,
+                    values,
+                    value_comments: BTreeMap::new(),
+                    value_annotations: BTreeMap::new(),
+                });
+            },
+            Some("fixed") => {
+                let size = object.get("size").and_then(Value::as_u64).ok_or_else(|| format!("Fixed `{}` has no size", full_name))?;
+                self.named.insert(full_name.clone(), NamedType::Fixed(size));
+            },
+            _ => {
+                // Registered before the fields so recursive references resolve
+                self.named.insert(full_name.clone(), NamedType::Record);
+                let index = {
+                    let entry = self.namespace_entry(&namespace);
+                    entry.schemas.push(SchemataSchema { name, annotations: String::new(), comment, fields: Vec::new() });
+                    entry.schemas.len() - 1
+                };
+
+                let mut fields = Vec::new();
+                let empty = Vec::new();
+                for field in object.get("fields").and_then(Value::as_array).unwrap_or(&empty) {
+                    let field = field.as_object().ok_or_else(|| format!("Malformed field in record `{}`", full_name))?;
+                    fields.push(self.field(&namespace, field)?);
+                }
+                if let Some(entry) = self.namespaces.iter_mut().find(|ns| ns.name == Self::namespace_name(&namespace)) {
+                    entry.schemas[index].fields = fields;
+                }
+            },
+        }
+
+        Ok(full_name)
+    }
+
+    fn field(&mut self, namespace: &str, field: &Map<String, Value>) -> Result<SchemataField, Box<dyn std::error::Error>> {
+        let name = field.get("name").and_then(Value::as_str).ok_or("Record field without a name")?.to_string();
+        let schema = field.get("type").ok_or_else(|| format!("Field `{}` has no type", name))?;
+        let field_type = self.field_type(namespace, &name, schema)?;
+
+        let mut annotations = field_type.annotations;
+        match field.get("default") {
+            None | Some(Value::Null) => {},
+            Some(Value::String(default)) => annotations.push(format!("@default(value = \"{}\")", default.replace('"', "'"))),
+            Some(default) => annotations.push(format!("@default(value = \"{}\")", default.to_string().replace('"', "'"))),
+        }
+
+        Ok(SchemataField {
+            name,
+            type_name: field_type.type_name,
+            nullable: field_type.nullable,
+            annotations: annotations.join(" "),
+            comment: Self::doc(field),
+            inline_schema: field_type.inline_schema,
+            inline_enum: None,
+        })
+    }
+
+    fn field_type(&mut self, namespace: &str, field_name: &str, schema: &Value) -> Result<FieldType, Box<dyn std::error::Error>> {
+        match schema {
+            Value::String(name) => Ok(self.reference(namespace, name)),
+            Value::Array(branches) => self.union(namespace, field_name, branches),
+            Value::Object(object) => {
+                if let Some(field_type) = Self::logical_type(object) {
+                    return Ok(field_type);
+                }
+                match Self::type_of(object) {
+                    Some("record" | "error" | "enum" | "fixed") => {
+                        let full_name = self.named_type(namespace, object)?;
+                        Ok(self.reference(namespace, &full_name))
+                    },
+                    Some("array") => {
+                        let items = object.get("items").ok_or_else(|| format!("Array field `{}` has no items", field_name))?;
+                        let mut item = self.field_type(namespace, field_name, items)?;
+                        if item.type_name.ends_with("[]") || item.nullable {
+                            log::warn!("Field `{}`: nested arrays and arrays of nullable items are flattened", field_name);
+                        }
+                        if !item.type_name.ends_with("[]") {
+                            item.type_name.push_str("[]");
+                        }
+                        item.nullable = false;
+                        Ok(item)
+                    },
+                    Some("map") => {
+                        let values = object.get("values").ok_or_else(|| format!("Map field `{}` has no values", field_name))?;
+                        self.map(namespace, field_name, values)
+                    },
+                    Some(_) => match object.get("type") {
+                        Some(inner) => self.field_type(namespace, field_name, inner),
+                        None => Err(format!("Field `{}` has no type", field_name).into()),
+                    },
+                    None => Err(format!("Field `{}` has no type", field_name).into()),
+                }
+            },
+            _ => Err(format!("Field `{}` has an invalid type", field_name).into()),
+        }
+    }
+
+    /// Avro map keys are always strings.
+    fn map(&mut self, namespace: &str, field_name: &str, values: &Value) -> Result<FieldType, Box<dyn std::error::Error>> {
+        let value = self.field_type(namespace, field_name, values)?;
+        if !value.annotations.is_empty() {
+            log::warn!("Field `{}`: annotations of map values are dropped: {}", field_name, value.annotations.join(" "));
+        }
+        let nullable = if value.nullable { "?" } else { "" };
+        Ok(FieldType::basic(&format!("map<string, {}{}>", value.type_name, nullable)))
+    }
+
+    /// `["null", T]` becomes a nullable `T`, and any other union a `union<A, B>` that is nullable
+    /// if `null` is one of its branches. Members of a union cannot be annotated.
+    fn union(&mut self, namespace: &str, field_name: &str, branches: &[Value]) -> Result<FieldType, Box<dyn std::error::Error>> {
+        let nullable = branches.iter().any(|b| b.as_str() == Some("null"));
+        let mut types = Vec::new();
+        for branch in branches.iter().filter(|b| b.as_str() != Some("null")) {
+            types.push(self.field_type(namespace, field_name, branch)?);
+        }
+
+        let mut field_type = match types.len() {
+            0 => FieldType::basic("string"),
+            1 => types.remove(0),
+            _ => {
+                let annotations: Vec<&str> = types.iter().flat_map(|t| &t.annotations).map(String::as_str).collect();
+                if !annotations.is_empty() {
+                    log::warn!("Field `{}`: annotations of union members are dropped: {}", field_name, annotations.join(" "));
+                }
+                let members: Vec<&str> = types.iter().map(|t| t.type_name.as_str()).collect();
+                FieldType::basic(&format!("union<{}>", members.join(", ")))
+            },
+        };
+        field_type.nullable |= nullable;
+        Ok(field_type)
+    }
+
+    fn reference(&self, namespace: &str, name: &str) -> FieldType {
+        if name == "null" {
+            return FieldType { nullable: true, ..FieldType::basic("string") };
+        }
+        if let Some(basic) = type_mapping::from_avro(name, None) {
+            return FieldType::basic(basic.name);
+        }
+
+        let full_name = if name.contains('.') { name.to_string() } else { Self::join(namespace, name) };
+        match self.named.get(&full_name) {
+            Some(NamedType::Fixed(size)) => FieldType::annotated("bytes", format!("@fixed(size = \"{}\")", size)),
+            Some(_) => {
+                let (type_namespace, type_name) = Self::split(&full_name);
+                if type_namespace == namespace {
+                    FieldType::basic(type_name)
+                } else {
+                    FieldType::basic(&full_name)
+                }
+            },
+            // Not declared in this document; keep the reference as written
+            None => FieldType::basic(name),
+        }
+    }
+
+    fn logical_type(object: &Map<String, Value>) -> Option<FieldType> {
+        let logical = object.get("logicalType")?.as_str()?;
+        // Unknown logical types fall back to the underlying type, as the spec requires
+        let basic = type_mapping::from_avro(Self::type_of(object)?, Some(logical))?;
+
+        Some(match basic.name {
+            "decimal" => {
+                let precision = object.get("precision").and_then(Value::as_u64)?;
+                let scale = object.get("scale").and_then(Value::as_u64).unwrap_or(0);
+                FieldType::basic(&format!("decimal({}, {})", precision, scale))
+            },
+            name => FieldType::basic(name),
+        })
+    }
+
+    /// Namespace and simple name of a named type, following the Avro rules: a dotted name is a
+    /// full name, otherwise an explicit `namespace` wins over the enclosing one.
+    fn full_name(enclosing: &str, object: &Map<String, Value>) -> Result<(String, String), Box<dyn std::error::Error>> {
+        let name = object.get("name").and_then(Value::as_str).ok_or("Named Avro type without a name")?;
+        if name.contains('.') {
+            let (namespace, name) = Self::split(name);
+            return Ok((namespace.to_string(), name.to_string()));
+        }
+        let namespace = object.get("namespace").and_then(Value::as_str).unwrap_or(enclosing);
+        Ok((namespace.to_string(), name.to_string()))
+    }
+
+    fn json_error(error: serde_json::Error, input: &str) -> Diagnostic {
+        // serde_json reports 1-based lines and columns; turn them back into a byte offset
+        let line_start: usize = input.split_inclusive('\n').take(error.line().saturating_sub(1)).map(str::len).sum();
+        let offset = (line_start + error.column().saturating_sub(1)).min(input.len());
+        let message = error.to_string();
+        let label = message.split(" at line ").next().unwrap_or(&message).to_string();
+        Diagnostic::error("Invalid Avro schema JSON")
+            .with_span(Span::new(offset, offset + 1))
+            .with_label(label)
+    }
+
+    fn namespace_entry(&mut self, namespace: &str) -> &mut SchemataNamespace {
+        let name = Self::namespace_name(namespace);
+        let index = match self.namespaces.iter().position(|ns| ns.name == name) {
+            Some(index) => index,
+            None => {
+                self.namespaces.push(SchemataNamespace { name, comment: None, schemas: Vec::new(), enums: Vec::new(), aliases: Vec::new() });
+                self.namespaces.len() - 1
+            },
+        };
+        &mut self.namespaces[index]
+    }
+
+    fn namespace_name(namespace: &str) -> String {
+        if namespace.is_empty() { "default".to_string() } else { namespace.to_string() }
+    }
+
+    fn type_of(object: &Map<String, Value>) -> Option<&str> {
+        object.get("type").and_then(Value::as_str)
+    }
+
+    fn doc(object: &Map<String, Value>) -> Option<String> {
+        object.get("doc").and_then(Value::as_str).map(|doc| doc.trim().lines().map(str::trim).collect::<Vec<_>>().join("\n"))
+    }
+
+    fn join(namespace: &str, name: &str) -> String {
+        if namespace.is_empty() { name.to_string() } else { format!("{}.{}", namespace, name) }
+    }
+
+    fn split(full_name: &str) -> (&str, &str) {
+        full_name.rsplit_once('.').unwrap_or(("", full_name))
+    }
+}
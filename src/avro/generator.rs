@@ -0,0 +1,293 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{json, Map, Value};
+
+use crate::diagnostics::Diagnostic;
+use crate::output::GeneratedFile;
+use crate::schemata::analyzer::{ResolvedType, Symbol, SymbolTable};
+use crate::schemata::ast::{Document, Enum, Field, Schema, TypeRef};
+use crate::schemata::type_mapping;
+
+/// Generates Avro schemas (`.avsc`) from a checked Schemata document, one file per top-level
+/// schema and enum.
+///
+/// Every file is self-contained: named types are defined inline where they are first used and
+/// referenced by full name afterwards, as Avro requires.
+pub struct AvroGenerator<'a> {
+    symbols: &'a SymbolTable,
+    /// Declarations by qualified name, inline schemas included
+    declarations: HashMap<String, Declaration<'a>>,
+    /// Named types already defined in the file being generated
+    defined: HashSet<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Declaration<'a> {
+    Schema(&'a Schema),
+    Enum(&'a Enum),
+}
+
+/// Type names that are not references to named types.
+const BUILTIN_TYPES: &[&str] = &[
+    "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+    "record", "enum", "array", "map", "fixed",
+];
+
+/// Basic types whose `@default` values Avro can write; dates and times are numbers in Avro.
+const DEFAULT_TYPES: &[&str] = &["int", "long", "float", "double", "bool", "string", "duration", "uuid", "uri"];
+
+impl<'a> AvroGenerator<'a> {
+    pub fn generate(document: &Document, symbols: &SymbolTable) -> Result<Vec<GeneratedFile>, Box<dyn std::error::Error>> {
+        AvroGenerator::generate_files(&symbols.expand_aliases(document), symbols)
+    }
+
+    fn generate_files(document: &'a Document, symbols: &'a SymbolTable) -> Result<Vec<GeneratedFile>, Box<dyn std::error::Error>> {
+        let mut generator = Self {
+            symbols,
+            declarations: HashMap::new(),
+            defined: HashSet::new(),
+        };
+        for namespace in &document.namespaces {
+            for schema in &namespace.schemas {
+                generator.declare_schema(&namespace.name, schema.name.as_deref().unwrap_or_default(), schema);
+            }
+            for enumeration in &namespace.enums {
+                generator.declarations.insert(format!("{}.{}", namespace.name, enumeration.name), Declaration::Enum(enumeration));
+            }
+        }
+
+        let mut files = Vec::new();
+        for namespace in &document.namespaces {
+            let names = namespace.schemas.iter()
+                .map(|s| s.name.as_deref().unwrap_or_default())
+                .chain(namespace.enums.iter().map(|e| e.name.as_str()));
+            for name in names {
+                let schema = generator.get_schema(&namespace.name, name)?;
+                Self::validate(&schema).map_err(|e| format!("Generated schema for `{}.{}` is not valid Avro: {}", namespace.name, name, e))?;
+                files.push(GeneratedFile {
+                    path: format!("{}/{}.avsc", namespace.name.replace('.', "/"), name),
+                    content: serde_json::to_string_pretty(&schema)? + "\n",
+                });
+            }
+        }
+        Ok(files)
+    }
+
+    fn declare_schema(&mut self, namespace: &str, name: &str, schema: &'a Schema) {
+        self.declarations.insert(format!("{}.{}", namespace, name), Declaration::Schema(schema));
+        for field in &schema.fields {
+            if let Some(inline) = &field.inline_schema {
+                self.declare_schema(namespace, &field.type_ref.name, inline);
+            }
+        }
+    }
+
+    /// The complete schema of one named type, as the root of its own file.
+    pub(crate) fn get_schema(&mut self, namespace: &str, name: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        self.defined.clear();
+        let symbol = self.symbols.get(&format!("{}.{}", namespace, name))
+            .ok_or_else(|| format!("Unknown declaration `{}.{}`", namespace, name))?
+            .clone();
+        self.named_type(&symbol)
+    }
+
+    /// Defines `symbol` the first time it is seen in a file and refers to it by name afterwards.
+    fn named_type(&mut self, symbol: &Symbol) -> Result<Value, Box<dyn std::error::Error>> {
+        let full_name = symbol.qualified_name();
+        if !self.defined.insert(full_name.clone()) {
+            return Ok(Value::String(full_name));
+        }
+
+        let mut schema = Map::new();
+        match self.declarations.get(&full_name).copied() {
+            Some(Declaration::Schema(declaration)) => {
+                schema.insert("type".to_string(), json!("record"));
+                schema.insert("name".to_string(), json!(symbol.name));
+                schema.insert("namespace".to_string(), json!(symbol.namespace));
+                if let Some(comment) = &declaration.comment {
+                    schema.insert("doc".to_string(), json!(comment));
+                }
+                let fields = declaration.fields.iter()
+                    .map(|field| self.get_field(&symbol.namespace, field))
+                    .collect::<Result<Vec<_>, _>>()?;
+                schema.insert("fields".to_string(), Value::Array(fields));
+            },
+            Some(Declaration::Enum(declaration)) => {
+                schema.insert("type".to_string(), json!("enum"));
+                schema.insert("name".to_string(), json!(symbol.name));
+                schema.insert("namespace".to_string(), json!(symbol.namespace));
+                if let Some(comment) = &declaration.comment {
+                    schema.insert("doc".to_string(), json!(comment));
+                }
+                schema.insert("symbols".to_string(), json!(declaration.values));
+            },
+            None => return Err(format!("Unknown declaration `{}`", full_name).into()),
+        }
+        Ok(Value::Object(schema))
+    }
+
+    fn get_field(&mut self, namespace: &str, field: &Field) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut schema = match type_mapping::map_entry(field) {
+            Some(value) => json!({ "type": "map", "values": self.field_type(namespace, &value.type_ref)? }),
+            None => self.field_type(namespace, &field.type_ref)?,
+        };
+        if field.type_ref.array && type_mapping::map_entry(field).is_none() {
+            schema = json!({ "type": "array", "items": schema });
+        }
+
+        let default = match field.annotations.iter().find(|a| a.name == "default").and_then(|a| a.arg("value")) {
+            Some(value) => type_mapping::default_value(namespace, field, value.as_str(), self.symbols, DEFAULT_TYPES, "Avro"),
+            None => None,
+        };
+
+        let mut avro_field = Map::new();
+        avro_field.insert("name".to_string(), json!(field.name));
+        if let Some(comment) = &field.comment {
+            avro_field.insert("doc".to_string(), json!(comment));
+        }
+        // A union's default must match its first branch, so `null` only leads without a default
+        match (field.type_ref.nullable, default) {
+            (true, None) => {
+                avro_field.insert("type".to_string(), Self::nullable(schema, true));
+                avro_field.insert("default".to_string(), Value::Null);
+            },
+            (true, Some(default)) => {
+                avro_field.insert("type".to_string(), Self::nullable(schema, false));
+                avro_field.insert("default".to_string(), default);
+            },
+            (false, default) => {
+                avro_field.insert("type".to_string(), schema);
+                if let Some(default) = default {
+                    avro_field.insert("default".to_string(), default);
+                }
+            },
+        }
+        Ok(Value::Object(avro_field))
+    }
+
+    /// The Avro schema of a field's type, ignoring `[]` and `?`. Avro map keys are strings, so
+    /// keys of other types are written as their string form.
+    fn field_type(&mut self, namespace: &str, type_ref: &TypeRef) -> Result<Value, Box<dyn std::error::Error>> {
+        let resolved = self.symbols.resolve(namespace, type_ref)
+            .map_err(|_| Diagnostic::error(format!("Unresolved type `{}`", type_ref.name)).with_span(type_ref.span))?;
+
+        Ok(match (resolved, &type_ref.map, &type_ref.union) {
+            (ResolvedType::Basic(_), Some(map), _) => {
+                let (key, value) = map.as_ref();
+                if key.name != "string" {
+                    log::warn!("Map keys of type `{}` are written as Avro strings", key.name);
+                }
+                json!({ "type": "map", "values": self.nested_type(namespace, value)? })
+            },
+            (ResolvedType::Basic(_), None, Some(members)) => {
+                // Members such as `decimal` and `string` may both be written as strings
+                let mut branches: Vec<Value> = Vec::new();
+                for member in members {
+                    let branch = self.nested_type(namespace, member)?;
+                    match branches.contains(&branch) {
+                        true => log::warn!("Union member `{}` is written as a type the union already has and is left out", member),
+                        false => branches.push(branch),
+                    }
+                }
+                match branches.len() {
+                    1 => branches.remove(0),
+                    _ => Value::Array(branches),
+                }
+            },
+            (ResolvedType::Basic(name), None, None) => Self::basic_type(&name, type_ref.decimal),
+            (ResolvedType::Schema(symbol) | ResolvedType::Enum(symbol), _, _) => self.named_type(&symbol)?,
+        })
+    }
+
+    /// The Avro schema of a map value or union member, with its `[]` and `?`.
+    fn nested_type(&mut self, namespace: &str, type_ref: &TypeRef) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut schema = self.field_type(namespace, type_ref)?;
+        if type_ref.array {
+            schema = json!({ "type": "array", "items": schema });
+        }
+        if type_ref.nullable {
+            schema = Self::nullable(schema, true);
+        }
+        Ok(schema)
+    }
+
+    /// Adds `null` to a schema, as the first or last branch. Unions cannot be nested, so a union
+    /// gets `null` as one more branch.
+    fn nullable(schema: Value, null_first: bool) -> Value {
+        let mut branches = match schema {
+            Value::Array(branches) => branches,
+            other => vec![other],
+        };
+        match null_first {
+            true => branches.insert(0, json!("null")),
+            false => branches.push(json!("null")),
+        }
+        Value::Array(branches)
+    }
+
+    fn basic_type(name: &str, decimal: Option<(u32, u32)>) -> Value {
+        let Some(&(avro, logical)) = type_mapping::basic_type(name).and_then(|basic| basic.avro.first()) else {
+            return json!("string");
+        };
+        match (logical, decimal) {
+            (Some("decimal"), Some((precision, scale))) => {
+                json!({ "type": avro, "logicalType": "decimal", "precision": precision, "scale": scale })
+            },
+            (Some("decimal"), None) => {
+                log::warn!("Avro decimals need a precision; `decimal` without one is written as a string");
+                json!("string")
+            },
+            (Some(logical), _) => json!({ "type": avro, "logicalType": logical }),
+            (None, _) => json!(avro),
+        }
+    }
+
+    /// Parses the schema with `avro-rs`. Its parser cannot resolve references to named types
+    /// (recursive ones included), so references are replaced by placeholder fixed types first;
+    /// every referenced type is validated in its own file. It also rejects unions of several
+    /// named types, which Avro allows, so all but the first named branch of a union are
+    /// validated on their own.
+    pub(crate) fn validate(schema: &Value) -> Result<(), avro_rs::Error> {
+        fn is_named(schema: &Value) -> bool {
+            match schema {
+                Value::String(name) => !BUILTIN_TYPES.contains(&name.as_str()),
+                Value::Object(object) => matches!(object.get("type").and_then(Value::as_str), Some("record" | "enum" | "fixed")),
+                _ => false,
+            }
+        }
+        fn stub(schema: &Value, detached: &mut Vec<Value>) -> Value {
+            match schema {
+                Value::String(name) if !BUILTIN_TYPES.contains(&name.as_str()) => json!({ "type": "fixed", "name": name, "size": 1 }),
+                Value::Array(branches) => {
+                    let mut named = false;
+                    let mut kept = Vec::new();
+                    for branch in branches {
+                        let stubbed = stub(branch, detached);
+                        if is_named(branch) && named {
+                            detached.push(stubbed);
+                        } else {
+                            named |= is_named(branch);
+                            kept.push(stubbed);
+                        }
+                    }
+                    Value::Array(kept)
+                },
+                Value::Object(object) => Value::Object(object.iter()
+                    .map(|(key, value)| {
+                        let value = match (key.as_str(), value) {
+                            ("type" | "items" | "values", _) => stub(value, detached),
+                            ("fields", Value::Array(fields)) => Value::Array(fields.iter().map(|field| stub(field, detached)).collect()),
+                            _ => value.clone(),
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect()),
+                other => other.clone(),
+            }
+        }
+
+        let mut detached = Vec::new();
+        avro_rs::Schema::parse(&stub(schema, &mut detached))?;
+        detached.iter().try_for_each(|schema| avro_rs::Schema::parse(schema).map(|_| ()))
+    }
+}
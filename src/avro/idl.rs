@@ -0,0 +1,293 @@
+use pest::iterators::Pair;
+use pest::Parser;
+use pest_derive::Parser;
+use serde_json::{json, Map, Value};
+
+use crate::diagnostics::{Diagnostic, Span};
+
+#[derive(Parser)]
+#[grammar = "../grammars/avdl.pest"]
+struct AvroIdlGrammar;
+
+/// Parses Avro IDL (`.avdl`) into the equivalent JSON protocol (`.avpr`), which
+/// [`AvroConverter`](super::AvroConverter) then maps onto Schemata.
+pub struct AvroIdlParser;
+
+impl AvroIdlParser {
+    pub fn parse(input: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        let idl = AvroIdlGrammar::parse(Rule::idl, input)
+            .map_err(Self::syntax_error)?
+            .next()
+            .ok_or("Empty parse tree")?;
+
+        let mut protocol = Map::new();
+        let mut types = Vec::new();
+
+        for pair in idl.into_inner() {
+            match pair.as_rule() {
+                Rule::doc_comment => { protocol.insert("doc".to_string(), Value::String(Self::doc(&pair))); },
+                Rule::annotation => {
+                    let (name, value) = Self::parse_annotation(pair)?;
+                    protocol.insert(name, value);
+                },
+                Rule::ident => { protocol.insert("protocol".to_string(), Value::String(Self::ident(&pair))); },
+                Rule::declaration => {
+                    if let Some(declaration) = Self::parse_declaration(pair)? {
+                        types.push(declaration);
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        protocol.insert("types".to_string(), Value::Array(types));
+        Ok(Value::Object(protocol))
+    }
+
+    /// Returns the JSON schema for a record, enum or fixed declaration; imports and messages
+    /// yield `None`.
+    fn parse_declaration(pair: Pair<Rule>) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+        let mut properties = Map::new();
+        let mut schema = None;
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::doc_comment => { properties.insert("doc".to_string(), Value::String(Self::doc(&inner))); },
+                Rule::annotation => {
+                    let (name, value) = Self::parse_annotation(inner)?;
+                    properties.insert(name, value);
+                },
+                Rule::record => schema = Some(Self::parse_record(inner)?),
+                Rule::r#enum => schema = Some(Self::parse_enum(inner)),
+                Rule::fixed => schema = Some(Self::parse_fixed(inner)?),
+                Rule::import => {
+                    let path = inner.into_inner().find(|p| p.as_rule() == Rule::string).map(|p| Self::string_value(&p));
+                    log::warn!("Import {} is not followed; its types are referenced by name only", path.unwrap_or_default());
+                },
+                _ => {}
+            }
+        }
+
+        Ok(schema.map(|mut schema| {
+            // Annotations on a named type become properties of its schema, `@namespace` included
+            schema.extend(properties);
+            Value::Object(schema)
+        }))
+    }
+
+    fn parse_record(pair: Pair<Rule>) -> Result<Map<String, Value>, Box<dyn std::error::Error>> {
+        let mut record = Map::new();
+        let mut fields = Vec::new();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::kw_record => { record.insert("type".to_string(), json!("record")); },
+                Rule::kw_error => { record.insert("type".to_string(), json!("error")); },
+                Rule::ident => { record.insert("name".to_string(), Value::String(Self::ident(&inner))); },
+                Rule::field => fields.extend(Self::parse_field(inner)?),
+                _ => {}
+            }
+        }
+
+        record.insert("fields".to_string(), Value::Array(fields));
+        Ok(record)
+    }
+
+    /// A field declaration can name several variables sharing one type, each becoming a field.
+    fn parse_field(pair: Pair<Rule>) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+        let mut doc = None;
+        let mut properties = Map::new();
+        let mut field_type = Value::Null;
+        let mut fields = Vec::new();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::doc_comment => doc = Some(Self::doc(&inner)),
+                // Annotations before the type annotate the type itself, e.g. `@logicalType`
+                Rule::annotation => {
+                    let (name, value) = Self::parse_annotation(inner)?;
+                    properties.insert(name, value);
+                },
+                Rule::field_type => field_type = Self::with_properties(Self::parse_type(inner)?, std::mem::take(&mut properties)),
+                Rule::variable => fields.push(Self::parse_variable(inner, &field_type, doc.as_deref())?),
+                _ => {}
+            }
+        }
+
+        Ok(fields)
+    }
+
+    fn parse_variable(pair: Pair<Rule>, field_type: &Value, doc: Option<&str>) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut field = Map::new();
+        field.insert("type".to_string(), field_type.clone());
+        if let Some(doc) = doc {
+            field.insert("doc".to_string(), Value::String(doc.to_string()));
+        }
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::doc_comment => { field.insert("doc".to_string(), Value::String(Self::doc(&inner))); },
+                Rule::annotation => {
+                    let (name, value) = Self::parse_annotation(inner)?;
+                    field.insert(name, value);
+                },
+                Rule::ident => { field.insert("name".to_string(), Value::String(Self::ident(&inner))); },
+                Rule::json_value => { field.insert("default".to_string(), serde_json::from_str(inner.as_str())?); },
+                _ => {}
+            }
+        }
+
+        Ok(Value::Object(field))
+    }
+
+    fn parse_type(pair: Pair<Rule>) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut schema = Value::Null;
+        let mut nullable = false;
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::array_type => schema = json!({ "type": "array", "items": Self::parse_type(Self::child(inner, Rule::field_type)?)? }),
+                Rule::map_type => schema = json!({ "type": "map", "values": Self::parse_type(Self::child(inner, Rule::field_type)?)? }),
+                Rule::union_type => {
+                    let branches = inner.into_inner()
+                        .filter(|p| p.as_rule() == Rule::field_type)
+                        .map(Self::parse_type)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    schema = Value::Array(branches);
+                },
+                Rule::decimal_type => {
+                    let mut numbers = inner.into_inner().filter(|p| p.as_rule() == Rule::int);
+                    let precision: u64 = numbers.next().ok_or("Decimal without precision")?.as_str().parse()?;
+                    let scale: u64 = numbers.next().map_or(Ok(0), |p| p.as_str().parse())?;
+                    schema = json!({ "type": "bytes", "logicalType": "decimal", "precision": precision, "scale": scale });
+                },
+                Rule::reference => schema = Self::reference(inner.as_str()),
+                Rule::nullable => nullable = true,
+                _ => {}
+            }
+        }
+
+        Ok(if nullable { json!(["null", schema]) } else { schema })
+    }
+
+    /// Primitive names and the IDL shorthands for logical types.
+    fn reference(name: &str) -> Value {
+        match name {
+            "void" => json!("null"),
+            "date" => json!({ "type": "int", "logicalType": "date" }),
+            "time_ms" => json!({ "type": "int", "logicalType": "time-millis" }),
+            "timestamp_ms" => json!({ "type": "long", "logicalType": "timestamp-millis" }),
+            "local_timestamp_ms" => json!({ "type": "long", "logicalType": "local-timestamp-millis" }),
+            "uuid" => json!({ "type": "string", "logicalType": "uuid" }),
+            name => Value::String(name.replace('`', "")),
+        }
+    }
+
+    fn parse_enum(pair: Pair<Rule>) -> Map<String, Value> {
+        let mut schema = Map::new();
+        schema.insert("type".to_string(), json!("enum"));
+        let mut symbols = Vec::new();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::ident if !schema.contains_key("name") => { schema.insert("name".to_string(), Value::String(Self::ident(&inner))); },
+                Rule::ident => symbols.push(Value::String(Self::ident(&inner))),
+                Rule::enum_default => { schema.insert("default".to_string(), Value::String(inner.as_str().to_string())); },
+                _ => {}
+            }
+        }
+
+        schema.insert("symbols".to_string(), Value::Array(symbols));
+        schema
+    }
+
+    fn parse_fixed(pair: Pair<Rule>) -> Result<Map<String, Value>, Box<dyn std::error::Error>> {
+        let mut schema = Map::new();
+        schema.insert("type".to_string(), json!("fixed"));
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::ident => { schema.insert("name".to_string(), Value::String(Self::ident(&inner))); },
+                Rule::int => { schema.insert("size".to_string(), json!(inner.as_str().parse::<u64>()?)); },
+                _ => {}
+            }
+        }
+
+        Ok(schema)
+    }
+
+    fn parse_annotation(pair: Pair<Rule>) -> Result<(String, Value), Box<dyn std::error::Error>> {
+        let mut inner = pair.into_inner();
+        let name = inner.next().ok_or("Annotation without a name")?.as_str().to_string();
+        let value = serde_json::from_str(inner.next().ok_or("Annotation without a value")?.as_str())?;
+        Ok((name, value))
+    }
+
+    /// Attaches type annotations, turning a primitive name into a schema object if needed.
+    fn with_properties(schema: Value, properties: Map<String, Value>) -> Value {
+        if properties.is_empty() {
+            return schema;
+        }
+        let mut object = match schema {
+            Value::Object(object) => object,
+            other => {
+                let mut object = Map::new();
+                object.insert("type".to_string(), other);
+                object
+            },
+        };
+        object.extend(properties);
+        Value::Object(object)
+    }
+
+    fn doc(pair: &Pair<Rule>) -> String {
+        let text = pair.as_str().trim_start_matches("/**").trim_end_matches("*/");
+        text.lines()
+            .map(|line| line.trim().trim_start_matches('*').trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn ident(pair: &Pair<Rule>) -> String {
+        pair.as_str().trim_matches('`').to_string()
+    }
+
+    fn string_value(pair: &Pair<Rule>) -> String {
+        pair.clone().into_inner().next().map(|p| p.as_str().to_string()).unwrap_or_default()
+    }
+
+    fn child(pair: Pair<Rule>, rule: Rule) -> Result<Pair<Rule>, Box<dyn std::error::Error>> {
+        pair.into_inner()
+            .find(|p| p.as_rule() == rule)
+            .ok_or_else(|| format!("Missing {:?}", rule).into())
+    }
+
+    fn syntax_error(error: pest::error::Error<Rule>) -> Diagnostic {
+        let error = error.renamed_rules(|rule| match rule {
+            Rule::kw_protocol => "`protocol`".to_string(),
+            Rule::kw_import | Rule::import => "`import`".to_string(),
+            Rule::kw_record | Rule::record => "`record`".to_string(),
+            Rule::kw_error => "`error`".to_string(),
+            Rule::kw_enum | Rule::r#enum => "`enum`".to_string(),
+            Rule::kw_fixed | Rule::fixed => "`fixed`".to_string(),
+            Rule::ident | Rule::reference => "identifier".to_string(),
+            Rule::field_type | Rule::array_type | Rule::map_type | Rule::union_type | Rule::decimal_type => "type".to_string(),
+            Rule::field | Rule::declaration => "declaration".to_string(),
+            Rule::variable => "field name".to_string(),
+            Rule::annotation => "annotation".to_string(),
+            Rule::doc_comment => "doc comment".to_string(),
+            Rule::json_value => "JSON value".to_string(),
+            Rule::nullable => "`?`".to_string(),
+            Rule::EOI => "end of file".to_string(),
+            other => format!("{:?}", other),
+        });
+        let span = match error.location {
+            pest::error::InputLocation::Pos(pos) => Span::new(pos, pos + 1),
+            pest::error::InputLocation::Span((start, end)) => Span::new(start, end),
+        };
+        Diagnostic::error("Syntax error in .avdl file")
+            .with_span(span)
+            .with_label(error.variant.message().to_string())
+    }
+}
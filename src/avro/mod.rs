@@ -0,0 +1,7 @@
+mod converter;
+mod generator;
+mod idl;
+mod tests;
+pub use converter::AvroConverter;
+pub use generator::AvroGenerator;
+pub use idl::AvroIdlParser;
\ No newline at end of file
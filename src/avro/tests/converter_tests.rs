@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use crate::avro::AvroConverter;
+    use crate::schemata::tests::support::{field, fields, schema};
+
+    #[test]
+    fn test_convert_record() {
+        let input = r#"{
+            "type": "record",
+            "name": "User",
+            "namespace": "com.example.people",
+            "doc": "A registered user",
+            "fields": [
+                {"name": "id", "type": "long", "doc": "Primary key"},
+                {"name": "email", "type": ["null", "string"], "default": null},
+                {"name": "active", "type": "boolean", "default": true},
+                {"name": "score", "type": "double"},
+                {"name": "tags", "type": {"type": "array", "items": "string"}},
+                {"name": "nickname", "type": ["string", "null"], "default": "anon"}
+            ]
+        }"#;
+        let namespaces = AvroConverter::convert(input).expect("Failed to convert schema");
+
+        assert_eq!(namespaces.len(), 1);
+        assert_eq!(namespaces[0].name, "com.example.people");
+        let user = schema(&namespaces[0], "User");
+        assert_eq!(user.comment.as_deref(), Some("A registered user"));
+        assert_eq!(user.fields[0].comment.as_deref(), Some("Primary key"));
+        assert_eq!(fields(user), vec![
+            field("id", "long", false, ""),
+            field("email", "string", true, ""),
+            field("active", "bool", false, "@default(value = \"true\")"),
+            field("score", "double", false, ""),
+            field("tags", "string[]", false, ""),
+            field("nickname", "string", true, "@default(value = \"anon\")"),
+        ]);
+    }
+
+    #[test]
+    fn test_convert_named_types() {
+        let input = r#"{
+            "type": "record",
+            "name": "Order",
+            "namespace": "com.example.ecommerce",
+            "fields": [
+                {"name": "status", "type": {"type": "enum", "name": "OrderStatus", "symbols": ["PENDING", "SHIPPED"]}},
+                {"name": "previous", "type": ["null", "OrderStatus"]},
+                {"name": "customer", "type": {
+                    "type": "record",
+                    "name": "Customer",
+                    "namespace": "com.example.people",
+                    "fields": [{"name": "name", "type": "string"}]
+                }},
+                {"name": "checksum", "type": {"type": "fixed", "name": "MD5", "size": 16}},
+                {"name": "next", "type": ["null", "Order"]}
+            ]
+        }"#;
+        let namespaces = AvroConverter::convert(input).unwrap();
+
+        assert_eq!(namespaces.iter().map(|ns| ns.name.as_str()).collect::<Vec<_>>(), vec!["com.example.ecommerce", "com.example.people"]);
+        assert_eq!(namespaces[0].enums[0].name, "OrderStatus");
+        assert_eq!(namespaces[0].enums[0].values, vec!["PENDING", "SHIPPED"]);
+        assert_eq!(schema(&namespaces[1], "Customer").fields.len(), 1);
+        assert_eq!(fields(schema(&namespaces[0], "Order")), vec![
+            field("status", "OrderStatus", false, ""),
+            field("previous", "OrderStatus", true, ""),
+            field("customer", "com.example.people.Customer", false, ""),
+            field("checksum", "bytes", false, "@fixed(size = \"16\")"),
+            field("next", "Order", true, ""),
+        ]);
+    }
+
+    #[test]
+    fn test_convert_logical_types_and_maps() {
+        let input = r#"{
+            "type": "record",
+            "name": "Payment",
+            "fields": [
+                {"name": "created_at", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+                {"name": "due", "type": {"type": "int", "logicalType": "date"}},
+                {"name": "amount", "type": {"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}},
+                {"name": "reference", "type": {"type": "string", "logicalType": "uuid"}},
+                {"name": "metadata", "type": {"type": "map", "values": "long"}},
+                {"name": "value", "type": ["int", "string"]},
+                {"name": "label", "type": ["null", "string", "long"]}
+            ]
+        }"#;
+        let namespaces = AvroConverter::convert(input).unwrap();
+
+        assert_eq!(namespaces[0].name, "default");
+        let payment = schema(&namespaces[0], "Payment");
+        assert_eq!(fields(payment), vec![
+            field("created_at", "datetime", false, ""),
+            field("due", "date", false, ""),
+            field("amount", "decimal(10, 2)", false, ""),
+            field("reference", "uuid", false, ""),
+            field("metadata", "map<string, long>", false, ""),
+            field("value", "union<int, string>", false, ""),
+            field("label", "union<string, long>", true, ""),
+        ]);
+    }
+
+    #[test]
+    fn test_convert_protocol_and_schema_list() {
+        let protocol = r#"{
+            "protocol": "Shop",
+            "namespace": "com.example.shop",
+            "types": [
+                {"type": "enum", "name": "Currency", "symbols": ["EUR", "USD"]},
+                {"type": "record", "name": "Price", "fields": [{"name": "currency", "type": "Currency"}]}
+            ],
+            "messages": {}
+        }"#;
+        let namespaces = AvroConverter::convert(protocol).unwrap();
+        assert_eq!(namespaces[0].name, "com.example.shop");
+        assert_eq!(fields(schema(&namespaces[0], "Price")), vec![field("currency", "Currency", false, "")]);
+
+        let list = r#"[
+            {"type": "record", "name": "a.A", "fields": []},
+            {"type": "record", "name": "B", "namespace": "b", "fields": [{"name": "a", "type": "a.A"}]}
+        ]"#;
+        let namespaces = AvroConverter::convert(list).unwrap();
+        assert_eq!(fields(schema(&namespaces[1], "B")), vec![field("a", "a.A", false, "")]);
+    }
+
+    #[test]
+    fn test_invalid_json_diagnostic() {
+        use crate::diagnostics::Diagnostic;
+
+        let input = "{\n  \"type\": \"record\",\n  \"name\": \"User\"\n  \"fields\": []\n}";
+        let error = AvroConverter::convert(input).expect_err("Conversion should fail");
+        let diagnostic = error.downcast_ref::<Diagnostic>().expect("Expected a diagnostic").clone();
+
+        let diagnostic = diagnostic.with_source("user.avsc", input);
+        assert_eq!(diagnostic.message, "Invalid Avro schema JSON");
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(4), Some(3)));
+    }
+}
@@ -0,0 +1,200 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::avro::{AvroConverter, AvroGenerator};
+    use crate::schemata::tests::support::json_files;
+    use crate::schemata::SchemataGenerator;
+
+    #[test]
+    fn test_generate_record() {
+        let input = r#"
+        namespace com.example.people;
+
+        # A registered user
+        schema User {
+            id int @id
+            email string?
+            score double @default(value = "1.5")
+            nickname string? @default(value = "anon")
+            active bool @default(value = "true")
+            createdAt datetime @default(value = "now()")
+            tags string[]
+        }
+        "#;
+        let files = json_files(input, AvroGenerator::generate);
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "com/example/people/User.avsc");
+        assert_eq!(files[0].1, json!({
+            "type": "record",
+            "name": "User",
+            "namespace": "com.example.people",
+            "doc": "A registered user",
+            "fields": [
+                {"name": "id", "type": "int"},
+                {"name": "email", "type": ["null", "string"], "default": null},
+                {"name": "score", "type": "double", "default": 1.5},
+                {"name": "nickname", "type": ["string", "null"], "default": "anon"},
+                {"name": "active", "type": "boolean", "default": true},
+                {"name": "createdAt", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+                {"name": "tags", "type": {"type": "array", "items": "string"}},
+            ],
+        }));
+    }
+
+    #[test]
+    fn test_named_types_are_defined_once() {
+        let input = r#"
+        namespace shop;
+        schema Order {
+            status Status @default(value = "PENDING")
+            previous Status?
+            items OrderItem[] schema {
+                sku string
+                order Order
+            }
+        }
+        enum Status { PENDING, SHIPPED }
+        "#;
+        let files = json_files(input, AvroGenerator::generate);
+
+        assert_eq!(files.iter().map(|(path, _)| path.as_str()).collect::<Vec<_>>(), vec!["shop/Order.avsc", "shop/Status.avsc"]);
+        let fields = &files[0].1["fields"];
+        assert_eq!(fields[0], json!({
+            "name": "status",
+            "type": {"type": "enum", "name": "Status", "namespace": "shop", "symbols": ["PENDING", "SHIPPED"]},
+            "default": "PENDING",
+        }));
+        assert_eq!(fields[1], json!({"name": "previous", "type": ["null", "shop.Status"], "default": null}));
+        assert_eq!(fields[2]["type"]["items"]["name"], "OrderItem");
+        assert_eq!(fields[2]["type"]["items"]["fields"][1]["type"], "shop.Order");
+        assert_eq!(files[1].1["type"], "enum");
+    }
+
+    #[test]
+    fn test_map_entries() {
+        let input = r#"
+        namespace test;
+        schema Counter {
+            counts CountsEntry[] schema {
+                key string
+                value int
+            }
+        }
+        "#;
+        let files = json_files(input, AvroGenerator::generate);
+        assert_eq!(files[0].1["fields"][0]["type"], json!({"type": "map", "values": "int"}));
+    }
+
+    #[test]
+    fn test_basic_types() {
+        let input = r#"
+        namespace test;
+        schema Reading {
+            count long
+            ratio float
+            amount decimal(10, 2)
+            day date
+            at time
+            wait duration
+            id uuid
+            payload bytes
+            counts map<string, int?>
+        }
+        "#;
+        let files = json_files(input, AvroGenerator::generate);
+
+        let types: Vec<&Value> = files[0].1["fields"].as_array().unwrap().iter().map(|field| &field["type"]).collect();
+        assert_eq!(types, vec![
+            &json!("long"),
+            &json!("float"),
+            &json!({"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}),
+            &json!({"type": "int", "logicalType": "date"}),
+            &json!({"type": "long", "logicalType": "time-micros"}),
+            &json!("bytes"),
+            &json!({"type": "string", "logicalType": "uuid"}),
+            &json!("bytes"),
+            &json!({"type": "map", "values": ["null", "int"]}),
+        ]);
+        AvroGenerator::validate(&files[0].1).expect("Generated schema should be valid Avro");
+    }
+
+    #[test]
+    fn test_aliases_and_unions() {
+        let input = r#"
+        namespace test;
+        type Sku = string;
+        type Size = union<int, string>;
+        schema Item {
+            sku Sku
+            related Sku[]?
+            size Size?
+            weight union<decimal(5, 2), float>
+            same union<Sku, string>
+        }
+        "#;
+        let files = json_files(input, AvroGenerator::generate);
+
+        let types: Vec<&Value> = files[0].1["fields"].as_array().unwrap().iter().map(|field| &field["type"]).collect();
+        assert_eq!(types, vec![
+            &json!("string"),
+            &json!(["null", {"type": "array", "items": "string"}]),
+            &json!(["null", "int", "string"]),
+            &json!([{"type": "bytes", "logicalType": "decimal", "precision": 5, "scale": 2}, "float"]),
+            &json!("string"),
+        ]);
+        AvroGenerator::validate(&files[0].1).expect("Generated schema should be valid Avro");
+    }
+
+    #[test]
+    fn test_round_trip_unions() {
+        let avsc = r#"{
+            "type": "record",
+            "name": "Measure",
+            "namespace": "test",
+            "fields": [
+                {"name": "value", "type": ["int", "string"]},
+                {"name": "label", "type": ["null", "string", "long"], "default": null}
+            ]
+        }"#;
+        let namespaces = AvroConverter::convert(avsc).expect("Failed to convert Avro schema");
+        let schemata = SchemataGenerator::new().unwrap().render(&namespaces).unwrap();
+        let files = json_files(&schemata, AvroGenerator::generate);
+
+        let types: Vec<&Value> = files[0].1["fields"].as_array().unwrap().iter().map(|field| &field["type"]).collect();
+        assert_eq!(types, vec![&json!(["int", "string"]), &json!(["null", "string", "long"])]);
+        AvroGenerator::validate(&files[0].1).expect("Generated schema should be valid Avro");
+    }
+
+    #[test]
+    fn test_validate() {
+        let recursive = json!({
+            "type": "record",
+            "name": "Node",
+            "namespace": "test",
+            "fields": [{"name": "next", "type": ["null", "test.Node"], "default": null}],
+        });
+        assert!(AvroGenerator::validate(&recursive).is_ok());
+
+        let missing_symbols = json!({"type": "enum", "name": "Status"});
+        assert!(AvroGenerator::validate(&missing_symbols).is_err());
+
+        // Avro allows several named types in a union, which `avro-rs` does not
+        let records = json!({
+            "type": "record",
+            "name": "Order",
+            "namespace": "test",
+            "fields": [{"name": "item", "type": [
+                {"type": "record", "name": "Book", "fields": [{"name": "isbn", "type": "string"}]},
+                {"type": "record", "name": "Dvd", "fields": [{"name": "minutes", "type": "int"}]},
+                "test.Order",
+            ]}],
+        });
+        assert!(AvroGenerator::validate(&records).is_ok());
+        let invalid_branch = json!(["int", "test.Node", {"type": "enum", "name": "Status"}]);
+        assert!(AvroGenerator::validate(&invalid_branch).is_err());
+        let duplicate = json!(["int", "string", "int"]);
+        assert!(AvroGenerator::validate(&duplicate).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::avro::{AvroConverter, AvroIdlParser};
+
+    #[test]
+    fn test_parse_protocol() {
+        let input = r#"
+        /** Shop records */
+        @namespace("com.example.shop")
+        protocol Shop {
+            import idl "common.avdl";
+
+            /** Supported currencies */
+            enum Currency { EUR, USD } = EUR;
+
+            fixed Hash(16);
+
+            // Messages are skipped
+            record Price {
+                /** Amount in minor units */
+                decimal(10, 2) amount;
+                Currency currency = "EUR";
+                union { null, string } note = null;
+                array<string> tags, labels;
+                map<long> counters;
+                @logicalType("timestamp-micros") long updated_at;
+                timestamp_ms created_at;
+                string? `error`;
+            }
+
+            Price quote(string sku) throws QuoteError;
+            void ping() oneway;
+        }
+        "#;
+        let protocol = AvroIdlParser::parse(input).expect("Failed to parse IDL");
+
+        assert_eq!(protocol["protocol"], "Shop");
+        assert_eq!(protocol["namespace"], "com.example.shop");
+        assert_eq!(protocol["doc"], "Shop records");
+
+        let types = protocol["types"].as_array().unwrap();
+        assert_eq!(types[0], json!({
+            "type": "enum", "name": "Currency", "symbols": ["EUR", "USD"], "default": "EUR", "doc": "Supported currencies",
+        }));
+        assert_eq!(types[1], json!({ "type": "fixed", "name": "Hash", "size": 16 }));
+        assert_eq!(types[2]["fields"], json!([
+            {"name": "amount", "doc": "Amount in minor units", "type": {"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}},
+            {"name": "currency", "type": "Currency", "default": "EUR"},
+            {"name": "note", "type": ["null", "string"], "default": null},
+            {"name": "tags", "type": {"type": "array", "items": "string"}},
+            {"name": "labels", "type": {"type": "array", "items": "string"}},
+            {"name": "counters", "type": {"type": "map", "values": "long"}},
+            {"name": "updated_at", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+            {"name": "created_at", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+            {"name": "error", "type": ["null", "string"]},
+        ]));
+    }
+
+    #[test]
+    fn test_convert_idl() {
+        let input = r#"
+        @namespace("com.example.people")
+        protocol People {
+            record User {
+                long id;
+                string? email;
+                @namespace("com.example.geo") record Address { string city; }
+            }
+        }
+        "#;
+        let result = AvroIdlParser::parse(input);
+        assert!(result.is_err(), "Nested record declarations are not valid IDL");
+
+        let input = r#"
+        @namespace("com.example.people")
+        protocol People {
+            @namespace("com.example.geo")
+            record Address { string city; }
+
+            /** A registered user */
+            record User {
+                long id;
+                string? email;
+                com.example.geo.Address address;
+            }
+        }
+        "#;
+        let namespaces = AvroConverter::convert_value(&AvroIdlParser::parse(input).unwrap()).unwrap();
+
+        assert_eq!(namespaces.iter().map(|ns| ns.name.as_str()).collect::<Vec<_>>(), vec!["com.example.geo", "com.example.people"]);
+        let user = &namespaces[1].schemas[0];
+        assert_eq!(user.comment.as_deref(), Some("A registered user"));
+        assert_eq!(user.fields.iter().map(|f| (f.type_name.as_str(), f.nullable)).collect::<Vec<_>>(), vec![
+            ("long", false),
+            ("string", true),
+            ("com.example.geo.Address", false),
+        ]);
+    }
+
+    #[test]
+    fn test_syntax_error_diagnostic() {
+        use crate::diagnostics::Diagnostic;
+
+        let input = "protocol P {\n    record User {\n        string name\n    }\n}\n";
+        let error = AvroIdlParser::parse(input).expect_err("Parsing should fail");
+        let diagnostic = error.downcast_ref::<Diagnostic>().expect("Expected a diagnostic").clone();
+
+        let diagnostic = diagnostic.with_source("user.avdl", input);
+        assert_eq!(diagnostic.message, "Syntax error in .avdl file");
+        assert_eq!(diagnostic.line, Some(3));
+    }
+}
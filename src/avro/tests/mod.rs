@@ -0,0 +1,6 @@
+#[cfg(test)]
+mod converter_tests;
+#[cfg(test)]
+mod generator_tests;
+#[cfg(test)]
+mod idl_tests;
\ No newline at end of file
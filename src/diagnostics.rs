@@ -0,0 +1,235 @@
+use std::fmt;
+use serde::Serialize;
+
+/// A byte range in a source file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A message about an input file, optionally pointing at the offending source.
+///
+/// `line` and `column` are 1-based and filled in by [`Diagnostic::with_source`], since most
+/// producers only know byte offsets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<String>,
+    pub span: Option<Span>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Short text printed under the highlighted source.
+    pub label: Option<String>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+/// Implemented by error types that can describe themselves as a [`Diagnostic`].
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self) -> Diagnostic;
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            file: None,
+            span: None,
+            line: None,
+            column: None,
+            label: None,
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Records the file name and computes `line`/`column` for the span against `source`.
+    pub fn with_source(mut self, file: impl Into<String>, source: &str) -> Self {
+        self.file = Some(file.into());
+        if let Some(span) = self.span {
+            let span = Self::trim_span(span, source);
+            let (line, column) = Self::line_col(source, span.start);
+            self.span = Some(span);
+            self.line = Some(line);
+            self.column = Some(column);
+        }
+        self
+    }
+
+    /// Renders the diagnostic with an annotated excerpt of `source`:
+    ///
+    /// ```text
+    /// error: Unknown type `Profle`
+    ///  --> user.schema:4:13
+    ///   |
+    /// 4 |     profile Profle?
+    ///   |             ^^^^^^ not declared in any namespace
+    ///   |
+    ///   = help: did you mean `Profile`?
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+
+        let Some(span) = self.span.map(|s| Self::trim_span(s, source)) else {
+            if let Some(file) = &self.file {
+                out.push_str(&format!(" --> {}\n", file));
+            }
+            self.render_footer(&mut out, 1);
+            return out;
+        };
+
+        let (line, column) = Self::line_col(source, span.start);
+        let gutter = line.to_string().len();
+        let pad = " ".repeat(gutter);
+
+        out.push_str(&format!("{}--> {}:{}:{}\n", pad, self.file.as_deref().unwrap_or("<input>"), line, column));
+        out.push_str(&format!("{} |\n", pad));
+
+        let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[span.start..].find('\n').map_or(source.len(), |i| span.start + i);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        out.push_str(&format!("{} | {}\n", line, text));
+
+        // Spans running past the end of the line are underlined up to the line end
+        let underline_end = span.end.min(line_start + text.len()).max(span.start + 1);
+        let offset = source[line_start..span.start].chars().count();
+        let width = source.get(span.start..underline_end).map_or(1, |s| s.chars().count().max(1));
+        out.push_str(&format!("{} | {}{}", pad, " ".repeat(offset), "^".repeat(width)));
+        if let Some(label) = &self.label {
+            out.push_str(&format!(" {}", label));
+        }
+        out.push('\n');
+
+        self.render_footer(&mut out, gutter);
+        out
+    }
+
+    fn render_footer(&self, out: &mut String, gutter: usize) {
+        if self.notes.is_empty() && self.help.is_none() {
+            return;
+        }
+        let pad = " ".repeat(gutter);
+        out.push_str(&format!("{} |\n", pad));
+        for note in &self.notes {
+            out.push_str(&format!("{} = note: {}\n", pad, note));
+        }
+        if let Some(help) = &self.help {
+            out.push_str(&format!("{} = help: {}\n", pad, help));
+        }
+    }
+
+    /// 1-based line and column (in characters) of a byte offset.
+    pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+        let offset = Self::clamp(source, offset);
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    /// Moves the start of a span past leading whitespace, which producers working on token
+    /// boundaries (like the XML reader) tend to include.
+    fn trim_span(span: Span, source: &str) -> Span {
+        let start = Self::clamp(source, span.start);
+        let end = Self::clamp(source, span.end.max(start));
+        let skipped = source[start..end].len() - source[start..end].trim_start().len();
+        if start + skipped < end {
+            Span::new(start + skipped, end)
+        } else {
+            Span::new(start, end)
+        }
+    }
+
+    fn clamp(source: &str, offset: usize) -> usize {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        match (&self.file, self.line, self.column) {
+            (Some(file), Some(line), Some(column)) => write!(f, " ({}:{}:{})", file, line, column),
+            (None, Some(line), Some(column)) => write!(f, " ({}:{})", line, column),
+            (Some(file), _, _) => write!(f, " ({})", file),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Renders any error produced while processing `file`. Errors that know their location are
+/// shown as annotated snippets; anything else falls back to a plain `error:` line.
+pub fn report(error: &(dyn std::error::Error + 'static), file: &str, source: &str) -> String {
+    let diagnostics: Vec<Diagnostic> = if let Some(diagnostic) = error.downcast_ref::<Diagnostic>() {
+        vec![diagnostic.clone()]
+    } else if let Some(error) = error.downcast_ref::<crate::xsd::XsdParsingError>() {
+        vec![error.to_diagnostic()]
+    } else if let Some(errors) = error.downcast_ref::<crate::schemata::analyzer::SemanticErrors>() {
+        errors.0.iter().map(|e| e.to_diagnostic()).collect()
+    } else {
+        vec![Diagnostic::error(error.to_string())]
+    };
+
+    diagnostics.into_iter()
+        .map(|d| d.with_source(file, source).render(source))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
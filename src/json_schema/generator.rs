@@ -0,0 +1,256 @@
+use serde_json::{json, Map, Value};
+
+use crate::diagnostics::Diagnostic;
+use crate::output::GeneratedFile;
+use crate::schemata::analyzer::{ResolvedType, Symbol, SymbolTable};
+use crate::schemata::ast::{Annotation, Document, Enum, Field, Namespace, Schema, TypeRef};
+use crate::schemata::type_mapping;
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Restriction facets and the JSON Schema keywords they become on numeric and string values.
+/// Bounds that are not numbers, like dates, keep their facet name as an `x-` keyword.
+const FACETS: &[(&str, &str)] = &[
+    ("pattern", "pattern"),
+    ("minLength", "minLength"),
+    ("maxLength", "maxLength"),
+    ("minInclusive", "minimum"),
+    ("maxInclusive", "maximum"),
+    ("minExclusive", "exclusiveMinimum"),
+    ("maxExclusive", "exclusiveMaximum"),
+    ("totalDigits", "x-totalDigits"),
+    ("fractionDigits", "x-fractionDigits"),
+];
+
+/// Basic types whose `@default` values JSON can write.
+const DEFAULT_TYPES: &[&str] = &["int", "long", "float", "double", "decimal", "bool", "string", "date", "time", "duration", "uuid", "uri"];
+
+/// Generates JSON Schema (draft 2020-12) documents from a checked Schemata document, one file
+/// per namespace with every schema and enum under `$defs`.
+///
+/// Documents only hold definitions; payloads are validated against a definition such as
+/// `com.example.people.schema.json#/$defs/User`. References to other namespaces point at the
+/// sibling document of that namespace.
+pub struct JsonSchemaGenerator<'a> {
+    namespace: &'a str,
+    symbols: &'a SymbolTable,
+}
+
+impl<'a> JsonSchemaGenerator<'a> {
+    pub fn generate(document: &Document, symbols: &SymbolTable) -> Result<Vec<GeneratedFile>, Box<dyn std::error::Error>> {
+        symbols.expand_aliases(document).namespaces.iter()
+            .map(|namespace| {
+                let schema = JsonSchemaGenerator { namespace: &namespace.name, symbols }.get_document(namespace)?;
+                Ok(GeneratedFile {
+                    path: Self::file_path(&namespace.name),
+                    content: serde_json::to_string_pretty(&schema)? + "\n",
+                })
+            })
+            .collect()
+    }
+
+    /// `com.example.people` is written to `com.example.people.schema.json`, so that documents
+    /// can refer to each other by file name.
+    pub fn file_path(namespace: &str) -> String {
+        format!("{}.schema.json", namespace)
+    }
+
+    pub(crate) fn get_document(&self, namespace: &Namespace) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut defs = Map::new();
+        for schema in &namespace.schemas {
+            self.add_object(&mut defs, schema.name.as_deref().unwrap_or_default(), schema)?;
+        }
+        for enumeration in &namespace.enums {
+            defs.insert(enumeration.name.clone(), Self::get_enum(enumeration));
+        }
+
+        Ok(json!({
+            "$schema": DRAFT,
+            "$id": Self::file_path(&namespace.name),
+            "title": namespace.name,
+            "$defs": defs,
+        }))
+    }
+
+    /// Adds the definition of `schema`, followed by definitions for its inline schemas.
+    fn add_object(&self, defs: &mut Map<String, Value>, name: &str, schema: &Schema) -> Result<(), Box<dyn std::error::Error>> {
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("object"));
+        if let Some(comment) = &schema.comment {
+            object.insert("description".to_string(), json!(comment));
+        }
+
+        let mut properties = Map::new();
+        for field in &schema.fields {
+            properties.insert(field.name.clone(), self.get_property(field)?);
+        }
+        object.insert("properties".to_string(), Value::Object(properties));
+
+        let required: Vec<&str> = schema.fields.iter()
+            .filter(|f| !f.type_ref.nullable)
+            .map(|f| f.name.as_str())
+            .collect();
+        if !required.is_empty() {
+            object.insert("required".to_string(), json!(required));
+        }
+        // Schemas open to content they do not declare, like XSD wildcards, accept any other property
+        let extensible = schema.annotations.iter().any(|a| a.name == "extensible");
+        object.insert("additionalProperties".to_string(), json!(extensible));
+        defs.insert(name.to_string(), Value::Object(object));
+
+        for field in &schema.fields {
+            if let Some(inline) = &field.inline_schema {
+                if type_mapping::map_entry(field).is_none() {
+                    self.add_object(defs, &field.type_ref.name, inline)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_enum(enumeration: &Enum) -> Value {
+        let mut schema = Map::new();
+        schema.insert("type".to_string(), json!("string"));
+        if let Some(comment) = &enumeration.comment {
+            schema.insert("description".to_string(), json!(comment));
+        }
+        schema.insert("enum".to_string(), json!(enumeration.values));
+        Value::Object(schema)
+    }
+
+    fn get_property(&self, field: &Field) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut schema = match type_mapping::map_entry(field) {
+            Some(value) => json!({ "type": "object", "additionalProperties": self.value_schema(value)? }),
+            None => self.value_schema(field)?,
+        };
+        if field.type_ref.array && type_mapping::map_entry(field).is_none() {
+            let mut array = Map::new();
+            array.insert("type".to_string(), json!("array"));
+            array.insert("items".to_string(), schema);
+            if let Some(min) = Self::size(field, "min") {
+                array.insert("minItems".to_string(), json!(min));
+            }
+            if let Some(max) = Self::size(field, "max") {
+                array.insert("maxItems".to_string(), json!(max));
+            }
+            schema = Value::Object(array);
+        }
+        if field.type_ref.nullable {
+            schema = Self::nullable(schema);
+        }
+
+        let Value::Object(mut property) = schema else { unreachable!("property schemas are objects") };
+        if let Some(comment) = &field.comment {
+            property.insert("description".to_string(), json!(comment));
+        }
+        if let Some(value) = field.annotations.iter().find(|a| a.name == "default").and_then(|a| a.arg("value")) {
+            if let Some(default) = type_mapping::default_value(self.namespace, field, value.as_str(), self.symbols, DEFAULT_TYPES, "JSON") {
+                property.insert("default".to_string(), default);
+            }
+        }
+        for (annotation, keyword) in [("id", "x-id"), ("unique", "x-unique")] {
+            if field.annotations.iter().any(|a| a.name == annotation) {
+                property.insert(keyword.to_string(), json!(true));
+            }
+        }
+        Ok(Value::Object(property))
+    }
+
+    /// The schema of a single value of the field, ignoring `[]` and `?`, with its facets.
+    fn value_schema(&self, field: &Field) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut schema = self.type_schema(&field.type_ref)?;
+        if let Value::Object(object) = &mut schema {
+            for annotation in &field.annotations {
+                Self::add_facet(object, annotation);
+            }
+        }
+        Ok(schema)
+    }
+
+    /// The schema of a single value of a type, ignoring `[]` and `?`. Maps become objects, whatever
+    /// their key type, as JSON writes every key as a string. Unions accept a value of any of
+    /// their members.
+    fn type_schema(&self, type_ref: &TypeRef) -> Result<Value, Box<dyn std::error::Error>> {
+        let resolved = self.symbols.resolve(self.namespace, type_ref)
+            .map_err(|_| Diagnostic::error(format!("Unresolved type `{}`", type_ref.name)).with_span(type_ref.span))?;
+
+        Ok(match (resolved, &type_ref.map, &type_ref.union) {
+            (ResolvedType::Basic(_), Some(map), _) => json!({ "type": "object", "additionalProperties": self.nested_schema(&map.1)? }),
+            (ResolvedType::Basic(_), None, Some(members)) => {
+                let members = members.iter().map(|member| self.nested_schema(member)).collect::<Result<Vec<_>, _>>()?;
+                json!({ "anyOf": members })
+            },
+            (ResolvedType::Basic(name), None, None) => match type_mapping::basic_type(&name) {
+                Some(basic) => serde_json::from_str(basic.json_schema)?,
+                None => json!({ "type": "string" }),
+            },
+            (ResolvedType::Schema(symbol) | ResolvedType::Enum(symbol), _, _) => json!({ "$ref": self.reference(&symbol) }),
+        })
+    }
+
+    /// The schema of a map value or union member, with its `[]` and `?`.
+    fn nested_schema(&self, type_ref: &TypeRef) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut schema = self.type_schema(type_ref)?;
+        if type_ref.array {
+            schema = json!({ "type": "array", "items": schema });
+        }
+        if type_ref.nullable {
+            schema = Self::nullable(schema);
+        }
+        Ok(schema)
+    }
+
+    fn add_facet(schema: &mut Map<String, Value>, annotation: &Annotation) {
+        let Some(value) = annotation.arg("value").map(|v| v.as_str()) else {
+            return;
+        };
+        if annotation.name == "length" {
+            if let Ok(length) = value.parse::<u64>() {
+                schema.insert("minLength".to_string(), json!(length));
+                schema.insert("maxLength".to_string(), json!(length));
+            }
+            return;
+        }
+        let Some((facet, keyword)) = FACETS.iter().find(|(facet, _)| *facet == annotation.name) else {
+            return;
+        };
+
+        if *facet == "pattern" {
+            schema.insert(keyword.to_string(), json!(value));
+        } else if let Ok(number) = value.parse::<u64>() {
+            schema.insert(keyword.to_string(), json!(number));
+        } else if let Ok(number) = value.parse::<f64>() {
+            schema.insert(keyword.to_string(), json!(number));
+        } else {
+            schema.insert(format!("x-{}", facet), json!(value));
+        }
+    }
+
+    fn reference(&self, symbol: &Symbol) -> String {
+        if symbol.namespace == self.namespace {
+            format!("#/$defs/{}", symbol.name)
+        } else {
+            format!("{}#/$defs/{}", Self::file_path(&symbol.namespace), symbol.name)
+        }
+    }
+
+    /// Adds `null` to the allowed types, or wraps schemas without a `type` in `anyOf`.
+    fn nullable(schema: Value) -> Value {
+        match schema {
+            Value::Object(mut object) if object.get("type").is_some_and(Value::is_string) => {
+                let type_name = object.remove("type").unwrap_or_default();
+                object.insert("type".to_string(), json!([type_name, "null"]));
+                Value::Object(object)
+            },
+            other => json!({ "anyOf": [other, { "type": "null" }] }),
+        }
+    }
+
+    /// The `min` or `max` bound of an array field from `@size(min = .., max = ..)`.
+    fn size(field: &Field, bound: &str) -> Option<u64> {
+        field.annotations.iter()
+            .find(|a| a.name == "size")
+            .and_then(|a| a.args.iter().find(|a| a.name.as_deref() == Some(bound)))
+            .and_then(|a| a.value.as_str().parse().ok())
+    }
+}
@@ -0,0 +1,3 @@
+mod generator;
+mod tests;
+pub use generator::JsonSchemaGenerator;
\ No newline at end of file
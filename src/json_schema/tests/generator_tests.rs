@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_schema::JsonSchemaGenerator;
+    use crate::schemata::tests::support::json_files;
+    use crate::schemata::SchemataGenerator;
+    use crate::xsd::XsdParser;
+
+    #[test]
+    fn test_generate_document() {
+        let input = r#"
+        namespace com.example.people;
+
+        # A registered user
+        schema User {
+            id int @id
+            email string? @unique
+            score float @default(value = "1.5")
+            createdAt datetime @default(value = "now()")
+            tags string[]
+            profile Profile?
+            status Status
+        }
+
+        schema Profile {
+            bio string
+        }
+
+        enum Status { ACTIVE, BANNED }
+        "#;
+        let files = json_files(input, JsonSchemaGenerator::generate);
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "com.example.people.schema.json");
+        assert_eq!(files[0].1, json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "com.example.people.schema.json",
+            "title": "com.example.people",
+            "$defs": {
+                "User": {
+                    "type": "object",
+                    "description": "A registered user",
+                    "properties": {
+                        "id": {"type": "integer", "x-id": true},
+                        "email": {"type": ["string", "null"], "x-unique": true},
+                        "score": {"type": "number", "default": 1.5},
+                        "createdAt": {"type": "string", "format": "date-time"},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "profile": {"anyOf": [{"$ref": "#/$defs/Profile"}, {"type": "null"}]},
+                        "status": {"$ref": "#/$defs/Status"},
+                    },
+                    "required": ["id", "score", "createdAt", "tags", "status"],
+                    "additionalProperties": false,
+                },
+                "Profile": {
+                    "type": "object",
+                    "properties": {"bio": {"type": "string"}},
+                    "required": ["bio"],
+                    "additionalProperties": false,
+                },
+                "Status": {"type": "string", "enum": ["ACTIVE", "BANNED"]},
+            },
+        }));
+    }
+
+    #[test]
+    fn test_references_inline_schemas_and_maps() {
+        let input = r#"
+        namespace com.example.people;
+        schema User { name string }
+
+        namespace com.example.shop;
+        schema Order {
+            buyer User
+            items OrderItem[] @size(min = 1) schema {
+                sku string
+            }
+            counts CountsEntry[] schema {
+                key string
+                value int
+            }
+        }
+        "#;
+        let files = json_files(input, JsonSchemaGenerator::generate);
+
+        let defs = &files[1].1["$defs"];
+        assert_eq!(defs["Order"]["properties"]["buyer"], json!({"$ref": "com.example.people.schema.json#/$defs/User"}));
+        assert_eq!(defs["Order"]["properties"]["items"], json!({"type": "array", "items": {"$ref": "#/$defs/OrderItem"}, "minItems": 1}));
+        assert_eq!(defs["Order"]["properties"]["counts"], json!({"type": "object", "additionalProperties": {"type": "integer"}}));
+        assert_eq!(defs["OrderItem"]["required"], json!(["sku"]));
+        assert!(defs.get("CountsEntry").is_none());
+    }
+
+    #[test]
+    fn test_basic_types() {
+        let input = r#"
+        namespace test;
+        schema Reading {
+            count long
+            amount decimal(10, 2)
+            day date
+            id uuid
+            payload bytes
+            tags map<string, string[]?>
+        }
+        "#;
+        let files = json_files(input, JsonSchemaGenerator::generate);
+
+        let properties = &files[0].1["$defs"]["Reading"]["properties"];
+        assert_eq!(properties["count"], json!({"type": "integer"}));
+        assert_eq!(properties["amount"], json!({"type": "number"}));
+        assert_eq!(properties["day"], json!({"type": "string", "format": "date"}));
+        assert_eq!(properties["id"], json!({"type": "string", "format": "uuid"}));
+        assert_eq!(properties["payload"], json!({"type": "string", "contentEncoding": "base64"}));
+        assert_eq!(properties["tags"], json!({
+            "type": "object",
+            "additionalProperties": {"type": ["array", "null"], "items": {"type": "string"}},
+        }));
+    }
+
+    #[test]
+    fn test_array_sizes() {
+        let input = r#"
+        namespace test;
+        schema Order {
+            lines string[] @size(min = 1, max = 10)
+            coupons string[] @size(min = 1)
+        }
+        "#;
+        let files = json_files(input, JsonSchemaGenerator::generate);
+
+        let properties = &files[0].1["$defs"]["Order"]["properties"];
+        assert_eq!(properties["lines"], json!({"type": "array", "items": {"type": "string"}, "minItems": 1, "maxItems": 10}));
+        assert_eq!(properties["coupons"], json!({"type": "array", "items": {"type": "string"}, "minItems": 1}));
+    }
+
+    #[test]
+    fn test_extensible_schemas_allow_other_properties() {
+        let input = r#"
+        namespace test;
+        schema Envelope @extensible {
+            id string
+        }
+        schema Closed {
+            id string
+        }
+        "#;
+        let files = json_files(input, JsonSchemaGenerator::generate);
+
+        assert_eq!(files[0].1["$defs"]["Envelope"]["additionalProperties"], json!(true));
+        assert_eq!(files[0].1["$defs"]["Closed"]["additionalProperties"], json!(false));
+    }
+
+    #[test]
+    fn test_aliases_and_unions() {
+        let input = r#"
+        namespace test;
+        type Sku = string @maxLength(value = "9");
+        type Size = union<int, Sku[]>;
+        schema Item {
+            sku Sku
+            size Size
+        }
+        "#;
+        let files = json_files(input, JsonSchemaGenerator::generate);
+
+        let properties = &files[0].1["$defs"]["Item"]["properties"];
+        assert_eq!(properties["sku"], json!({"type": "string", "maxLength": 9}));
+        assert_eq!(properties["size"], json!({"anyOf": [
+            {"type": "integer"},
+            {"type": "array", "items": {"type": "string"}},
+        ]}));
+    }
+
+    #[test]
+    fn test_xsd_facets() {
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+            <xs:simpleType name="Sku">
+                <xs:restriction base="string">
+                    <xs:pattern value="[A-Z]{3}-\d+"/>
+                    <xs:maxLength value="12"/>
+                </xs:restriction>
+            </xs:simpleType>
+            <xs:complexType name="Item">
+                <xs:sequence>
+                    <xs:element name="sku" type="Sku"/>
+                    <xs:element name="quantity">
+                        <xs:simpleType>
+                            <xs:restriction base="int">
+                                <xs:minInclusive value="1"/>
+                                <xs:maxExclusive value="100"/>
+                            </xs:restriction>
+                        </xs:simpleType>
+                    </xs:element>
+                </xs:sequence>
+            </xs:complexType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let schemata = SchemataGenerator::new().unwrap().generate(schema).expect("Failed to generate Schemata");
+        let files = json_files(&schemata, JsonSchemaGenerator::generate);
+
+        let properties = &files[0].1["$defs"]["Item"]["properties"];
+        assert_eq!(properties["sku"], json!({"type": "string", "pattern": "[A-Z]{3}-\\d+", "maxLength": 12}));
+        assert_eq!(properties["quantity"], json!({"type": "integer", "minimum": 1, "exclusiveMaximum": 100}));
+        assert!(files[0].1["$defs"].get("Sku").is_none(), "Facet-only simple types are not enums");
+    }
+}
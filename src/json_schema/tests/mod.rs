@@ -0,0 +1,2 @@
+#[cfg(test)]
+mod generator_tests;
\ No newline at end of file
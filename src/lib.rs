@@ -0,0 +1,7 @@
+pub mod avro;
+pub mod diagnostics;
+pub mod json_schema;
+pub mod output;
+pub mod proto;
+pub mod schemata;
+pub mod xsd;
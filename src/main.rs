@@ -1,74 +1,463 @@
-mod xsd;
-mod schemata;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use std::fs::File;
-use std::io::BufReader;
-use clap::Parser;
-use xsd::XsdParser;
-use crate::schemata::SchemataGenerator;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use log::LevelFilter;
+use schemata::avro::{AvroConverter, AvroGenerator, AvroIdlParser};
+use schemata::diagnostics;
+use schemata::json_schema::JsonSchemaGenerator;
+use schemata::output::GeneratedFile;
+use schemata::proto::{ProtoConverter, ProtoGenerator, ProtoParser};
+use schemata::schemata::ast::Document;
+use schemata::schemata::analyzer::SymbolTable;
+use schemata::schemata::{Groups, Inheritance, InlineSchemas, SchemataDiff, SchemataFormatter, SchemataGenerator, SchemataLinter, SchemataParser, SemanticAnalyzer};
+use schemata::xsd::{XsdLoadError, XsdLoader};
 
+/// Converts between schema languages through the Schemata format.
+///
+/// Inputs and outputs may be `-` for stdin and stdout. The exit code is 0 on success and 1 when
+/// a command fails or finds problems: errors, lint warnings, differences or unformatted input.
 #[derive(Parser)]
-#[clap(version = "1.0", author = "Michael Bolton", about = "...")]
+#[clap(name = "schemata", version)]
 struct Cli {
-    #[clap(short, long)]
-    name: String,
+    /// Log more details; repeat for more (-v info, -vv debug, -vvv trace)
+    #[clap(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Only log errors
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    #[clap(subcommand)]
+    command: Command,
 }
 
-#[derive(Parser)]
-enum Commands {
+#[derive(Subcommand)]
+enum Command {
     Convert(Convert),
+    Validate(Validate),
+    Fmt(Fmt),
+    Diff(Diff),
+    Lint(Lint),
+    Generate(Generate),
 }
 
+/// Converts a schema from one format to another
 #[derive(Parser)]
 struct Convert {
     #[clap(value_parser)]
     input: String,
-    #[clap(short = 'f', long = "format", value_enum)]
-    format: InputFormat,
-    #[clap(short = 'o', long = "output", value_parser)]
+    /// Format of the input; inferred from the file extension when omitted
+    #[clap(long, value_enum)]
+    from: Option<InputFormat>,
+    #[clap(long, value_enum, default_value = "schemata")]
+    to: OutputFormat,
+    #[clap(flatten)]
+    xsd: XsdOptions,
+    /// Output file, or directory for formats that produce several files
+    #[clap(short = 'o', long = "output", value_parser, default_value = "-")]
+    output: String,
+}
+
+/// Checks that a schema parses and that every Schemata type it describes resolves
+#[derive(Parser)]
+struct Validate {
+    #[clap(value_parser)]
+    input: String,
+    /// Format of the input; inferred from the file extension when omitted
+    #[clap(long, value_enum)]
+    from: Option<InputFormat>,
+    #[clap(flatten)]
+    xsd: XsdOptions,
+}
+
+/// Prints a Schemata file with canonical formatting
+#[derive(Parser)]
+struct Fmt {
+    #[clap(value_parser)]
+    input: String,
+    #[clap(short = 'o', long = "output", value_parser, default_value = "-")]
+    output: String,
+    /// Only check whether the input is formatted
+    #[clap(long)]
+    check: bool,
+}
+
+/// Lists the schemas, enums and fields that differ between two Schemata files
+#[derive(Parser)]
+struct Diff {
+    #[clap(value_parser)]
+    old: String,
+    #[clap(value_parser)]
+    new: String,
+}
+
+/// Reports style problems in a Schemata file
+#[derive(Parser)]
+struct Lint {
+    #[clap(value_parser)]
+    input: String,
+}
+
+/// Generates files in another schema language from a Schemata file
+#[derive(Parser)]
+struct Generate {
+    #[clap(value_parser)]
+    input: String,
+    #[clap(short = 't', long = "to", alias = "format", short_alias = 'f', value_enum)]
+    to: OutputFormat,
+    /// Directory the generated files are written to
+    #[clap(short = 'o', long = "output", value_parser, default_value = "-")]
     output: String,
 }
 
-#[derive(clap::ValueEnum, Clone)]
+/// How XSD input is read
+#[derive(Parser, Default)]
+struct XsdOptions {
+    /// How XSD types derived by extension get the fields of their base type
+    #[clap(long, value_enum, default_value = "flatten")]
+    inheritance: InheritanceMode,
+    /// Where schemas of anonymous XSD types are written
+    #[clap(long, value_enum, default_value = "nested")]
+    inline_schemas: InlineSchemaMode,
+    /// How XSD model groups and attribute groups are written
+    #[clap(long, value_enum, default_value = "expand")]
+    groups: GroupMode,
+    /// Read a local file for an included or imported location, as LOCATION=PATH; may be repeated
+    #[clap(long, value_name = "LOCATION=PATH", value_parser = parse_catalog_entry)]
+    catalog: Vec<(String, PathBuf)>,
+}
+
+fn parse_catalog_entry(entry: &str) -> Result<(String, PathBuf), String> {
+    // Split at the last `=`, as URLs may contain one
+    entry.rsplit_once('=')
+        .map(|(location, path)| (location.to_string(), PathBuf::from(path)))
+        .ok_or_else(|| format!("expected LOCATION=PATH, found `{}`", entry))
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
 enum InputFormat {
-    Protobuf,
+    Schemata,
     Xsd,
+    Protobuf,
+    Avro,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Schemata,
+    Protobuf,
     Avro,
+    JsonSchema,
 }
 
-fn handle_xsd(input: &str, output: &str) -> Result<(), Box<dyn std::error::Error>> {
-    log::info!("Parsing the XSD file {}...", input);
-    let file = File::open(input)?;
-    let reader = BufReader::new(file);
-    let schema = XsdParser::parse(reader)?;
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Eq)]
+enum InheritanceMode {
+    /// Copy the fields of the base type into the derived schema
+    #[default]
+    Flatten,
+    /// Annotate the derived schema with `@extends(Base)`
+    Extends,
+}
 
-    log::info!("Generating Schemata...");
-    let generator = SchemataGenerator::new()?;
-    let schemata = generator.generate(schema)?;
+impl From<InheritanceMode> for Inheritance {
+    fn from(mode: InheritanceMode) -> Self {
+        match mode {
+            InheritanceMode::Flatten => Inheritance::Flatten,
+            InheritanceMode::Extends => Inheritance::Extends,
+        }
+    }
+}
 
-    log::info!("Writing the output to {}...", output);
-    std::fs::write(output, schemata)?;
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Eq)]
+enum InlineSchemaMode {
+    /// Write them inline, under the field they type
+    #[default]
+    Nested,
+    /// Write them as top-level schemas
+    Hoist,
+}
 
-    log::info!("Done!");
-    Ok(())
+impl From<InlineSchemaMode> for InlineSchemas {
+    fn from(mode: InlineSchemaMode) -> Self {
+        match mode {
+            InlineSchemaMode::Nested => InlineSchemas::Nested,
+            InlineSchemaMode::Hoist => InlineSchemas::Hoist,
+        }
+    }
 }
 
-fn main() {
-    let cli = Cli::parse();
-    env_logger::init();
-
-    match Commands::parse() {
-        Commands::Convert(convert) => {
-            match convert.format {
-                InputFormat::Xsd => {
-                    if let Err(e) = handle_xsd(&convert.input, &convert.output) {
-                        log::error!("Error: {}", e);
-                    }
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Eq)]
+enum GroupMode {
+    /// Write the content of a group in place of every reference to it
+    #[default]
+    Expand,
+    /// Write groups as schemas marked `@mixin`, included with `@include(Group)`
+    Mixins,
+}
+
+impl From<GroupMode> for Groups {
+    fn from(mode: GroupMode) -> Self {
+        match mode {
+            GroupMode::Expand => Groups::Expand,
+            GroupMode::Mixins => Groups::Mixins,
+        }
+    }
+}
+
+impl InputFormat {
+    fn infer(path: &str) -> Option<Self> {
+        match Path::new(path).extension()?.to_str()? {
+            "schema" => Some(InputFormat::Schemata),
+            "xsd" => Some(InputFormat::Xsd),
+            "proto" => Some(InputFormat::Protobuf),
+            "avsc" | "avdl" | "avpr" => Some(InputFormat::Avro),
+            _ => None,
+        }
+    }
+}
+
+/// An error and the source its spans point into.
+struct Failure {
+    error: Box<dyn std::error::Error>,
+    file: String,
+    source: String,
+}
+
+trait InSource<T> {
+    fn in_source(self, file: &str, source: &str) -> Result<T, Failure>;
+}
+
+impl<T, E: Into<Box<dyn std::error::Error>>> InSource<T> for Result<T, E> {
+    fn in_source(self, file: &str, source: &str) -> Result<T, Failure> {
+        self.map_err(|error| Failure {
+            error: error.into(),
+            file: file.to_string(),
+            source: source.to_string(),
+        })
+    }
+}
+
+/// A Schemata file or input converted to Schemata, with the name used in diagnostics.
+struct Input {
+    file: String,
+    source: String,
+    /// True when the source was converted from another format
+    converted: bool,
+}
+
+impl Input {
+    fn read(path: &str) -> Result<Self, Failure> {
+        let file = if path == "-" { "<stdin>".to_string() } else { path.to_string() };
+        let mut source = String::new();
+        let result = if path == "-" {
+            std::io::stdin().read_to_string(&mut source).map(|_| ())
+        } else {
+            std::fs::read_to_string(path).map(|content| source = content)
+        };
+        result.map_err(|e| format!("Cannot read {}: {}", file, e)).in_source(&file, "")?;
+        Ok(Self { file, source, converted: false })
+    }
+
+    /// Reads `path` and converts it to Schemata source.
+    fn read_as_schemata(path: &str, from: Option<InputFormat>, xsd: &XsdOptions) -> Result<Self, Failure> {
+        let input = Self::read(path)?;
+        let format = from.or_else(|| InputFormat::infer(path))
+            .ok_or_else(|| format!("Cannot tell the format of {}; pass it with --from", input.file))
+            .in_source(&input.file, "")?;
+        if format == InputFormat::Schemata {
+            return Ok(input);
+        }
+
+        log::info!("Converting {} to Schemata...", input.file);
+        let generator = SchemataGenerator::new().in_source(&input.file, "")?.with_inheritance(xsd.inheritance.into())
+            .with_inline_schemas(xsd.inline_schemas.into())
+            .with_groups(xsd.groups.into());
+        let source = match format {
+            InputFormat::Xsd => {
+                let loader = xsd.catalog.iter()
+                    .fold(XsdLoader::new(), |loader, (location, path)| loader.with_catalog_entry(location, path));
+                let root = (path != "-").then(|| Path::new(path));
+                // Errors in included documents point into their own source
+                let schemas = loader.load_source(&input.source, root).map_err(|error| match error.downcast::<XsdLoadError>() {
+                    Ok(failed) => Failure { error: failed.error, file: failed.file, source: failed.source },
+                    Err(error) => Failure { error, file: input.file.clone(), source: input.source.clone() },
+                })?;
+                generator.generate_all(&schemas)
+            },
+            InputFormat::Protobuf => {
+                let proto = ProtoParser::parse(&input.source).in_source(&input.file, &input.source)?;
+                for import in &proto.imports {
+                    log::warn!("Import {} is not followed; its types are referenced by name only", import);
                 }
-                _ => {
-                    log::error!("Unsupported format");
+                generator.render(&ProtoConverter::convert(&proto))
+            },
+            InputFormat::Avro => {
+                // Avro IDL is the only Avro syntax that does not start as JSON
+                let namespaces = if input.source.trim_start().starts_with(['{', '[', '"']) {
+                    AvroConverter::convert(&input.source)
+                } else {
+                    AvroIdlParser::parse(&input.source).and_then(|protocol| AvroConverter::convert_value(&protocol))
+                };
+                generator.render(&namespaces.in_source(&input.file, &input.source)?)
+            },
+            InputFormat::Schemata => unreachable!(),
+        };
+        Ok(Self {
+            file: format!("{} (converted to Schemata)", input.file),
+            source: source.in_source(&input.file, "")?,
+            converted: true,
+        })
+    }
+
+    fn parse(&self) -> Result<Document, Failure> {
+        SchemataParser::parse(&self.source).in_source(&self.file, &self.source)
+    }
+
+    fn analyze(&self) -> Result<(Document, SymbolTable), Failure> {
+        let document = self.parse()?;
+        let symbols = SemanticAnalyzer::analyze(&document).in_source(&self.file, &self.source)?;
+        Ok((document, symbols))
+    }
+}
+
+fn write_output(path: &str, content: &str) -> Result<(), Failure> {
+    let result = if path == "-" {
+        std::io::stdout().write_all(content.as_bytes())
+    } else {
+        std::fs::write(path, content)
+    };
+    result.map_err(|e| format!("Cannot write {}: {}", path, e)).in_source(path, "")
+}
+
+fn convert(input: &Input, to: OutputFormat, output: &str) -> Result<ExitCode, Failure> {
+    let (document, symbols) = input.analyze()?;
+    let files = match to {
+        OutputFormat::Schemata => {
+            return write_output(output, &SchemataFormatter::format(&document)).map(|_| ExitCode::SUCCESS);
+        },
+        OutputFormat::Protobuf => {
+            log::info!("Generating Protobuf...");
+            ProtoGenerator::new().and_then(|generator| generator.generate(&document, &symbols))
+        },
+        OutputFormat::Avro => {
+            log::info!("Generating Avro schemas...");
+            AvroGenerator::generate(&document, &symbols)
+        },
+        OutputFormat::JsonSchema => {
+            log::info!("Generating JSON Schema documents...");
+            JsonSchemaGenerator::generate(&document, &symbols)
+        },
+    };
+    write_files(&files.in_source(&input.file, &input.source)?, output)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Writes generated files below the `output` directory, or a single file to stdout.
+fn write_files(files: &[GeneratedFile], output: &str) -> Result<(), Failure> {
+    if output == "-" {
+        return match files {
+            [file] => write_output("-", &file.content),
+            _ => Err(format!("{} files were generated; pass a directory with --output", files.len())).in_source(output, ""),
+        };
+    }
+    for file in files {
+        log::info!("Writing {}...", file.path);
+        file.write_to(Path::new(output))
+            .map_err(|e| format!("Cannot write {}: {}", file.path, e))
+            .in_source(output, "")?;
+    }
+    Ok(())
+}
+
+/// Prints warnings, returning whether there were any.
+fn report_warnings(warnings: &[diagnostics::Diagnostic], input: &Input) -> bool {
+    for warning in warnings {
+        eprint!("{}", warning.clone().with_source(&input.file, &input.source).render(&input.source));
+    }
+    !warnings.is_empty()
+}
+
+fn run(command: &Command) -> Result<ExitCode, Failure> {
+    match command {
+        Command::Convert(args) => {
+            let input = Input::read_as_schemata(&args.input, args.from, &args.xsd)?;
+            if args.to == OutputFormat::Schemata && input.converted {
+                // Converted documents need not pass analysis. When they do not even parse, they
+                // are still written as rendered, so that the problem can be fixed by hand, but the
+                // command fails
+                return match input.parse() {
+                    Ok(document) => {
+                        write_output(&args.output, &SchemataFormatter::format(&document))?;
+                        Ok(ExitCode::SUCCESS)
+                    },
+                    Err(failure) => {
+                        write_output(&args.output, &input.source)?;
+                        Err(failure)
+                    },
+                };
+            }
+            convert(&input, args.to, &args.output)
+        },
+        Command::Generate(args) => {
+            let input = Input::read_as_schemata(&args.input, Some(InputFormat::Schemata), &XsdOptions::default())?;
+            convert(&input, args.to, &args.output)
+        },
+        Command::Validate(args) => {
+            Input::read_as_schemata(&args.input, args.from, &args.xsd)?.analyze()?;
+            log::info!("{} is valid", args.input);
+            Ok(ExitCode::SUCCESS)
+        },
+        Command::Fmt(args) => {
+            let input = Input::read(&args.input)?;
+            let formatted = SchemataFormatter::format(&input.parse()?);
+            if args.check {
+                if formatted == input.source {
+                    return Ok(ExitCode::SUCCESS);
                 }
+                eprintln!("{} is not formatted", input.file);
+                return Ok(ExitCode::FAILURE);
             }
-        }
+            write_output(&args.output, &formatted)?;
+            Ok(ExitCode::SUCCESS)
+        },
+        Command::Diff(args) => {
+            let old = Input::read(&args.old)?.parse()?;
+            let new = Input::read(&args.new)?.parse()?;
+            let changes = SchemataDiff::compare(&old, &new);
+            let report: String = changes.iter().map(|change| format!("{}\n", change)).collect();
+            write_output("-", &report)?;
+            Ok(if changes.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+        },
+        Command::Lint(args) => {
+            let input = Input::read(&args.input)?;
+            let (document, symbols) = input.analyze()?;
+            let warnings = SchemataLinter::lint(&document, &symbols);
+            Ok(if report_warnings(&warnings, &input) { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+        },
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let level = match (cli.quiet, cli.verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Info,
+        (false, 2) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    env_logger::Builder::new().filter_level(level).parse_env("RUST_LOG").init();
+
+    match run(&cli.command) {
+        Ok(code) => code,
+        // Errors without source text, like unreadable files, have no location worth showing
+        Err(failure) if failure.source.is_empty() => {
+            eprintln!("error: {}", failure.error);
+            ExitCode::FAILURE
+        },
+        Err(failure) => {
+            eprint!("{}", diagnostics::report(failure.error.as_ref(), &failure.file, &failure.source));
+            ExitCode::FAILURE
+        },
     }
 }
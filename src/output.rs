@@ -0,0 +1,19 @@
+use std::path::Path;
+
+/// A file produced by an exporter, with a path relative to the output directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFile {
+    pub path: String,
+    pub content: String,
+}
+
+impl GeneratedFile {
+    /// Writes the file below `dir`, creating any missing parent directories.
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<()> {
+        let path = dir.join(&self.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, &self.content)
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::types::*;
+use crate::schemata::type_mapping;
+use crate::schemata::types::{SchemataEnum, SchemataField, SchemataNamespace, SchemataSchema};
+
+/// Maps a parsed `.proto` file onto Schemata schemas and enums.
+///
+/// Nested messages and enums are hoisted to the top level with their parent names prepended
+/// (`Order.Item` becomes `OrderItem`), since Schemata has no nested declarations.
+pub struct ProtoConverter {
+    /// Fully qualified proto name (`.pkg.Order.Item`) to Schemata name (`OrderItem`) and
+    /// whether it is a message rather than an enum
+    names: HashMap<String, (String, bool)>,
+    proto3: bool,
+}
+
+impl ProtoConverter {
+    pub fn convert(file: &ProtoFile) -> Vec<SchemataNamespace> {
+        let package = file.package.clone().unwrap_or_default();
+        let scope = if package.is_empty() { String::new() } else { format!(".{}", package) };
+
+        let mut converter = Self {
+            names: HashMap::new(),
+            proto3: file.syntax == "proto3",
+        };
+        converter.register(&scope, "", &file.messages, &file.enums);
+
+        let mut namespace = SchemataNamespace {
+            name: file.package.clone().unwrap_or_else(|| "default".to_string()),
+            comment: None,
+            schemas: Vec::new(),
+            enums: Vec::new(),
+            aliases: Vec::new(),
+        };
+        converter.convert_messages(&scope, "", &file.messages, &mut namespace);
+        namespace.enums.extend(file.enums.iter().map(|e| Self::convert_enum("", e)));
+
+        vec![namespace]
+    }
+
+    fn register(&mut self, scope: &str, prefix: &str, messages: &[ProtoMessage], enums: &[ProtoEnum]) {
+        for message in messages {
+            let full_name = format!("{}.{}", scope, message.name);
+            let name = format!("{}{}", prefix, message.name);
+            self.register(&full_name, &name, &message.messages, &message.enums);
+            self.names.insert(full_name, (name, true));
+        }
+        for proto_enum in enums {
+            self.names.insert(format!("{}.{}", scope, proto_enum.name), (format!("{}{}", prefix, proto_enum.name), false));
+        }
+    }
+
+    fn convert_messages(&self, scope: &str, prefix: &str, messages: &[ProtoMessage], namespace: &mut SchemataNamespace) {
+        for message in messages {
+            let full_name = format!("{}.{}", scope, message.name);
+            let name = format!("{}{}", prefix, message.name);
+
+            let mut fields: Vec<(u32, SchemataField)> = message.fields.iter()
+                .map(|f| (f.number, self.convert_field(&full_name, f, None)))
+                .collect();
+            for oneof in &message.oneofs {
+                fields.extend(oneof.fields.iter().map(|f| (f.number, self.convert_field(&full_name, f, Some(&oneof.name)))));
+            }
+            fields.sort_by_key(|(number, _)| *number);
+
+            namespace.schemas.push(SchemataSchema {
+                name: name.clone(),
+                annotations: String::new(),
+                comment: None,
+                fields: fields.into_iter().map(|(_, field)| field).collect(),
+            });
+            namespace.enums.extend(message.enums.iter().map(|e| Self::convert_enum(&name, e)));
+            self.convert_messages(&full_name, &name, &message.messages, namespace);
+        }
+    }
+
+    pub(crate) fn convert_field(&self, scope: &str, field: &ProtoField, oneof: Option<&str>) -> SchemataField {
+        let mut annotations = vec![format!("@field(number = \"{}\")", field.number)];
+        if let Some(oneof) = oneof {
+            annotations.push(format!("@oneof(name = \"{}\")", oneof));
+        }
+        if let Some(default) = &field.default {
+            annotations.push(format!("@default(value = \"{}\")", default));
+        }
+
+        if let Some((key, value)) = &field.map {
+            return SchemataField {
+                name: field.name.clone(),
+                type_name: format!("map<{}, {}>", self.resolve_type(scope, key).0, self.resolve_type(scope, value).0),
+                nullable: false,
+                annotations: annotations.join(" "),
+                comment: None,
+                inline_schema: None,
+                inline_enum: None,
+            };
+        }
+
+        let (type_name, is_message, wrapper) = self.resolve_type(scope, &field.type_name);
+        let nullable = match field.label {
+            ProtoLabel::Repeated | ProtoLabel::Required => false,
+            ProtoLabel::Optional => true,
+            // Oneof members, message-typed fields and wrapper types have presence tracking
+            ProtoLabel::None => oneof.is_some() || is_message || wrapper || !self.proto3,
+        };
+
+        SchemataField {
+            name: field.name.clone(),
+            type_name: if field.label == ProtoLabel::Repeated { format!("{}[]", type_name) } else { type_name },
+            nullable,
+            annotations: annotations.join(" "),
+            comment: None,
+            inline_schema: None,
+            inline_enum: None,
+        }
+    }
+
+    /// Returns the Schemata type for a proto type reference, whether it names a message, and
+    /// whether it is a `google.protobuf` wrapper type.
+    pub(crate) fn resolve_type(&self, scope: &str, type_name: &str) -> (String, bool, bool) {
+        let absolute = type_name.trim_start_matches('.');
+        if let Some(basic) = type_mapping::from_proto(absolute) {
+            // Well-known types like `Timestamp` are messages, with presence tracking
+            return (basic.name.to_string(), absolute.starts_with("google.protobuf."), false);
+        }
+        if let Some(basic) = Self::wrapper_type(absolute) {
+            return (basic.to_string(), false, true);
+        }
+
+        // Search the enclosing scopes from the innermost outwards, like protoc does
+        let found = if type_name.starts_with('.') {
+            self.names.get(type_name)
+        } else {
+            let mut current = scope.to_string();
+            loop {
+                let candidate = format!("{}.{}", current, type_name);
+                if let Some(entry) = self.names.get(&candidate) {
+                    break Some(entry);
+                }
+                match current.rfind('.') {
+                    Some(index) => current.truncate(index),
+                    None => break None,
+                }
+            }
+        };
+
+        match found {
+            Some((name, is_message)) => (name.clone(), *is_message, false),
+            // Declared in an imported file; keep the reference as written
+            None => (type_name.trim_start_matches('.').to_string(), true, false),
+        }
+    }
+
+    fn convert_enum(prefix: &str, proto_enum: &ProtoEnum) -> SchemataEnum {
+        SchemataEnum {
+            name: format!("{}{}", prefix, proto_enum.name),
+            annotations: if proto_enum.allow_alias { "@allowAlias".to_string() } else { String::new() },
+            comment: None,
+            values: proto_enum.values.iter().map(|(name, _)| name.clone()).collect(),
+            value_comments: BTreeMap::new(),
+            // Numbers are kept, as the wire format carries them rather than the names
+            value_annotations: proto_enum.values.iter()
+                .map(|(name, number)| (name.clone(), format!("@value(number = \"{}\")", number)))
+                .collect(),
+        }
+    }
+
+    /// Schemata types for the `google.protobuf` wrapper messages, which make a scalar nullable.
+    fn wrapper_type(type_name: &str) -> Option<&'static str> {
+        Some(match type_name {
+            "google.protobuf.DoubleValue" => "double",
+            "google.protobuf.FloatValue" => "float",
+            "google.protobuf.Int32Value" => "int",
+            "google.protobuf.Int64Value" | "google.protobuf.UInt32Value" | "google.protobuf.UInt64Value" => "long",
+            "google.protobuf.BoolValue" => "bool",
+            "google.protobuf.StringValue" => "string",
+            "google.protobuf.BytesValue" => "bytes",
+            _ => return None,
+        })
+    }
+}
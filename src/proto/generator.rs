@@ -0,0 +1,376 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::RangeInclusive;
+
+use tera::{Context, Tera};
+
+use super::types::*;
+use crate::diagnostics::Diagnostic;
+use crate::output::GeneratedFile;
+use crate::schemata::analyzer::{ResolvedType, SymbolTable};
+use crate::schemata::ast::{Document, Enum, Field, Namespace, Schema, TypeRef};
+use crate::schemata::type_mapping;
+
+/// Field numbers reserved for the Protobuf implementation itself.
+const RESERVED_NUMBERS: RangeInclusive<u32> = 19000..=19999;
+const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+/// Generates proto3 files from a checked Schemata document, one file per namespace.
+///
+/// Inline schemas become top-level messages, matching how the analyzer registers them, except
+/// for `XEntry[]` key/value schemas, which are written back as `map<K, V>` fields.
+pub struct ProtoGenerator {
+    tera: Tera,
+}
+
+/// State for the file of a single namespace.
+struct FileBuilder<'a> {
+    namespace: &'a str,
+    symbols: &'a SymbolTable,
+    imports: BTreeSet<String>,
+    messages: Vec<ProtoMessage>,
+}
+
+impl ProtoGenerator {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut tera = Tera::default();
+        tera.add_raw_template("proto", include_str!("../../templates/proto/proto.tera"))?;
+        Ok(Self { tera })
+    }
+
+    pub fn generate(&self, document: &Document, symbols: &SymbolTable) -> Result<Vec<GeneratedFile>, Box<dyn std::error::Error>> {
+        let document = symbols.expand_aliases(document);
+        let mut imports = BTreeMap::new();
+        let mut files = Vec::new();
+
+        for namespace in &document.namespaces {
+            let file = self.get_file(namespace, symbols)?;
+            let path = Self::file_path(&namespace.name);
+            imports.insert(path.clone(), file.imports.clone());
+
+            let mut context = Context::new();
+            context.insert("file", &file);
+            files.push(GeneratedFile { path, content: self.tera.render("proto", &context)? });
+        }
+
+        if let Some(cycle) = Self::import_cycle(&imports) {
+            log::warn!("The generated files import each other ({}), which protoc rejects; \
+                move the types involved into one namespace", cycle.join(" -> "));
+        }
+        Ok(files)
+    }
+
+    /// A chain of files that ends by importing its first file, if there is one.
+    pub(crate) fn import_cycle(imports: &BTreeMap<String, Vec<String>>) -> Option<Vec<String>> {
+        fn visit(file: &str, imports: &BTreeMap<String, Vec<String>>, path: &mut Vec<String>, done: &mut BTreeSet<String>) -> Option<Vec<String>> {
+            if let Some(start) = path.iter().position(|p| p == file) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(file.to_string());
+                return Some(cycle);
+            }
+            if !done.insert(file.to_string()) {
+                return None;
+            }
+            path.push(file.to_string());
+            for import in imports.get(file).into_iter().flatten() {
+                if let Some(cycle) = visit(import, imports, path, done) {
+                    return Some(cycle);
+                }
+            }
+            path.pop();
+            None
+        }
+
+        let mut done = BTreeSet::new();
+        imports.keys().find_map(|file| visit(file, imports, &mut Vec::new(), &mut done))
+    }
+
+    /// `com.example.people` is written to `com/example/people.proto`.
+    pub fn file_path(namespace: &str) -> String {
+        format!("{}.proto", namespace.replace('.', "/"))
+    }
+
+    pub(crate) fn get_file(&self, namespace: &Namespace, symbols: &SymbolTable) -> Result<ProtoFile, Box<dyn std::error::Error>> {
+        let mut builder = FileBuilder {
+            namespace: &namespace.name,
+            symbols,
+            imports: BTreeSet::new(),
+            messages: Vec::new(),
+        };
+        for schema in &namespace.schemas {
+            builder.add_message(schema.name.as_deref().unwrap_or_default(), schema)?;
+        }
+
+        Ok(ProtoFile {
+            syntax: "proto3".to_string(),
+            package: Some(namespace.name.clone()),
+            imports: builder.imports.into_iter().collect(),
+            messages: builder.messages,
+            enums: namespace.enums.iter().map(Self::get_enum).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// proto3 enums must start with a zero value, and enum values share the package scope, so
+    /// values are prefixed with the enum name. Numbers from `@value(number = "..")` are kept and
+    /// the remaining values get the lowest free numbers. Unless a value is numbered 0, or an
+    /// unnumbered `_UNSPECIFIED` value comes first, an `_UNSPECIFIED` zero value is added.
+    /// Values may only share a number in enums marked `@allowAlias`.
+    pub(crate) fn get_enum(enumeration: &Enum) -> Result<ProtoEnum, Box<dyn std::error::Error>> {
+        let prefix = Self::screaming_snake_case(&enumeration.name);
+        let mut names: Vec<String> = enumeration.values.iter()
+            .map(|value| {
+                let value = Self::screaming_snake_case(value);
+                if value.starts_with(&format!("{}_", prefix)) { value } else { format!("{}_{}", prefix, value) }
+            })
+            .collect();
+        let allow_alias = enumeration.annotations.iter().any(|a| a.name == "allowAlias");
+        let mut numbers = Self::value_numbers(enumeration, allow_alias)?;
+        if !numbers.contains(&Some(0)) {
+            if names.first().is_some_and(|name| name.ends_with("_UNSPECIFIED")) && numbers[0].is_none() {
+                numbers[0] = Some(0);
+            } else {
+                names.insert(0, format!("{}_UNSPECIFIED", prefix));
+                numbers.insert(0, Some(0));
+            }
+        }
+
+        let mut used: HashSet<i32> = numbers.iter().flatten().copied().collect();
+        let mut next = 1;
+        let mut values: Vec<(String, i32)> = names.into_iter().zip(numbers)
+            .map(|(name, number)| {
+                let number = number.unwrap_or_else(|| {
+                    while used.contains(&next) {
+                        next += 1;
+                    }
+                    used.insert(next);
+                    next
+                });
+                (name, number)
+            })
+            .collect();
+        // The zero value is the default and must come first
+        if let Some(index) = values.iter().position(|(_, number)| *number == 0) {
+            let zero = values.remove(index);
+            values.insert(0, zero);
+        }
+
+        Ok(ProtoEnum {
+            name: enumeration.name.clone(),
+            comment: enumeration.comment.as_deref().map(Self::line_comment),
+            values,
+            allow_alias,
+        })
+    }
+
+    /// The numbers from `@value(number = "..")` of the values of an enum, in declaration order.
+    /// Unless `allow_alias` is set, no two values may share a number.
+    fn value_numbers(enumeration: &Enum, allow_alias: bool) -> Result<Vec<Option<i32>>, Box<dyn std::error::Error>> {
+        let mut used: HashMap<i32, &str> = HashMap::new();
+        let mut numbers = Vec::new();
+
+        for value in &enumeration.values {
+            let annotation = enumeration.value_annotations.get(value)
+                .and_then(|annotations| annotations.iter().find(|a| a.name == "value"));
+            let Some(annotation) = annotation else {
+                numbers.push(None);
+                continue;
+            };
+            let number = annotation.arg("number")
+                .ok_or_else(|| Diagnostic::error("@value requires a `number` argument").with_span(annotation.span))?;
+            let number = number.as_str().parse::<i32>()
+                .map_err(|_| Diagnostic::error(format!("Invalid enum value number `{}`", number.as_str())).with_span(annotation.span))?;
+            if let Some(other) = used.insert(number, value).filter(|_| !allow_alias) {
+                return Err(Diagnostic::error(format!("Enum value number {} is used by both `{}` and `{}`", number, other, value))
+                    .with_span(annotation.span)
+                    .into());
+            }
+            numbers.push(Some(number));
+        }
+        Ok(numbers)
+    }
+
+    /// `OrderStatus` and `orderStatus` become `ORDER_STATUS`; anything that is not
+    /// alphanumeric becomes an underscore.
+    fn screaming_snake_case(name: &str) -> String {
+        let mut result = String::new();
+        let mut previous: Option<char> = None;
+        for c in name.chars() {
+            if c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) {
+                result.push('_');
+            }
+            if c.is_ascii_alphanumeric() {
+                result.push(c.to_ascii_uppercase());
+            } else if !result.ends_with('_') {
+                result.push('_');
+            }
+            previous = Some(c);
+        }
+        result
+    }
+
+    /// Continues multi-line comments with `//` on every line.
+    fn line_comment(comment: &str) -> String {
+        comment.replace('\n', "\n// ")
+    }
+}
+
+impl FileBuilder<'_> {
+    /// Adds the message for `schema`, followed by messages for its inline schemas.
+    fn add_message(&mut self, name: &str, schema: &Schema) -> Result<(), Box<dyn std::error::Error>> {
+        let mut message = ProtoMessage {
+            name: name.to_string(),
+            comment: schema.comment.as_deref().map(ProtoGenerator::line_comment),
+            ..Default::default()
+        };
+        let mut inline = Vec::new();
+
+        for (field, number) in schema.fields.iter().zip(Self::field_numbers(&schema.fields)?) {
+            let oneof = field.annotations.iter()
+                .find(|a| a.name == "oneof")
+                .and_then(|a| a.arg("name"))
+                .map(|name| name.as_str().to_string());
+            let proto_field = self.get_field(field, number, oneof.is_some())?;
+
+            match oneof {
+                Some(oneof) => match message.oneofs.iter_mut().find(|o| o.name == oneof) {
+                    Some(group) => group.fields.push(proto_field),
+                    None => message.oneofs.push(ProtoOneof { name: oneof, fields: vec![proto_field] }),
+                },
+                None => message.fields.push(proto_field),
+            }
+
+            if let Some(inline_schema) = &field.inline_schema {
+                if Self::map_entry(field).is_none() {
+                    inline.push((field.type_ref.name.as_str(), inline_schema));
+                }
+            }
+        }
+
+        self.messages.push(message);
+        for (name, inline_schema) in inline {
+            self.add_message(name, inline_schema)?;
+        }
+        Ok(())
+    }
+
+    fn get_field(&mut self, field: &Field, number: u32, in_oneof: bool) -> Result<ProtoField, Box<dyn std::error::Error>> {
+        let mut proto_field = ProtoField {
+            name: field.name.clone(),
+            number,
+            comment: field.comment.as_ref().map(|c| c.replace('\n', " ")),
+            ..Default::default()
+        };
+
+        if let Some((key, value)) = Self::map_entry(field) {
+            proto_field.map = Some((self.type_name(&key.type_ref)?, self.type_name(&value.type_ref)?));
+            return Ok(proto_field);
+        }
+        if let Some(map) = &field.type_ref.map {
+            let (key, value) = map.as_ref();
+            if field.type_ref.array || in_oneof || value.array || value.nullable || value.map.is_some() {
+                return Err(Diagnostic::error(format!("Map field `{}` cannot be written as a Protobuf map", field.name))
+                    .with_span(field.type_ref.span)
+                    .with_help("Protobuf maps cannot be repeated or part of a oneof, and their values cannot be repeated, optional or maps")
+                    .into());
+            }
+            proto_field.map = Some((self.type_name(key)?, self.type_name(value)?));
+            return Ok(proto_field);
+        }
+
+        proto_field.type_name = self.type_name(&field.type_ref)?;
+        proto_field.label = match (field.type_ref.array, field.type_ref.nullable) {
+            _ if in_oneof && field.type_ref.array => {
+                return Err(Diagnostic::error(format!("Array field `{}` cannot be part of a oneof", field.name))
+                    .with_span(field.span)
+                    .into());
+            },
+            _ if in_oneof => ProtoLabel::None,
+            (true, _) => ProtoLabel::Repeated,
+            (false, true) => ProtoLabel::Optional,
+            (false, false) => ProtoLabel::None,
+        };
+        Ok(proto_field)
+    }
+
+    fn type_name(&mut self, type_ref: &TypeRef) -> Result<String, Box<dyn std::error::Error>> {
+        if type_ref.union.is_some() {
+            return Err(Diagnostic::error(format!("Union type `{}` cannot be written in Protobuf", type_ref))
+                .with_span(type_ref.span)
+                .with_help("declare a field per member type and mark them with the same @oneof")
+                .into());
+        }
+        let resolved = self.symbols.resolve(self.namespace, type_ref)
+            .map_err(|_| Diagnostic::error(format!("Unresolved type `{}`", type_ref.name)).with_span(type_ref.span))?;
+
+        Ok(match resolved {
+            ResolvedType::Basic(name) => {
+                let proto = type_mapping::basic_type(&name).and_then(|basic| basic.proto.first()).copied().unwrap_or("string");
+                // Well-known types live in google/protobuf/<name in lower case>.proto
+                if let Some(well_known) = proto.strip_prefix("google.protobuf.") {
+                    self.imports.insert(format!("google/protobuf/{}.proto", well_known.to_lowercase()));
+                }
+                proto.to_string()
+            },
+            ResolvedType::Schema(symbol) | ResolvedType::Enum(symbol) => {
+                if symbol.namespace == self.namespace {
+                    symbol.name
+                } else {
+                    self.imports.insert(ProtoGenerator::file_path(&symbol.namespace));
+                    symbol.qualified_name()
+                }
+            },
+        })
+    }
+
+    /// The key and value fields of an `XEntry[]` inline schema that can be written as a map.
+    fn map_entry(field: &Field) -> Option<(&Field, &Field)> {
+        let inline = field.inline_schema.as_ref()?;
+        if !field.type_ref.array || !field.type_ref.name.ends_with("Entry") {
+            return None;
+        }
+        match inline.fields.as_slice() {
+            [key, value] if key.name == "key" && value.name == "value"
+                && matches!(key.type_ref.name.as_str(), "int" | "long" | "string" | "bool")
+                && !key.type_ref.array && !key.type_ref.nullable
+                && !value.type_ref.array && value.inline_schema.is_none() => Some((key, value)),
+            _ => None,
+        }
+    }
+
+    /// Numbers from `@field(number = "..")` are kept; the remaining fields get the lowest free
+    /// numbers in declaration order.
+    fn field_numbers(fields: &[Field]) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+        let mut used: HashMap<u32, &str> = HashMap::new();
+        let mut explicit = Vec::new();
+
+        for field in fields {
+            let Some(annotation) = field.annotations.iter().find(|a| a.name == "field") else {
+                explicit.push(None);
+                continue;
+            };
+            let value = annotation.arg("number")
+                .ok_or_else(|| Diagnostic::error("@field requires a `number` argument").with_span(annotation.span))?;
+            let number = value.as_str().parse::<u32>().ok()
+                .filter(|n| (1..=MAX_FIELD_NUMBER).contains(n) && !RESERVED_NUMBERS.contains(n))
+                .ok_or_else(|| Diagnostic::error(format!("Invalid field number `{}`", value.as_str()))
+                    .with_span(annotation.span)
+                    .with_help("field numbers range from 1 to 536870911, excluding 19000 to 19999"))?;
+            if let Some(other) = used.insert(number, &field.name) {
+                return Err(Diagnostic::error(format!("Field number {} is used by both `{}` and `{}`", number, other, field.name))
+                    .with_span(annotation.span)
+                    .into());
+            }
+            explicit.push(Some(number));
+        }
+
+        let mut next = 1;
+        Ok(explicit.into_iter()
+            .map(|number| number.unwrap_or_else(|| {
+                while used.contains_key(&next) || RESERVED_NUMBERS.contains(&next) {
+                    next += 1;
+                }
+                used.insert(next, "");
+                next
+            }))
+            .collect())
+    }
+}
@@ -0,0 +1,8 @@
+mod converter;
+mod generator;
+mod parser;
+mod tests;
+pub mod types;
+pub use converter::ProtoConverter;
+pub use generator::ProtoGenerator;
+pub use parser::ProtoParser;
\ No newline at end of file
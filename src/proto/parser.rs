@@ -0,0 +1,214 @@
+use pest::iterators::Pair;
+use pest::Parser;
+use pest_derive::Parser;
+
+use super::types::*;
+use crate::diagnostics::{Diagnostic, Span};
+
+#[derive(Parser)]
+#[grammar = "../grammars/proto.pest"]
+struct ProtoGrammar;
+
+pub struct ProtoParser;
+
+impl ProtoParser {
+    pub fn parse(input: &str) -> Result<ProtoFile, Box<dyn std::error::Error>> {
+        let file = ProtoGrammar::parse(Rule::proto, input)
+            .map_err(Self::syntax_error)?
+            .next()
+            .ok_or("Empty parse tree")?;
+
+        let mut proto = ProtoFile {
+            syntax: "proto2".to_string(),
+            ..Default::default()
+        };
+
+        for pair in file.into_inner() {
+            match pair.as_rule() {
+                Rule::syntax => proto.syntax = Self::string_value(Self::child(pair, Rule::string_literal)?),
+                Rule::import => proto.imports.push(Self::string_value(Self::child(pair, Rule::string_literal)?)),
+                Rule::package => proto.package = Some(Self::child(pair, Rule::full_ident)?.as_str().to_string()),
+                Rule::message => proto.messages.push(Self::parse_message(pair)?),
+                Rule::r#enum => proto.enums.push(Self::parse_enum(pair)?),
+                _ => {}
+            }
+        }
+
+        Ok(proto)
+    }
+
+    pub(crate) fn parse_message(pair: Pair<Rule>) -> Result<ProtoMessage, Box<dyn std::error::Error>> {
+        let mut message = ProtoMessage::default();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::ident => message.name = inner.as_str().to_string(),
+                Rule::field => message.fields.push(Self::parse_field(inner)?),
+                Rule::map_field => message.fields.push(Self::parse_map_field(inner)?),
+                Rule::oneof => message.oneofs.push(Self::parse_oneof(inner)?),
+                Rule::message => message.messages.push(Self::parse_message(inner)?),
+                Rule::r#enum => message.enums.push(Self::parse_enum(inner)?),
+                _ => {}
+            }
+        }
+
+        Ok(message)
+    }
+
+    /// Parses a regular or oneof field; both list type, name, number and options in that order.
+    pub(crate) fn parse_field(pair: Pair<Rule>) -> Result<ProtoField, Box<dyn std::error::Error>> {
+        let mut field = ProtoField::default();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::label => {
+                    field.label = match inner.as_str() {
+                        "repeated" => ProtoLabel::Repeated,
+                        "optional" => ProtoLabel::Optional,
+                        "required" => ProtoLabel::Required,
+                        _ => ProtoLabel::None,
+                    };
+                },
+                Rule::field_type => field.type_name = inner.as_str().to_string(),
+                Rule::ident => field.name = inner.as_str().to_string(),
+                Rule::int_literal => field.number = Self::field_number(&inner)?,
+                Rule::field_options => field.default = Self::default_option(inner),
+                _ => {}
+            }
+        }
+
+        Ok(field)
+    }
+
+    pub(crate) fn parse_map_field(pair: Pair<Rule>) -> Result<ProtoField, Box<dyn std::error::Error>> {
+        let mut field = ProtoField::default();
+        let mut types = Vec::new();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::field_type => types.push(inner.as_str().to_string()),
+                Rule::ident => field.name = inner.as_str().to_string(),
+                Rule::int_literal => field.number = Self::field_number(&inner)?,
+                _ => {}
+            }
+        }
+
+        match <[String; 2]>::try_from(types) {
+            Ok([key, value]) => field.map = Some((key, value)),
+            Err(_) => return Err(format!("Malformed map field `{}`", field.name).into()),
+        }
+        Ok(field)
+    }
+
+    pub(crate) fn parse_oneof(pair: Pair<Rule>) -> Result<ProtoOneof, Box<dyn std::error::Error>> {
+        let mut oneof = ProtoOneof::default();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::ident => oneof.name = inner.as_str().to_string(),
+                Rule::oneof_field => oneof.fields.push(Self::parse_field(inner)?),
+                _ => {}
+            }
+        }
+
+        Ok(oneof)
+    }
+
+    pub(crate) fn parse_enum(pair: Pair<Rule>) -> Result<ProtoEnum, Box<dyn std::error::Error>> {
+        let mut proto_enum = ProtoEnum::default();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::ident => proto_enum.name = inner.as_str().to_string(),
+                Rule::enum_value => {
+                    let name = Self::child(inner.clone(), Rule::ident)?.as_str().to_string();
+                    let number = Self::parse_number(&Self::child(inner, Rule::int_literal)?)?;
+                    proto_enum.values.push((name, i32::try_from(number)?));
+                },
+                Rule::option => {
+                    let mut parts = inner.into_inner().skip(1);
+                    if let (Some(name), Some(constant)) = (parts.next(), parts.next()) {
+                        proto_enum.allow_alias |= name.as_str() == "allow_alias" && constant.as_str() == "true";
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        Ok(proto_enum)
+    }
+
+    /// The value of a proto2 `[default = ...]` field option.
+    fn default_option(pair: Pair<Rule>) -> Option<String> {
+        pair.into_inner()
+            .filter(|option| option.as_rule() == Rule::field_option)
+            .find_map(|option| {
+                let mut parts = option.into_inner();
+                let name = parts.next()?;
+                let constant = parts.next()?.into_inner().next()?;
+                if name.as_str() != "default" {
+                    return None;
+                }
+                Some(match constant.as_rule() {
+                    Rule::string_literal => Self::string_value(constant),
+                    _ => constant.as_str().to_string(),
+                })
+            })
+    }
+
+    fn parse_number(pair: &Pair<Rule>) -> Result<i64, Box<dyn std::error::Error>> {
+        let text = pair.as_str().trim_start_matches('+');
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            Some(hex) => i64::from_str_radix(hex, 16)?,
+            None => digits.parse::<i64>()?,
+        };
+        Ok(if negative { -value } else { value })
+    }
+
+    fn field_number(pair: &Pair<Rule>) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(u32::try_from(Self::parse_number(pair)?)?)
+    }
+
+    fn child(pair: Pair<Rule>, rule: Rule) -> Result<Pair<Rule>, Box<dyn std::error::Error>> {
+        pair.into_inner()
+            .find(|p| p.as_rule() == rule)
+            .ok_or_else(|| format!("Missing {:?}", rule).into())
+    }
+
+    fn string_value(pair: Pair<Rule>) -> String {
+        pair.into_inner().next().map(|p| p.as_str().to_string()).unwrap_or_default()
+    }
+
+    fn syntax_error(error: pest::error::Error<Rule>) -> Diagnostic {
+        let error = error.renamed_rules(|rule| match rule {
+            Rule::kw_package => "`package`".to_string(),
+            Rule::kw_option | Rule::option => "`option`".to_string(),
+            Rule::kw_message | Rule::message => "`message`".to_string(),
+            Rule::kw_oneof => "`oneof`".to_string(),
+            Rule::kw_map => "`map`".to_string(),
+            Rule::kw_reserved => "`reserved`".to_string(),
+            Rule::kw_extensions => "`extensions`".to_string(),
+            Rule::kw_enum | Rule::r#enum => "`enum`".to_string(),
+            Rule::kw_service => "`service`".to_string(),
+            Rule::kw_extend => "`extend`".to_string(),
+            Rule::empty_statement => "`;`".to_string(),
+            Rule::ident | Rule::full_ident => "identifier".to_string(),
+            Rule::field_type => "type".to_string(),
+            Rule::int_literal => "integer".to_string(),
+            Rule::string_literal => "string".to_string(),
+            Rule::EOI => "end of file".to_string(),
+            other => format!("{:?}", other),
+        });
+        let span = match error.location {
+            pest::error::InputLocation::Pos(pos) => Span::new(pos, pos + 1),
+            pest::error::InputLocation::Span((start, end)) => Span::new(start, end),
+        };
+        Diagnostic::error("Syntax error in .proto file")
+            .with_span(span)
+            .with_label(error.variant.message().to_string())
+    }
+}
@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use crate::proto::{ProtoConverter, ProtoParser};
+    use crate::schemata::tests::support::{field, fields, schema};
+    use crate::schemata::types::SchemataNamespace;
+    use crate::schemata::SchemataGenerator;
+
+    fn convert(input: &str) -> Vec<SchemataNamespace> {
+        ProtoConverter::convert(&ProtoParser::parse(input).expect("Failed to parse proto file"))
+    }
+
+    #[test]
+    fn test_convert_proto3_fields() {
+        let input = r#"
+        syntax = "proto3";
+        package shop;
+        message Customer { string name = 1; }
+        message Order {
+            string id = 1;
+            repeated string tags = 2;
+            optional int32 priority = 3;
+            Customer customer = 4;
+            google.protobuf.Timestamp created_at = 5;
+            google.protobuf.StringValue note = 6;
+            double total = 7;
+        }
+        "#;
+        let namespaces = convert(input);
+
+        assert_eq!(namespaces.len(), 1);
+        assert_eq!(namespaces[0].name, "shop");
+        assert_eq!(fields(schema(&namespaces[0], "Order")), vec![
+            field("id", "string", false, "@field(number = \"1\")"),
+            field("tags", "string[]", false, "@field(number = \"2\")"),
+            field("priority", "int", true, "@field(number = \"3\")"),
+            field("customer", "Customer", true, "@field(number = \"4\")"),
+            field("created_at", "datetime", true, "@field(number = \"5\")"),
+            field("note", "string", true, "@field(number = \"6\")"),
+            field("total", "double", false, "@field(number = \"7\")"),
+        ]);
+    }
+
+    #[test]
+    fn test_convert_nested_declarations() {
+        let input = r#"
+        syntax = "proto3";
+        message Order {
+            message Item {
+                string sku = 1;
+                Status status = 2;
+            }
+            enum Status {
+                STATUS_UNSPECIFIED = 0;
+                SHIPPED = 5;
+            }
+            repeated Item items = 1;
+            Status status = 2;
+            .Order.Item first = 3;
+        }
+        "#;
+        let namespaces = convert(input);
+        let namespace = &namespaces[0];
+
+        assert_eq!(namespace.name, "default");
+        assert_eq!(namespace.schemas.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Order", "OrderItem"]);
+        assert_eq!(namespace.enums[0].name, "OrderStatus");
+        assert_eq!(namespace.enums[0].values, vec!["STATUS_UNSPECIFIED", "SHIPPED"]);
+        // Value numbers are kept, gaps included
+        assert_eq!(namespace.enums[0].value_annotations["STATUS_UNSPECIFIED"], "@value(number = \"0\")");
+        assert_eq!(namespace.enums[0].value_annotations["SHIPPED"], "@value(number = \"5\")");
+
+        // Enum fields have no presence in proto3, message fields do
+        assert_eq!(fields(schema(namespace, "Order")), vec![
+            field("items", "OrderItem[]", false, "@field(number = \"1\")"),
+            field("status", "OrderStatus", false, "@field(number = \"2\")"),
+            field("first", "OrderItem", true, "@field(number = \"3\")"),
+        ]);
+        // Names resolve from the innermost scope outwards
+        assert_eq!(schema(namespace, "OrderItem").fields[1].type_name, "OrderStatus");
+    }
+
+    #[test]
+    fn test_convert_oneof_and_map() {
+        let input = r#"
+        syntax = "proto3";
+        message Payment {
+            string id = 1;
+            oneof method {
+                string card_number = 2;
+                string iban = 3;
+            }
+            map<string, int64> tag_counts = 4;
+        }
+        "#;
+        let namespaces = convert(input);
+        let payment = schema(&namespaces[0], "Payment");
+
+        assert_eq!(fields(payment), vec![
+            field("id", "string", false, "@field(number = \"1\")"),
+            field("card_number", "string", true, "@field(number = \"2\") @oneof(name = \"method\")"),
+            field("iban", "string", true, "@field(number = \"3\") @oneof(name = \"method\")"),
+            field("tag_counts", "map<string, long>", false, "@field(number = \"4\")"),
+        ]);
+    }
+
+    #[test]
+    fn test_convert_proto2_labels() {
+        let input = r#"
+        package legacy;
+        import "other.proto";
+        message Config {
+            required string name = 1;
+            optional int32 retries = 2 [default = 3];
+            repeated other.Rule rules = 3;
+        }
+        "#;
+        let namespaces = convert(input);
+
+        assert_eq!(fields(schema(&namespaces[0], "Config")), vec![
+            field("name", "string", false, "@field(number = \"1\")"),
+            field("retries", "int", true, "@field(number = \"2\") @default(value = \"3\")"),
+            field("rules", "other.Rule[]", false, "@field(number = \"3\")"),
+        ]);
+    }
+
+    #[test]
+    fn test_render_converted_file() {
+        let input = r#"
+        syntax = "proto3";
+        package shop;
+        message Order {
+            string id = 1;
+            repeated string tags = 2;
+        }
+        enum Status {
+            STATUS_UNSPECIFIED = 0;
+        }
+        "#;
+        let generator = SchemataGenerator::new().unwrap();
+        let output = generator.render(&convert(input)).unwrap();
+
+        assert!(output.contains("namespace shop;"));
+        assert!(output.contains("schema Order {"));
+        assert!(output.contains("tags string[] @field(number = \"2\")"));
+        assert!(output.contains("enum Status {"));
+    }
+}
@@ -0,0 +1,253 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::output::GeneratedFile;
+    use crate::proto::types::ProtoLabel;
+    use crate::proto::{ProtoConverter, ProtoGenerator, ProtoParser};
+    use crate::schemata::{SchemataGenerator, SchemataParser, SemanticAnalyzer};
+
+    fn generate(input: &str) -> Result<Vec<GeneratedFile>, Box<dyn std::error::Error>> {
+        let document = SchemataParser::parse(input)?;
+        let symbols = SemanticAnalyzer::analyze(&document)?;
+        ProtoGenerator::new()?.generate(&document, &symbols)
+    }
+
+    #[test]
+    fn test_generate_messages() {
+        let input = r#"
+        namespace com.example.shop;
+
+        # An order
+        schema Order {
+            id int @id
+            total double
+            note string?
+            tags string[]
+            placedAt datetime
+            status OrderStatus
+        }
+
+        enum OrderStatus { PENDING, inProgress }
+        "#;
+        let files = generate(input).expect("Failed to generate proto files");
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "com/example/shop.proto");
+        assert_eq!(files[0].content, [
+            "syntax = \"proto3\";",
+            "",
+            "package com.example.shop;",
+            "",
+            "import \"google/protobuf/timestamp.proto\";",
+            "",
+            "// An order",
+            "message Order {",
+            "  int32 id = 1;",
+            "  double total = 2;",
+            "  optional string note = 3;",
+            "  repeated string tags = 4;",
+            "  google.protobuf.Timestamp placedAt = 5;",
+            "  OrderStatus status = 6;",
+            "}",
+            "",
+            "enum OrderStatus {",
+            "  ORDER_STATUS_UNSPECIFIED = 0;",
+            "  ORDER_STATUS_PENDING = 1;",
+            "  ORDER_STATUS_IN_PROGRESS = 2;",
+            "}",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_basic_types() {
+        let input = r#"
+        namespace test;
+        schema Reading {
+            count long
+            ratio float
+            timeout duration
+            payload bytes
+            counts map<long, Sensor>
+        }
+        schema Sensor { id uuid }
+        "#;
+        let files = generate(input).expect("Failed to generate proto");
+
+        let content = &files[0].content;
+        assert!(content.contains("import \"google/protobuf/duration.proto\";\n"));
+        assert!(content.contains("  int64 count = 1;\n  float ratio = 2;\n  google.protobuf.Duration timeout = 3;\n  bytes payload = 4;\n  map<int64, Sensor> counts = 5;\n"));
+        assert!(content.contains("  string id = 1;\n"));
+
+        let invalid = "namespace test; schema Reading { counts map<string, int[]> }";
+        assert!(generate(invalid).is_err(), "Maps of repeated values cannot be written");
+
+        let union = "namespace test; type Id = union<int, string>; schema Reading { id Id }";
+        assert!(generate(union).is_err(), "Unions have no proto type");
+        let alias = "namespace test; type Id = long; schema Reading { id Id }";
+        assert!(generate(alias).unwrap()[0].content.contains("  int64 id = 1;\n"));
+    }
+
+    #[test]
+    fn test_field_numbers() {
+        let input = r#"
+        namespace test;
+        schema User {
+            name string
+            id int @field(number = "1")
+            email string
+            legacy string @field(number = "19001")
+        }
+        "#;
+        let error = generate(input).expect_err("Reserved numbers should be rejected");
+        assert!(error.to_string().starts_with("Invalid field number `19001`"));
+
+        let input = input.replace("19001", "5");
+        let document = SchemataParser::parse(&input).unwrap();
+        let symbols = SemanticAnalyzer::analyze(&document).unwrap();
+        let file = ProtoGenerator::new().unwrap().get_file(&document.namespaces[0], &symbols).unwrap();
+        let numbers: Vec<(&str, u32)> = file.messages[0].fields.iter().map(|f| (f.name.as_str(), f.number)).collect();
+        assert_eq!(numbers, vec![("name", 2), ("id", 1), ("email", 3), ("legacy", 5)]);
+
+        let duplicate = "namespace test;\nschema User {\n    a int @field(number = \"1\")\n    b int @field(1)\n}\n";
+        let error = generate(duplicate).expect_err("Duplicate numbers should be rejected");
+        assert!(error.to_string().starts_with("Field number 1 is used by both `a` and `b`"));
+    }
+
+    #[test]
+    fn test_cross_namespace_references() {
+        let input = r#"
+        namespace com.example.people;
+        schema User { name string }
+
+        namespace com.example.shop;
+        schema Order {
+            buyer User
+            items OrderItem[] schema {
+                sku string
+            }
+        }
+        "#;
+        let document = SchemataParser::parse(input).unwrap();
+        let symbols = SemanticAnalyzer::analyze(&document).unwrap();
+        let file = ProtoGenerator::new().unwrap().get_file(&document.namespaces[1], &symbols).unwrap();
+
+        assert_eq!(file.imports, vec!["com/example/people.proto"]);
+        assert_eq!(file.messages.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec!["Order", "OrderItem"]);
+        assert_eq!(file.messages[0].fields[0].type_name, "com.example.people.User");
+        assert_eq!(file.messages[0].fields[1].label, ProtoLabel::Repeated);
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        let imports: BTreeMap<String, Vec<String>> = [
+            ("a.proto", vec!["b.proto", "google/protobuf/timestamp.proto"]),
+            ("b.proto", vec!["c.proto"]),
+            ("c.proto", vec!["a.proto"]),
+        ].into_iter().map(|(file, imports)| (file.to_string(), imports.into_iter().map(String::from).collect())).collect();
+
+        assert_eq!(ProtoGenerator::import_cycle(&imports), Some(vec![
+            "a.proto".to_string(), "b.proto".to_string(), "c.proto".to_string(), "a.proto".to_string(),
+        ]));
+        let mut acyclic = imports;
+        acyclic.insert("c.proto".to_string(), vec![]);
+        assert_eq!(ProtoGenerator::import_cycle(&acyclic), None);
+    }
+
+    #[test]
+    fn test_enum_numbers() {
+        let input = r#"
+        namespace test;
+        enum Status { UNKNOWN @value(number = "0"), ACTIVE @value(number = "1") }
+        enum Level {
+            LOW,
+            HIGH @value(number = "10"),
+            MEDIUM @value(number = "5"),
+            EXTREME,
+        }
+        "#;
+        let document = SchemataParser::parse(input).unwrap();
+        let symbols = SemanticAnalyzer::analyze(&document).unwrap();
+        let file = ProtoGenerator::new().unwrap().get_file(&document.namespaces[0], &symbols).unwrap();
+        // A value numbered 0 is the default; no other is added
+        assert_eq!(file.enums[0].values, vec![("STATUS_UNKNOWN".to_string(), 0), ("STATUS_ACTIVE".to_string(), 1)]);
+        let level: Vec<(&str, i32)> = file.enums[1].values.iter().map(|(name, number)| (name.as_str(), *number)).collect();
+        assert_eq!(level, vec![("LEVEL_UNSPECIFIED", 0), ("LEVEL_LOW", 1), ("LEVEL_HIGH", 10), ("LEVEL_MEDIUM", 5), ("LEVEL_EXTREME", 2)]);
+
+        let duplicate = "namespace test;\nenum Status { A @value(number = \"1\"), B @value(number = \"1\") }\n";
+        let error = generate(duplicate).expect_err("Duplicate numbers should be rejected");
+        assert!(error.to_string().starts_with("Enum value number 1 is used by both `A` and `B`"));
+    }
+
+    #[test]
+    fn test_round_trip_enum_numbers() {
+        let proto = r#"
+        syntax = "proto3";
+        package shop;
+        enum Status {
+            UNKNOWN = 0;
+            ACTIVE = 1;
+        }
+        enum Priority {
+            PRIORITY_NONE = 0;
+            PRIORITY_HIGH = 5;
+            PRIORITY_URGENT = 10;
+        }
+        "#;
+        let namespaces = ProtoConverter::convert(&ProtoParser::parse(proto).unwrap());
+        let schemata = SchemataGenerator::new().unwrap().render(&namespaces).unwrap();
+        let files = generate(&schemata).expect("Converted proto should generate again");
+
+        let content = &files[0].content;
+        assert!(content.contains("enum Status {\n  STATUS_UNKNOWN = 0;\n  STATUS_ACTIVE = 1;\n}\n"), "{}", content);
+        assert!(content.contains("enum Priority {\n  PRIORITY_NONE = 0;\n  PRIORITY_HIGH = 5;\n  PRIORITY_URGENT = 10;\n}\n"), "{}", content);
+    }
+
+    #[test]
+    fn test_round_trip_enum_aliases() {
+        let proto = r#"
+        syntax = "proto3";
+        package shop;
+        enum Status {
+            option allow_alias = true;
+            STATUS_UNKNOWN = 0;
+            STATUS_ACTIVE = 1;
+            STATUS_ENABLED = 1;
+        }
+        "#;
+        let parsed = ProtoParser::parse(proto).unwrap();
+        assert!(parsed.enums[0].allow_alias);
+        let namespaces = ProtoConverter::convert(&parsed);
+        assert_eq!(namespaces[0].enums[0].annotations, "@allowAlias");
+        let schemata = SchemataGenerator::new().unwrap().render(&namespaces).unwrap();
+        let files = generate(&schemata).expect("Aliased values should generate again");
+
+        let content = &files[0].content;
+        assert!(content.contains("enum Status {\n  option allow_alias = true;\n  STATUS_UNKNOWN = 0;\n  STATUS_ACTIVE = 1;\n  STATUS_ENABLED = 1;\n}\n"), "{}", content);
+    }
+
+    #[test]
+    fn test_round_trip_maps_and_oneofs() {
+        let proto = r#"
+        syntax = "proto3";
+        package shop;
+        message Payment {
+            string id = 1;
+            oneof method {
+                string card_number = 2;
+                string iban = 3;
+            }
+            map<string, int64> tag_counts = 4;
+        }
+        "#;
+        let namespaces = ProtoConverter::convert(&ProtoParser::parse(proto).unwrap());
+        let schemata = SchemataGenerator::new().unwrap().render(&namespaces).unwrap();
+        let files = generate(&schemata).expect("Converted proto should generate again");
+
+        let content = &files[0].content;
+        assert!(content.contains("  string id = 1;\n  map<string, int64> tag_counts = 4;\n"));
+        assert!(content.contains("  oneof method {\n    string card_number = 2;\n    string iban = 3;\n  }\n"));
+        assert!(!content.contains("TagCountsEntry"));
+    }
+}
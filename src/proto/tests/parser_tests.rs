@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+    use crate::proto::types::ProtoLabel;
+    use crate::proto::ProtoParser;
+
+    #[test]
+    fn test_parse_proto3_message() {
+        let input = r#"
+        syntax = "proto3";
+        package shop.orders;
+
+        import "google/protobuf/timestamp.proto";
+        option java_package = "com.example.shop";
+
+        // An order placed by a customer
+        message Order {
+            string id = 1;
+            repeated string tags = 2;
+            optional int32 priority = 3 [deprecated = true];
+            google.protobuf.Timestamp created_at = 4;
+            reserved 5, 6 to 8;
+            reserved "legacy";
+        }
+        "#;
+        let proto = ProtoParser::parse(input).expect("Failed to parse proto file");
+
+        assert_eq!(proto.syntax, "proto3");
+        assert_eq!(proto.package.as_deref(), Some("shop.orders"));
+        assert_eq!(proto.imports, vec!["google/protobuf/timestamp.proto"]);
+
+        let order = &proto.messages[0];
+        assert_eq!(order.name, "Order");
+        let fields: Vec<(&str, &str, u32, ProtoLabel)> = order.fields.iter()
+            .map(|f| (f.name.as_str(), f.type_name.as_str(), f.number, f.label))
+            .collect();
+        assert_eq!(fields, vec![
+            ("id", "string", 1, ProtoLabel::None),
+            ("tags", "string", 2, ProtoLabel::Repeated),
+            ("priority", "int32", 3, ProtoLabel::Optional),
+            ("created_at", "google.protobuf.Timestamp", 4, ProtoLabel::None),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_nested_declarations() {
+        let input = r#"
+        syntax = "proto3";
+        message Order {
+            message Item {
+                string sku = 1;
+            }
+            enum Status {
+                STATUS_UNSPECIFIED = 0;
+                SHIPPED = 1;
+                RETURNED = -1;
+            }
+            repeated Item items = 1;
+            Status status = 2;
+        }
+        "#;
+        let proto = ProtoParser::parse(input).unwrap();
+
+        let order = &proto.messages[0];
+        assert_eq!(order.messages[0].name, "Item");
+        assert_eq!(order.enums[0].name, "Status");
+        assert_eq!(order.enums[0].values, vec![
+            ("STATUS_UNSPECIFIED".to_string(), 0),
+            ("SHIPPED".to_string(), 1),
+            ("RETURNED".to_string(), -1),
+        ]);
+        assert_eq!(order.fields.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_oneof_and_map() {
+        let input = r#"
+        syntax = "proto3";
+        message Payment {
+            oneof method {
+                string card_number = 1;
+                string iban = 2;
+            }
+            map<string, int64> attributes = 3;
+        }
+        "#;
+        let proto = ProtoParser::parse(input).unwrap();
+
+        let payment = &proto.messages[0];
+        assert_eq!(payment.oneofs[0].name, "method");
+        assert_eq!(payment.oneofs[0].fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["card_number", "iban"]);
+        assert_eq!(payment.fields[0].name, "attributes");
+        assert_eq!(payment.fields[0].map, Some(("string".to_string(), "int64".to_string())));
+    }
+
+    #[test]
+    fn test_parse_proto2_defaults() {
+        let input = r#"
+        /* Files without a syntax statement are proto2 */
+        message Config {
+            required string name = 1;
+            optional int32 retries = 2 [default = 3];
+            optional string mode = 3 [default = "fast"];
+            extensions 100 to max;
+        }
+        service ConfigService {
+            rpc Get (Config) returns (Config) { option idempotency_level = NO_SIDE_EFFECTS; }
+        }
+        "#;
+        let proto = ProtoParser::parse(input).unwrap();
+
+        assert_eq!(proto.syntax, "proto2");
+        let config = &proto.messages[0];
+        assert_eq!(config.fields[0].label, ProtoLabel::Required);
+        assert_eq!(config.fields[1].default.as_deref(), Some("3"));
+        assert_eq!(config.fields[2].default.as_deref(), Some("fast"));
+    }
+
+    #[test]
+    fn test_syntax_error_diagnostic() {
+        use crate::diagnostics::Diagnostic;
+
+        let input = "syntax = \"proto3\";\nmessage User {\n    string name 1;\n}\n";
+        let error = ProtoParser::parse(input).expect_err("Parsing should fail");
+        let diagnostic = error.downcast_ref::<Diagnostic>().expect("Expected a diagnostic");
+
+        let diagnostic = diagnostic.clone().with_source("user.proto", input);
+        assert_eq!(diagnostic.message, "Syntax error in .proto file");
+        assert_eq!(diagnostic.line, Some(3));
+        assert!(diagnostic.label.as_deref().unwrap_or_default().contains("`message`"));
+    }
+}
@@ -0,0 +1,59 @@
+use serde::Serialize;
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProtoFile {
+    /// `proto2` or `proto3`; files without a `syntax` statement are proto2.
+    pub syntax: String,
+    pub package: Option<String>,
+    pub imports: Vec<String>,
+    pub messages: Vec<ProtoMessage>,
+    pub enums: Vec<ProtoEnum>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProtoMessage {
+    pub name: String,
+    pub comment: Option<String>,
+    pub fields: Vec<ProtoField>,
+    pub oneofs: Vec<ProtoOneof>,
+    pub messages: Vec<ProtoMessage>,
+    pub enums: Vec<ProtoEnum>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtoLabel {
+    /// No label: a proto3 singular field, or a oneof member
+    #[default]
+    None,
+    Optional,
+    Required,
+    Repeated,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProtoField {
+    pub name: String,
+    pub type_name: String,
+    pub number: u32,
+    pub label: ProtoLabel,
+    /// Key and value types of a `map<K, V>` field; `type_name` is empty for maps.
+    pub map: Option<(String, String)>,
+    pub default: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProtoOneof {
+    pub name: String,
+    pub fields: Vec<ProtoField>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProtoEnum {
+    pub name: String,
+    pub comment: Option<String>,
+    pub values: Vec<(String, i32)>,
+    /// Set by `option allow_alias = true;`, which lets values share a number
+    pub allow_alias: bool,
+}
@@ -0,0 +1,674 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use super::ast::{Annotation, Document, Field, Literal, Schema, TypeAlias, TypeRef};
+use super::type_mapping::MAP_KEY_TYPES;
+use crate::diagnostics::{Diagnostic, Span, ToDiagnostic};
+
+/// Where an annotation may be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationTarget {
+    Schema,
+    Enum,
+    EnumValue,
+    Field,
+    Alias,
+}
+
+/// Annotations with a known meaning and the declarations they may be attached to.
+/// Annotations not listed here are passed through unchecked.
+pub const KNOWN_ANNOTATIONS: &[(&str, &[AnnotationTarget])] = &[
+    ("id", &[AnnotationTarget::Field]),
+    ("generated", &[AnnotationTarget::Field]),
+    ("unique", &[AnnotationTarget::Field]),
+    ("default", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("relation", &[AnnotationTarget::Field]),
+    ("size", &[AnnotationTarget::Field]),
+    ("field", &[AnnotationTarget::Field]),
+    ("oneof", &[AnnotationTarget::Field]),
+    ("pattern", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("length", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("minLength", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("maxLength", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("minInclusive", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("maxInclusive", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("minExclusive", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("maxExclusive", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("totalDigits", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("fractionDigits", &[AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("extends", &[AnnotationTarget::Schema]),
+    ("mixin", &[AnnotationTarget::Schema]),
+    ("include", &[AnnotationTarget::Schema]),
+    ("extensible", &[AnnotationTarget::Schema]),
+    ("appinfo", &[AnnotationTarget::Schema, AnnotationTarget::Enum, AnnotationTarget::Field, AnnotationTarget::Alias]),
+    ("const", &[AnnotationTarget::Field]),
+    ("xmlAttribute", &[AnnotationTarget::Field]),
+    ("root", &[AnnotationTarget::Schema, AnnotationTarget::Alias]),
+    ("value", &[AnnotationTarget::EnumValue]),
+    ("allowAlias", &[AnnotationTarget::Enum]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Schema,
+    Enum,
+    Alias,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub namespace: String,
+    pub name: String,
+    pub kind: SymbolKind,
+}
+
+impl Symbol {
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.namespace, self.name)
+    }
+}
+
+/// The target of a field type after resolution. Aliases resolve to the type they name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedType {
+    Basic(String),
+    Schema(Symbol),
+    Enum(Symbol),
+}
+
+/// Every schema, enum and type alias declared in a document, keyed by qualified name.
+///
+/// Schemas declared inline on a field are registered under the field's type name in the
+/// enclosing namespace.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    symbols: BTreeMap<String, Symbol>,
+    /// Declarations of the aliases, keyed by qualified name
+    aliases: BTreeMap<String, TypeAlias>,
+}
+
+impl SymbolTable {
+    pub fn get(&self, qualified_name: &str) -> Option<&Symbol> {
+        self.symbols.get(qualified_name)
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.values()
+    }
+
+    /// Looks up `name` as seen from `namespace`: a qualified name, then a declaration in the
+    /// same namespace, then the single declaration of that name in any other namespace.
+    pub fn lookup(&self, namespace: &str, name: &str) -> Result<&Symbol, SemanticErrorKind> {
+        if let Some(symbol) = self.symbols.get(name) {
+            return Ok(symbol);
+        }
+        if let Some(symbol) = self.symbols.get(&format!("{}.{}", namespace, name)) {
+            return Ok(symbol);
+        }
+
+        let candidates: Vec<&Symbol> = self.symbols.values().filter(|s| s.name == name).collect();
+        match candidates.as_slice() {
+            [symbol] => Ok(symbol),
+            [] => Err(SemanticErrorKind::UnknownType),
+            _ => Err(SemanticErrorKind::AmbiguousType),
+        }
+    }
+
+    pub fn resolve(&self, namespace: &str, type_ref: &TypeRef) -> Result<ResolvedType, SemanticErrorKind> {
+        let mut namespace = namespace.to_string();
+        let mut type_ref = type_ref;
+        let mut visited = HashSet::new();
+        loop {
+            if type_ref.basic {
+                return Ok(ResolvedType::Basic(type_ref.name.clone()));
+            }
+            let symbol = self.lookup(&namespace, &type_ref.name)?;
+            match symbol.kind {
+                SymbolKind::Schema => return Ok(ResolvedType::Schema(symbol.clone())),
+                SymbolKind::Enum => return Ok(ResolvedType::Enum(symbol.clone())),
+                SymbolKind::Alias => {
+                    let qualified_name = symbol.qualified_name();
+                    if !visited.insert(qualified_name.clone()) {
+                        return Err(SemanticErrorKind::CyclicAlias);
+                    }
+                    namespace = symbol.namespace.clone();
+                    type_ref = &self.aliases[&qualified_name].type_ref;
+                },
+            }
+        }
+    }
+
+    /// The declaration of an alias symbol.
+    pub fn alias(&self, symbol: &Symbol) -> Option<&TypeAlias> {
+        self.aliases.get(&symbol.qualified_name())
+    }
+
+    /// A copy of `document` in which every type naming an alias is replaced by the aliased type,
+    /// so that generators only meet schemas, enums and basic types. The `[]` and `?` of the field
+    /// are kept, and the alias's annotations are added ahead of the field's, unless the field
+    /// has an annotation of the same name. Requires a document without semantic errors.
+    pub fn expand_aliases(&self, document: &Document) -> Document {
+        let mut document = document.clone();
+        for namespace in &mut document.namespaces {
+            for schema in &mut namespace.schemas {
+                self.expand_field_aliases(&namespace.name, &mut schema.fields);
+            }
+        }
+        document
+    }
+
+    fn expand_field_aliases(&self, namespace: &str, fields: &mut [Field]) {
+        for field in fields {
+            let (type_ref, annotations) = self.expand_type(namespace, &field.type_ref);
+            field.type_ref = type_ref;
+            let mut inherited: Vec<Annotation> = annotations.into_iter()
+                .filter(|annotation| !field.annotations.iter().any(|a| a.name == annotation.name))
+                .collect();
+            inherited.append(&mut field.annotations);
+            field.annotations = inherited;
+            if let Some(inline) = &mut field.inline_schema {
+                self.expand_field_aliases(namespace, &mut inline.fields);
+            }
+        }
+    }
+
+    /// The type an alias stands for, with the annotations of the aliases followed, outermost
+    /// first. Types that are not aliases are returned as they are, with their map and union
+    /// members expanded.
+    fn expand_type(&self, namespace: &str, type_ref: &TypeRef) -> (TypeRef, Vec<Annotation>) {
+        let alias = match self.lookup(namespace, &type_ref.name) {
+            Ok(symbol) if !type_ref.basic && symbol.kind == SymbolKind::Alias => self.alias(symbol).map(|alias| (symbol.namespace.clone(), alias)),
+            _ => None,
+        };
+        let Some((alias_namespace, alias)) = alias else {
+            let mut expanded = type_ref.clone();
+            if let Some(map) = &mut expanded.map {
+                map.0 = self.expand_type(namespace, &map.0).0;
+                map.1 = self.expand_type(namespace, &map.1).0;
+            }
+            for member in expanded.union.iter_mut().flatten() {
+                *member = self.expand_type(namespace, member).0;
+            }
+            return (expanded, Vec::new());
+        };
+
+        let (mut expanded, inner) = self.expand_type(&alias_namespace, &alias.type_ref);
+        expanded.array |= type_ref.array;
+        expanded.nullable |= type_ref.nullable;
+        expanded.span = type_ref.span;
+        let mut annotations = alias.annotations.clone();
+        annotations.extend(inner.into_iter().filter(|annotation| !alias.annotations.iter().any(|a| a.name == annotation.name)));
+        (expanded, annotations)
+    }
+
+    fn insert(&mut self, symbol: Symbol) -> bool {
+        let key = symbol.qualified_name();
+        if self.symbols.contains_key(&key) {
+            return false;
+        }
+        self.symbols.insert(key, symbol);
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticErrorKind {
+    DuplicateDeclaration,
+    DuplicateField,
+    DuplicateEnumValue,
+    UnknownType,
+    AmbiguousType,
+    InvalidInlineSchema,
+    IllegalAnnotationTarget,
+    /// A `decimal(p, s)`, `map<K, V>` or `union<A, B>` with parameters that do not make a
+    /// type, or an array of an alias of an array type
+    InvalidType,
+    /// An alias that refers to itself, directly or through other aliases
+    CyclicAlias,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticError {
+    pub kind: SemanticErrorKind,
+    /// Dotted path of the offending declaration, e.g. `com.example.people.User.email`.
+    pub path: String,
+    pub message: String,
+    pub span: Span,
+    pub help: Option<String>,
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+impl ToDiagnostic for SemanticError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let label = match self.kind {
+            SemanticErrorKind::DuplicateDeclaration => "declared again here",
+            SemanticErrorKind::DuplicateField => "field declared again here",
+            SemanticErrorKind::DuplicateEnumValue => "in this enum",
+            SemanticErrorKind::UnknownType => "not declared in any namespace",
+            SemanticErrorKind::AmbiguousType => "matches several declarations",
+            SemanticErrorKind::InvalidInlineSchema => "inline schema declared here",
+            SemanticErrorKind::IllegalAnnotationTarget => "not allowed here",
+            SemanticErrorKind::InvalidType => "invalid type",
+            SemanticErrorKind::CyclicAlias => "refers to itself",
+        };
+        let mut diagnostic = Diagnostic::error(&self.message)
+            .with_span(self.span)
+            .with_label(label)
+            .with_note(format!("in `{}`", self.path));
+        if let Some(help) = &self.help {
+            diagnostic = diagnostic.with_help(help);
+        }
+        diagnostic
+    }
+}
+
+/// All errors found in a document; analysis does not stop at the first one.
+#[derive(Debug)]
+pub struct SemanticErrors(pub Vec<SemanticError>);
+
+impl fmt::Display for SemanticErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl std::error::Error for SemanticErrors {}
+
+pub struct SemanticAnalyzer {
+    symbols: SymbolTable,
+    errors: Vec<SemanticError>,
+}
+
+impl SemanticAnalyzer {
+    /// Checks `document` and returns its symbol table, or every error that was found.
+    pub fn analyze(document: &Document) -> Result<SymbolTable, SemanticErrors> {
+        let mut analyzer = Self {
+            symbols: SymbolTable::default(),
+            errors: Vec::new(),
+        };
+
+        analyzer.declare(document);
+        analyzer.check(document);
+
+        if analyzer.errors.is_empty() {
+            Ok(analyzer.symbols)
+        } else {
+            Err(SemanticErrors(analyzer.errors))
+        }
+    }
+
+    /// First pass: register every schema, enum and alias so that forward references resolve.
+    fn declare(&mut self, document: &Document) {
+        for namespace in &document.namespaces {
+            for schema in &namespace.schemas {
+                if let Some(name) = &schema.name {
+                    self.declare_symbol(&namespace.name, name, SymbolKind::Schema, schema.span);
+                }
+                self.declare_inline_schemas(&namespace.name, &schema.fields);
+            }
+            for enumeration in &namespace.enums {
+                self.declare_symbol(&namespace.name, &enumeration.name, SymbolKind::Enum, enumeration.span);
+            }
+            for alias in &namespace.aliases {
+                if self.declare_symbol(&namespace.name, &alias.name, SymbolKind::Alias, alias.span) {
+                    self.symbols.aliases.insert(format!("{}.{}", namespace.name, alias.name), alias.clone());
+                }
+            }
+        }
+    }
+
+    fn declare_inline_schemas(&mut self, namespace: &str, fields: &[Field]) {
+        for field in fields {
+            if let Some(inline) = &field.inline_schema {
+                if !field.type_ref.basic {
+                    self.declare_symbol(namespace, &field.type_ref.name, SymbolKind::Schema, field.type_ref.span);
+                }
+                self.declare_inline_schemas(namespace, &inline.fields);
+            }
+        }
+    }
+
+    fn declare_symbol(&mut self, namespace: &str, name: &str, kind: SymbolKind, span: Span) -> bool {
+        let symbol = Symbol {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            kind,
+        };
+        let path = symbol.qualified_name();
+        let declared = self.symbols.insert(symbol);
+        if !declared {
+            self.error(SemanticErrorKind::DuplicateDeclaration, path, span, format!("`{}` is declared more than once", name));
+        }
+        declared
+    }
+
+    /// Second pass: resolve field types and validate annotations.
+    fn check(&mut self, document: &Document) {
+        for namespace in &document.namespaces {
+            for schema in &namespace.schemas {
+                let path = format!("{}.{}", namespace.name, schema.name.as_deref().unwrap_or_default());
+                self.check_annotations(&path, &schema.annotations, AnnotationTarget::Schema, None);
+                self.check_extends(&namespace.name, &path, schema);
+                self.check_schema(&namespace.name, &path, schema);
+            }
+            for enumeration in &namespace.enums {
+                let path = format!("{}.{}", namespace.name, enumeration.name);
+                self.check_annotations(&path, &enumeration.annotations, AnnotationTarget::Enum, None);
+
+                let mut seen = HashSet::new();
+                for value in &enumeration.values {
+                    if !seen.insert(value) {
+                        self.error(SemanticErrorKind::DuplicateEnumValue, path.clone(), enumeration.span, format!("Duplicate enum value `{}`", value));
+                    }
+                }
+                for (value, annotations) in &enumeration.value_annotations {
+                    self.check_annotations(&format!("{}.{}", path, value), annotations, AnnotationTarget::EnumValue, None);
+                }
+            }
+            for alias in &namespace.aliases {
+                let path = format!("{}.{}", namespace.name, alias.name);
+                self.check_annotations(&path, &alias.annotations, AnnotationTarget::Alias, None);
+                if !self.check_alias_cycle(&namespace.name, &path, alias) {
+                    self.check_type(&namespace.name, &path, &alias.type_ref);
+                }
+            }
+        }
+    }
+
+    fn check_schema(&mut self, namespace: &str, path: &str, schema: &Schema) {
+        let mut seen = HashSet::new();
+        for field in &schema.fields {
+            let field_path = format!("{}.{}", path, field.name);
+            if !seen.insert(&field.name) {
+                self.error(SemanticErrorKind::DuplicateField, field_path.clone(), field.span, format!("Duplicate field `{}`", field.name));
+            }
+
+            let resolved = self.check_type(namespace, &field_path, &field.type_ref);
+            self.check_annotations(&field_path, &field.annotations, AnnotationTarget::Field, Some((field, resolved.as_ref())));
+            self.check_relation(namespace, &field_path, field);
+
+            if let Some(inline) = &field.inline_schema {
+                if field.type_ref.basic {
+                    self.error(SemanticErrorKind::InvalidInlineSchema, field_path.clone(), inline.span,
+                        format!("Inline schema cannot be declared with basic type `{}`", field.type_ref.name));
+                }
+                self.check_schema(namespace, &field_path, inline);
+            }
+        }
+    }
+
+    /// Resolves a field or alias type, reporting unknown types, aliases that refer to
+    /// themselves and invalid parameters of `decimal`, `map` and `union` types.
+    fn check_type(&mut self, namespace: &str, path: &str, type_ref: &TypeRef) -> Option<ResolvedType> {
+        if let Some((precision, scale)) = type_ref.decimal {
+            if precision == 0 || scale > precision {
+                self.errors.push(SemanticError {
+                    kind: SemanticErrorKind::InvalidType,
+                    path: path.to_string(),
+                    message: format!("Invalid decimal precision {} and scale {}", precision, scale),
+                    span: type_ref.span,
+                    help: Some("the precision must be positive and the scale cannot exceed it".to_string()),
+                });
+            }
+        }
+        if let Some(map) = &type_ref.map {
+            let (key, value) = map.as_ref();
+            if !key.basic || key.array || key.nullable || !MAP_KEY_TYPES.contains(&key.name.as_str()) {
+                self.errors.push(SemanticError {
+                    kind: SemanticErrorKind::InvalidType,
+                    path: path.to_string(),
+                    message: format!("`{}` cannot be a map key", key),
+                    span: key.span,
+                    help: Some(format!("map keys are one of `{}`", MAP_KEY_TYPES.join("`, `"))),
+                });
+            }
+            self.check_type(namespace, path, value);
+        }
+        for member in type_ref.union.iter().flatten() {
+            if member.nullable || self.aliased_type(namespace, member).union.is_some() {
+                self.errors.push(SemanticError {
+                    kind: SemanticErrorKind::InvalidType,
+                    path: path.to_string(),
+                    message: format!("`{}` cannot be a union member", member),
+                    span: member.span,
+                    help: Some("make the union itself nullable, and list the members of nested unions in the outer one".to_string()),
+                });
+            }
+            self.check_type(namespace, path, member);
+        }
+        if type_ref.array && !type_ref.basic && self.aliases_array(namespace, type_ref) {
+            self.errors.push(SemanticError {
+                kind: SemanticErrorKind::InvalidType,
+                path: path.to_string(),
+                message: format!("`{}` is an array type and cannot be made an array", type_ref.name),
+                span: type_ref.span,
+                help: None,
+            });
+        }
+
+        match self.symbols.resolve(namespace, type_ref) {
+            Ok(resolved) => Some(resolved),
+            Err(kind) => {
+                let (message, help) = match kind {
+                    SemanticErrorKind::CyclicAlias => (format!("Alias `{}` refers to itself", type_ref.name), None),
+                    SemanticErrorKind::AmbiguousType => (
+                        format!("Type `{}` is declared in several namespaces", type_ref.name),
+                        Some(format!("qualify it, e.g. `{}`", self.candidates(&type_ref.name).join("` or `"))),
+                    ),
+                    _ => (
+                        format!("Unknown type `{}`", type_ref.name),
+                        self.suggest(&type_ref.name).map(|name| format!("did you mean `{}`?", name)),
+                    ),
+                };
+                self.errors.push(SemanticError { kind, path: path.to_string(), message, span: type_ref.span, help });
+                None
+            }
+        }
+    }
+
+    /// The type `type_ref` stands for once aliases are followed: the type of the last alias in
+    /// the chain, or `type_ref` itself if it does not name an alias.
+    fn aliased_type<'t>(&'t self, namespace: &str, type_ref: &'t TypeRef) -> &'t TypeRef {
+        let mut namespace = namespace.to_string();
+        let mut type_ref = type_ref;
+        let mut visited = HashSet::new();
+        while let Ok(symbol) = self.symbols.lookup(&namespace, &type_ref.name) {
+            let Some(alias) = self.symbols.alias(symbol).filter(|_| !type_ref.basic && visited.insert(symbol.qualified_name())) else {
+                break;
+            };
+            namespace = symbol.namespace.clone();
+            type_ref = &alias.type_ref;
+        }
+        type_ref
+    }
+
+    /// Whether `type_ref` names an alias of an array type, through any number of aliases.
+    fn aliases_array(&self, namespace: &str, type_ref: &TypeRef) -> bool {
+        let mut namespace = namespace.to_string();
+        let mut type_ref = type_ref;
+        let mut visited = HashSet::new();
+        while let Ok(symbol) = self.symbols.lookup(&namespace, &type_ref.name) {
+            let Some(alias) = self.symbols.alias(symbol).filter(|_| !type_ref.basic && visited.insert(symbol.qualified_name())) else {
+                break;
+            };
+            if alias.type_ref.array {
+                return true;
+            }
+            namespace = symbol.namespace.clone();
+            type_ref = &alias.type_ref;
+        }
+        false
+    }
+
+    /// Reports an alias whose type refers back to it, also through map values or union
+    /// members, as expanding it would never end. Returns whether it does.
+    fn check_alias_cycle(&mut self, namespace: &str, path: &str, alias: &TypeAlias) -> bool {
+        fn refers_to(symbols: &SymbolTable, namespace: &str, type_ref: &TypeRef, target: &str, visited: &mut HashSet<String>) -> bool {
+            let nested = type_ref.map.iter().flat_map(|map| [&map.0, &map.1]).chain(type_ref.union.iter().flatten());
+            if nested.into_iter().any(|nested| refers_to(symbols, namespace, nested, target, visited)) {
+                return true;
+            }
+            let Ok(symbol) = symbols.lookup(namespace, &type_ref.name) else {
+                return false;
+            };
+            let Some(alias) = symbols.alias(symbol).filter(|_| !type_ref.basic) else {
+                return false;
+            };
+            let name = symbol.qualified_name();
+            name == target || (visited.insert(name) && refers_to(symbols, &symbol.namespace, &alias.type_ref, target, visited))
+        }
+
+        let cyclic = refers_to(&self.symbols, namespace, &alias.type_ref, path, &mut HashSet::new());
+        if cyclic {
+            self.errors.push(SemanticError {
+                kind: SemanticErrorKind::CyclicAlias,
+                path: path.to_string(),
+                message: format!("Alias `{}` refers to itself", alias.name),
+                span: alias.span,
+                help: Some("only schemas can refer to themselves".to_string()),
+            });
+        }
+        cyclic
+    }
+
+    /// `@extends(Base)` names the schema whose fields the annotated schema inherits, and
+    /// `@include(Mixin)` a schema whose fields it has besides its own.
+    fn check_extends(&mut self, namespace: &str, path: &str, schema: &Schema) {
+        for annotation in schema.annotations.iter().filter(|a| a.name == "extends" || a.name == "include") {
+            let (arg, verb, role) = match annotation.name.as_str() {
+                "extends" => ("base", "extend", "Base schema"),
+                _ => ("schema", "include", "Mixin"),
+            };
+            let Some(Literal::Identifier(base) | Literal::String(base)) = annotation.arg(arg) else {
+                self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                    format!("@{} requires the name of a schema", annotation.name));
+                continue;
+            };
+            match self.symbols.lookup(namespace, base) {
+                Ok(symbol) if symbol.kind == SymbolKind::Schema && symbol.qualified_name() != path => {},
+                Ok(symbol) if symbol.kind == SymbolKind::Schema => {
+                    self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                        format!("A schema cannot {} itself", verb));
+                },
+                Ok(symbol) => {
+                    let kind = if symbol.kind == SymbolKind::Enum { "enum" } else { "alias" };
+                    self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                        format!("@{} requires a schema, found {} `{}`", annotation.name, kind, base));
+                },
+                Err(kind) => {
+                    let message = match kind {
+                        SemanticErrorKind::AmbiguousType => format!("{} `{}` is declared in several namespaces", role, base),
+                        _ => format!("Unknown {} `{}`", role.to_lowercase(), base),
+                    };
+                    self.error(kind, path.to_string(), annotation.span, message);
+                },
+            }
+        }
+    }
+
+    /// `@relation(schema = Target, field = "key")` on a field holding a value rather than a
+    /// schema names the schema whose `key` field the value refers to.
+    fn check_relation(&mut self, namespace: &str, path: &str, field: &Field) {
+        for annotation in field.annotations.iter().filter(|a| a.name == "relation") {
+            let Some(target) = annotation.args.iter().find(|arg| arg.name.as_deref() == Some("schema")).map(|arg| &arg.value) else {
+                continue;
+            };
+            let (Literal::Identifier(name) | Literal::String(name)) = target else {
+                self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                    "@relation requires the name of a schema".to_string());
+                continue;
+            };
+            match self.symbols.lookup(namespace, name) {
+                Ok(symbol) if symbol.kind == SymbolKind::Schema => {},
+                Ok(_) => {
+                    self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                        format!("@relation requires a schema, found `{}`", name));
+                },
+                Err(kind) => {
+                    let message = match kind {
+                        SemanticErrorKind::AmbiguousType => format!("Schema `{}` is declared in several namespaces", name),
+                        _ => format!("Unknown schema `{}`", name),
+                    };
+                    self.error(kind, path.to_string(), annotation.span, message);
+                },
+            }
+        }
+    }
+
+    fn check_annotations(&mut self, path: &str, annotations: &[Annotation], target: AnnotationTarget, field: Option<(&Field, Option<&ResolvedType>)>) {
+        for annotation in annotations {
+            let Some((_, targets)) = KNOWN_ANNOTATIONS.iter().find(|(name, _)| *name == annotation.name) else {
+                continue;
+            };
+            if !targets.contains(&target) {
+                self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                    format!("@{} cannot be applied to {:?} declarations", annotation.name, target));
+                continue;
+            }
+
+            let Some((field, resolved)) = field else { continue };
+            match annotation.name.as_str() {
+                "id" if field.type_ref.array || field.type_ref.nullable => {
+                    self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                        "@id cannot be applied to nullable or array fields".to_string());
+                },
+                "size" if !field.type_ref.array => {
+                    self.error(SemanticErrorKind::IllegalAnnotationTarget, path.to_string(), annotation.span,
+                        "@size can only be applied to array fields".to_string());
+                },
+                "relation" if !annotation.args.iter().any(|arg| arg.name.as_deref() == Some("schema")) && matches!(resolved, Some(ResolvedType::Basic(_) | ResolvedType::Enum(_))) => {
+                    self.errors.push(SemanticError {
+                        kind: SemanticErrorKind::IllegalAnnotationTarget,
+                        path: path.to_string(),
+                        message: format!("@relation requires a schema type, found `{}`", field.type_ref.name),
+                        span: annotation.span,
+                        help: Some("name the schema the value refers to with `schema = ..`".to_string()),
+                    });
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn error(&mut self, kind: SemanticErrorKind, path: String, span: Span, message: String) {
+        self.errors.push(SemanticError { kind, path, message, span, help: None });
+    }
+
+    fn candidates(&self, name: &str) -> Vec<String> {
+        self.symbols.symbols()
+            .filter(|s| s.name == name)
+            .map(|s| s.qualified_name())
+            .collect()
+    }
+
+    /// The declared name closest to `name`, if any is within a couple of edits.
+    fn suggest(&self, name: &str) -> Option<String> {
+        self.symbols.symbols()
+            .map(|s| (Self::edit_distance(&s.name, name), &s.name))
+            .filter(|(distance, _)| *distance <= 2)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate.clone())
+    }
+
+    fn edit_distance(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut previous: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut current = vec![i + 1];
+            for (j, cb) in b.iter().enumerate() {
+                let substitution = previous[j] + usize::from(ca != *cb);
+                current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+            }
+            previous = current;
+        }
+        previous[b.len()]
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::diagnostics::Span;
+
+/// A parsed `.schema` file. Namespaces declared more than once are merged, in order of first appearance.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Document {
+    pub namespaces: Vec<Namespace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Namespace {
+    pub name: String,
+    /// The comment above the first declaration of the namespace
+    pub comment: Option<String>,
+    pub schemas: Vec<Schema>,
+    pub enums: Vec<Enum>,
+    pub aliases: Vec<TypeAlias>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Schema {
+    /// `None` for inline schemas declared on a field.
+    pub name: Option<String>,
+    pub annotations: Vec<Annotation>,
+    pub comment: Option<String>,
+    pub fields: Vec<Field>,
+    /// Span of the name, or of the `schema` keyword for inline schemas.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Field {
+    pub name: String,
+    pub type_ref: TypeRef,
+    pub annotations: Vec<Annotation>,
+    pub comment: Option<String>,
+    pub inline_schema: Option<Schema>,
+    /// Span of the field name.
+    pub span: Span,
+}
+
+/// A field type such as `int`, `Profile?`, `Order[]`, `decimal(10, 2)`, `map<string, int>` or
+/// `union<int, string>`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeRef {
+    /// The type name; `decimal`, `map` and `union` for the parameterized types.
+    pub name: String,
+    /// True for the built-in types (`int`, `string`, `map`, ...), false for schema, enum and alias
+    /// references.
+    pub basic: bool,
+    /// Precision and scale of a `decimal(p, s)`.
+    pub decimal: Option<(u32, u32)>,
+    /// Key and value types of a `map<K, V>`.
+    pub map: Option<Box<(TypeRef, TypeRef)>>,
+    /// Member types of a `union<A, B, ...>`.
+    pub union: Option<Vec<TypeRef>>,
+    pub array: bool,
+    pub nullable: bool,
+    /// Span of the type name, without `[]` or `?`.
+    pub span: Span,
+}
+
+/// A named type such as `type Percentage = float @minInclusive(0) @maxInclusive(100);`. Fields
+/// of the alias type take the aliased type and the alias's annotations.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeAlias {
+    pub name: String,
+    pub type_ref: TypeRef,
+    pub annotations: Vec<Annotation>,
+    pub comment: Option<String>,
+    /// Span of the name.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Enum {
+    pub name: String,
+    pub annotations: Vec<Annotation>,
+    pub comment: Option<String>,
+    pub values: Vec<String>,
+    /// Comments of enum values, keyed by value
+    pub value_comments: BTreeMap<String, String>,
+    /// Annotations of enum values, like `@value(number = "1")`, keyed by value
+    pub value_annotations: BTreeMap<String, Vec<Annotation>>,
+    /// Span of the name.
+    pub span: Span,
+}
+
+/// An annotation such as `@id` or `@relation(field = "userId", onDelete = "CASCADE")`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Annotation {
+    pub name: String,
+    pub args: Vec<AnnotationArg>,
+    pub span: Span,
+}
+
+/// A single annotation argument; `name` is `None` for positional arguments like `@generated("UUID")`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnnotationArg {
+    pub name: Option<String>,
+    pub value: Literal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Literal {
+    String(String),
+    Number(String),
+    Identifier(String),
+}
+
+impl Document {
+    pub fn namespace(&self, name: &str) -> Option<&Namespace> {
+        self.namespaces.iter().find(|ns| ns.name == name)
+    }
+
+    pub(crate) fn namespace_entry(&mut self, name: &str) -> &mut Namespace {
+        match self.namespaces.iter().position(|ns| ns.name == name) {
+            Some(index) => &mut self.namespaces[index],
+            None => {
+                self.namespaces.push(Namespace {
+                    name: name.to_string(),
+                    comment: None,
+                    schemas: Vec::new(),
+                    enums: Vec::new(),
+                    aliases: Vec::new(),
+                });
+                self.namespaces.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.decimal, &self.map, &self.union) {
+            (Some((precision, scale)), _, _) => write!(f, "{}({}, {})", self.name, precision, scale)?,
+            (None, Some(map), _) => write!(f, "{}<{}, {}>", self.name, map.0, map.1)?,
+            (None, None, Some(members)) => {
+                let members: Vec<String> = members.iter().map(|member| member.to_string()).collect();
+                write!(f, "{}<{}>", self.name, members.join(", "))?
+            },
+            (None, None, None) => write!(f, "{}", self.name)?,
+        }
+        write!(f, "{}{}", if self.array { "[]" } else { "" }, if self.nullable { "?" } else { "" })
+    }
+}
+
+impl Annotation {
+    /// Looks up an argument by name, falling back to the first positional argument.
+    pub fn arg(&self, name: &str) -> Option<&Literal> {
+        self.args.iter()
+            .find(|a| a.name.as_deref() == Some(name))
+            .or_else(|| self.args.iter().find(|a| a.name.is_none()))
+            .map(|a| &a.value)
+    }
+}
+
+impl Literal {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Literal::String(s) | Literal::Number(s) | Literal::Identifier(s) => s,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+
+use super::ast::{Annotation, Document, Enum, Field, Schema, TypeAlias};
+use super::formatter::SchemataFormatter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A single difference between two documents, e.g. a field whose type changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Dotted path of the declaration, e.g. `com.example.people.User.email`.
+    pub path: String,
+    /// What changed, for modifications, e.g. ``type `string` -> `string?` ``.
+    pub detail: Option<String>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Modified => '~',
+        };
+        write!(f, "{} {}", marker, self.path)?;
+        if let Some(detail) = &self.detail {
+            write!(f, ": {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two documents declaration by declaration, ignoring formatting, comments and
+/// declaration order.
+pub struct SchemataDiff {
+    changes: Vec<Change>,
+}
+
+impl SchemataDiff {
+    pub fn compare(old: &Document, new: &Document) -> Vec<Change> {
+        let mut diff = Self { changes: Vec::new() };
+
+        for old_namespace in &old.namespaces {
+            let Some(new_namespace) = new.namespace(&old_namespace.name) else {
+                diff.push(ChangeKind::Removed, old_namespace.name.clone(), None);
+                continue;
+            };
+            let path = |name: &str| format!("{}.{}", old_namespace.name, name);
+
+            for old_schema in &old_namespace.schemas {
+                let name = old_schema.name.as_deref().unwrap_or_default();
+                match new_namespace.schemas.iter().find(|s| s.name == old_schema.name) {
+                    Some(new_schema) => diff.compare_schemas(&path(name), old_schema, new_schema),
+                    None => diff.push(ChangeKind::Removed, path(name), None),
+                }
+            }
+            for new_schema in &new_namespace.schemas {
+                if !old_namespace.schemas.iter().any(|s| s.name == new_schema.name) {
+                    diff.push(ChangeKind::Added, path(new_schema.name.as_deref().unwrap_or_default()), None);
+                }
+            }
+
+            for old_enum in &old_namespace.enums {
+                match new_namespace.enums.iter().find(|e| e.name == old_enum.name) {
+                    Some(new_enum) => diff.compare_enums(&path(&old_enum.name), old_enum, new_enum),
+                    None => diff.push(ChangeKind::Removed, path(&old_enum.name), None),
+                }
+            }
+            for new_enum in &new_namespace.enums {
+                if !old_namespace.enums.iter().any(|e| e.name == new_enum.name) {
+                    diff.push(ChangeKind::Added, path(&new_enum.name), None);
+                }
+            }
+
+            for old_alias in &old_namespace.aliases {
+                match new_namespace.aliases.iter().find(|a| a.name == old_alias.name) {
+                    Some(new_alias) => diff.compare_aliases(&path(&old_alias.name), old_alias, new_alias),
+                    None => diff.push(ChangeKind::Removed, path(&old_alias.name), None),
+                }
+            }
+            for new_alias in &new_namespace.aliases {
+                if !old_namespace.aliases.iter().any(|a| a.name == new_alias.name) {
+                    diff.push(ChangeKind::Added, path(&new_alias.name), Some(format!("`{}`", new_alias.type_ref)));
+                }
+            }
+        }
+        for new_namespace in &new.namespaces {
+            if old.namespace(&new_namespace.name).is_none() {
+                diff.push(ChangeKind::Added, new_namespace.name.clone(), None);
+            }
+        }
+        diff.changes
+    }
+
+    fn compare_schemas(&mut self, path: &str, old: &Schema, new: &Schema) {
+        let old_annotations = Self::annotations(&old.annotations);
+        let new_annotations = Self::annotations(&new.annotations);
+        if old_annotations != new_annotations {
+            self.push(ChangeKind::Modified, path.to_string(), Some(format!("annotations `{}` -> `{}`", old_annotations, new_annotations)));
+        }
+
+        for old_field in &old.fields {
+            let field_path = format!("{}.{}", path, old_field.name);
+            match new.fields.iter().find(|f| f.name == old_field.name) {
+                Some(new_field) => self.compare_fields(&field_path, old_field, new_field),
+                None => self.push(ChangeKind::Removed, field_path, None),
+            }
+        }
+        for new_field in &new.fields {
+            if !old.fields.iter().any(|f| f.name == new_field.name) {
+                let detail = format!("`{}`", new_field.type_ref);
+                self.push(ChangeKind::Added, format!("{}.{}", path, new_field.name), Some(detail));
+            }
+        }
+    }
+
+    fn compare_fields(&mut self, path: &str, old: &Field, new: &Field) {
+        let old_type = old.type_ref.to_string();
+        let new_type = new.type_ref.to_string();
+        if old_type != new_type {
+            self.push(ChangeKind::Modified, path.to_string(), Some(format!("type `{}` -> `{}`", old_type, new_type)));
+        }
+
+        let old_annotations = Self::annotations(&old.annotations);
+        let new_annotations = Self::annotations(&new.annotations);
+        if old_annotations != new_annotations {
+            self.push(ChangeKind::Modified, path.to_string(), Some(format!("annotations `{}` -> `{}`", old_annotations, new_annotations)));
+        }
+
+        match (&old.inline_schema, &new.inline_schema) {
+            (Some(old_inline), Some(new_inline)) => self.compare_schemas(path, old_inline, new_inline),
+            (Some(_), None) => self.push(ChangeKind::Removed, format!("{}.schema", path), None),
+            (None, Some(_)) => self.push(ChangeKind::Added, format!("{}.schema", path), None),
+            (None, None) => {}
+        }
+    }
+
+    fn compare_enums(&mut self, path: &str, old: &Enum, new: &Enum) {
+        for value in &old.values {
+            if !new.values.contains(value) {
+                self.push(ChangeKind::Removed, format!("{}.{}", path, value), None);
+            }
+        }
+        for value in &new.values {
+            if !old.values.contains(value) {
+                self.push(ChangeKind::Added, format!("{}.{}", path, value), None);
+            }
+        }
+    }
+
+    fn compare_aliases(&mut self, path: &str, old: &TypeAlias, new: &TypeAlias) {
+        let old_type = old.type_ref.to_string();
+        let new_type = new.type_ref.to_string();
+        if old_type != new_type {
+            self.push(ChangeKind::Modified, path.to_string(), Some(format!("type `{}` -> `{}`", old_type, new_type)));
+        }
+
+        let old_annotations = Self::annotations(&old.annotations);
+        let new_annotations = Self::annotations(&new.annotations);
+        if old_annotations != new_annotations {
+            self.push(ChangeKind::Modified, path.to_string(), Some(format!("annotations `{}` -> `{}`", old_annotations, new_annotations)));
+        }
+    }
+
+    fn annotations(annotations: &[Annotation]) -> String {
+        annotations.iter().map(SchemataFormatter::format_annotation).collect::<Vec<_>>().join(" ")
+    }
+
+    fn push(&mut self, kind: ChangeKind, path: String, detail: Option<String>) {
+        self.changes.push(Change { kind, path, detail });
+    }
+}
@@ -0,0 +1,167 @@
+use super::ast::{Annotation, Document, Enum, Field, Literal, Schema, TypeAlias};
+
+const INDENT: &str = "    ";
+
+/// Prints a parsed document back as canonical Schemata source.
+///
+/// Namespaces are written as `namespace x;` declarations, one declaration per line, with
+/// four-space indentation. Comments attached to declarations are kept: single-line comments of
+/// single-line fields and of enum values are written after them, all others above the declaration.
+pub struct SchemataFormatter;
+
+impl SchemataFormatter {
+    pub fn format(document: &Document) -> String {
+        let mut blocks = Vec::new();
+        for namespace in &document.namespaces {
+            blocks.push(format!("{}namespace {};\n", Self::leading_comment(namespace.comment.as_deref(), ""), namespace.name));
+            for schema in &namespace.schemas {
+                blocks.push(Self::format_schema(schema));
+            }
+            for enumeration in &namespace.enums {
+                blocks.push(Self::format_enum(enumeration));
+            }
+            for alias in &namespace.aliases {
+                blocks.push(Self::format_alias(alias));
+            }
+        }
+        blocks.join("\n")
+    }
+
+    fn format_schema(schema: &Schema) -> String {
+        let mut out = Self::leading_comment(schema.comment.as_deref(), "");
+        out.push_str("schema ");
+        out.push_str(schema.name.as_deref().unwrap_or_default());
+        for annotation in &schema.annotations {
+            out.push(' ');
+            out.push_str(&Self::format_annotation(annotation));
+        }
+        out.push_str(" {\n");
+        Self::format_fields(&mut out, &schema.fields, 1);
+        out.push_str("}\n");
+        out
+    }
+
+    /// Trailing comments of consecutive single-line fields are aligned in one column.
+    fn format_fields(out: &mut String, fields: &[Field], depth: usize) {
+        let indent = INDENT.repeat(depth);
+        let lines: Vec<(String, Option<&str>)> = fields.iter()
+            .map(|field| {
+                let code = Self::format_field(field, &indent, depth);
+                let trailing = field.comment.as_deref().filter(|c| !c.contains('\n') && !code.contains('\n'));
+                (code, trailing)
+            })
+            .collect();
+        let width = lines.iter()
+            .filter(|(_, trailing)| trailing.is_some())
+            .map(|(code, _)| code.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (field, (code, trailing)) in fields.iter().zip(lines) {
+            if trailing.is_none() {
+                out.push_str(&Self::leading_comment(field.comment.as_deref(), &indent));
+            }
+            out.push_str(&code);
+            if let Some(comment) = trailing {
+                out.push_str(&" ".repeat(width - code.chars().count()));
+                out.push_str(" # ");
+                out.push_str(comment);
+            }
+            out.push('\n');
+        }
+    }
+
+    /// A field without its comment or final newline.
+    fn format_field(field: &Field, indent: &str, depth: usize) -> String {
+        let mut out = format!("{}{} {}", indent, field.name, field.type_ref);
+        for annotation in &field.annotations {
+            out.push(' ');
+            out.push_str(&Self::format_annotation(annotation));
+        }
+        if let Some(inline) = &field.inline_schema {
+            out.push_str(" schema {\n");
+            Self::format_fields(&mut out, &inline.fields, depth + 1);
+            out.push_str(indent);
+            out.push('}');
+        }
+        out
+    }
+
+    fn format_enum(enumeration: &Enum) -> String {
+        let mut out = Self::leading_comment(enumeration.comment.as_deref(), "");
+        out.push_str("enum ");
+        out.push_str(&enumeration.name);
+        for annotation in &enumeration.annotations {
+            out.push(' ');
+            out.push_str(&Self::format_annotation(annotation));
+        }
+        out.push_str(" {\n");
+        let trailing = |value: &String| enumeration.value_comments.get(value).filter(|c| !c.contains('\n'));
+        let entries: Vec<String> = enumeration.values.iter()
+            .map(|value| {
+                let mut entry = value.clone();
+                for annotation in enumeration.value_annotations.get(value).into_iter().flatten() {
+                    entry.push(' ');
+                    entry.push_str(&Self::format_annotation(annotation));
+                }
+                entry
+            })
+            .collect();
+        let width = enumeration.values.iter().zip(&entries)
+            .filter(|(value, _)| trailing(value).is_some())
+            .map(|(_, entry)| entry.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (value, entry) in enumeration.values.iter().zip(&entries) {
+            match trailing(value) {
+                Some(comment) => {
+                    out.push_str(&format!("{}{},{} # {}\n", INDENT, entry, " ".repeat(width - entry.chars().count()), comment));
+                },
+                None => {
+                    out.push_str(&Self::leading_comment(enumeration.value_comments.get(value).map(String::as_str), INDENT));
+                    out.push_str(&format!("{}{},\n", INDENT, entry));
+                },
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn format_alias(alias: &TypeAlias) -> String {
+        let mut out = Self::leading_comment(alias.comment.as_deref(), "");
+        out.push_str(&format!("type {} = {}", alias.name, alias.type_ref));
+        for annotation in &alias.annotations {
+            out.push(' ');
+            out.push_str(&Self::format_annotation(annotation));
+        }
+        out.push_str(";\n");
+        out
+    }
+
+    pub(crate) fn format_annotation(annotation: &Annotation) -> String {
+        if annotation.args.is_empty() {
+            return format!("@{}", annotation.name);
+        }
+        let args: Vec<String> = annotation.args.iter()
+            .map(|arg| {
+                let value = match &arg.value {
+                    Literal::String(s) => format!("\"{}\"", s),
+                    Literal::Number(s) | Literal::Identifier(s) => s.clone(),
+                };
+                match &arg.name {
+                    Some(name) => format!("{} = {}", name, value),
+                    None => value,
+                }
+            })
+            .collect();
+        format!("@{}({})", annotation.name, args.join(", "))
+    }
+
+    fn leading_comment(comment: Option<&str>, indent: &str) -> String {
+        comment.map(|comment| {
+            comment.lines()
+                .map(|line| if line.is_empty() { format!("{}#\n", indent) } else { format!("{}# {}\n", indent, line) })
+                .collect()
+        }).unwrap_or_default()
+    }
+}
@@ -1,85 +1,1618 @@
+use std::collections::{BTreeMap, HashSet};
+
 use tera::{Context, Tera};
-use super::types::{SchemataEnum, SchemataField, SchemataNamespace, SchemataSchema};
-use crate::xsd::types::{XsdComplexType, XsdElement, XsdSchema, XsdSimpleType};
+use super::type_mapping;
+use super::types::{SchemataAlias, SchemataEnum, SchemataField, SchemataNamespace, SchemataSchema};
+use crate::xsd::qname::{split_expanded_name, XSD_NAMESPACE};
+use crate::xsd::types::{XsdAppInfo, XsdAttribute, XsdAttributeGroup, XsdComplexType, XsdCompositor, XsdCompositorKind, XsdDerivation, XsdDerivationMethod, XsdElement, XsdGroup, XsdIdentityConstraint, XsdIdentityConstraintKind, XsdParticle, XsdRestriction, XsdSchema, XsdSimpleType, XsdWildcard};
+
+/// Occurrence bounds of a particle; `None` is `unbounded`.
+type Occurs = (u64, Option<u64>);
+
+/// A global element declaration and the name of the schema written for it.
+#[derive(Debug)]
+struct RootElement {
+    /// Index of the document declaring the element
+    document: usize,
+    /// `{namespace}name`, as `ref`s name the element
+    name: String,
+    element: XsdElement,
+    schema_name: String,
+    /// Whether the element is typed by a complex type of the same name, which is annotated
+    /// instead of being aliased
+    annotates_type: bool,
+}
+
+/// A model group or attribute group and the name of the schema written for it with
+/// [`Groups::Mixins`].
+#[derive(Debug)]
+struct Mixin {
+    /// Index of the document declaring the group
+    document: usize,
+    /// `{namespace}name`, as references name the group
+    name: String,
+    /// Whether this is an attribute group; model groups and attribute groups are named apart
+    attributes: bool,
+    schema_name: String,
+}
+
+/// The model groups and attribute groups of every document by `{namespace}name`, with the
+/// mixins written for them.
+struct GroupDefinitions<'a> {
+    groups: Vec<(String, XsdGroup)>,
+    attribute_groups: Vec<(String, XsdAttributeGroup)>,
+    mixins: &'a [Mixin],
+}
 
+/// A schema by the name of its namespace and the path leading to it: the name of a top-level
+/// schema, then the names of the fields holding the inline schemas down to it.
+type SchemaLocation = (String, Vec<String>);
+
+/// An identity constraint with the schema of the element declaring it, which its XPaths start
+/// from.
+#[derive(Debug)]
+struct IdentityConstraint {
+    /// `{namespace}name`, as `refer` names the constraint
+    name: String,
+    location: SchemaLocation,
+    constraint: XsdIdentityConstraint,
+}
+
+/// Documentation lines longer than this are wrapped into several comment lines.
+const COMMENT_WIDTH: usize = 100;
+
+/// How types derived by extension from another complex type are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Inheritance {
+    /// The fields of the base types are copied into the derived schema, ahead of its own.
+    #[default]
+    Flatten,
+    /// The derived schema keeps its own fields and is annotated with `@extends(Base)`.
+    /// Inline schemas cannot be annotated and are always flattened.
+    Extends,
+}
+
+/// Where the schemas of anonymous complex types are written. Either way they are named after
+/// the path of fields that leads to them, as `OrderItems` for the `items` field of `Order`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InlineSchemas {
+    /// Each schema is written inline, under the field it types.
+    #[default]
+    Nested,
+    /// Each schema is written as a top-level schema following the one that uses it.
+    Hoist,
+}
+
+/// How model groups and attribute groups, which types reuse by reference, are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Groups {
+    /// The content of a group is written in place of every reference to it.
+    #[default]
+    Expand,
+    /// Each group is written as a schema marked `@mixin`, and the schemas referring to it are
+    /// annotated with `@include(Group)`. References that are optional, repeated, nested in a
+    /// choice or made from an anonymous type cannot be annotated and are expanded.
+    Mixins,
+}
 
 pub struct SchemataGenerator {
     tera: Tera,
+    inheritance: Inheritance,
+    inline_schemas: InlineSchemas,
+    groups: Groups,
 }
 
 impl SchemataGenerator {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let mut tera = Tera::default();
         tera.add_raw_template("schema", include_str!("../../templates/schemata/schema.tera"))?;
-        Ok(Self { tera })
+        Ok(Self { tera, inheritance: Inheritance::default(), inline_schemas: InlineSchemas::default(), groups: Groups::default() })
+    }
+
+    pub fn with_inheritance(mut self, inheritance: Inheritance) -> Self {
+        self.inheritance = inheritance;
+        self
+    }
+
+    pub fn with_inline_schemas(mut self, inline_schemas: InlineSchemas) -> Self {
+        self.inline_schemas = inline_schemas;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: Groups) -> Self {
+        self.groups = groups;
+        self
     }
 
     pub fn generate(&self, xsd_schema: XsdSchema) -> Result<String, Box<dyn std::error::Error>> {
-        let mut context = Context::new();
+        self.generate_all(std::slice::from_ref(&xsd_schema))
+    }
 
-        let namespaces = self.get_namespaces(&xsd_schema);
-        context.insert("namespaces", &namespaces);
+    /// Renders several XSD documents into a single Schemata file, one namespace per target namespace.
+    pub fn generate_all(&self, xsd_schemas: &[XsdSchema]) -> Result<String, Box<dyn std::error::Error>> {
+        let namespaces = self.get_namespaces(xsd_schemas);
+        self.render(&namespaces)
+    }
+
+    /// Renders namespaces built by any importer into Schemata source.
+    pub fn render(&self, namespaces: &[SchemataNamespace]) -> Result<String, Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        context.insert("namespaces", namespaces);
 
         Ok(self.tera.render("schema", &context)?)
     }
 
-    pub(crate) fn get_namespaces(&self, xsd_schema: &XsdSchema) -> Vec<SchemataNamespace> {
-        // Group schemas and enums by namespace
-        vec![SchemataNamespace {
-            name: xsd_schema.target_namespace.clone().unwrap_or_else(|| "default".to_string()),
-            schemas: self.get_schemas(&xsd_schema.complex_types),
-            enums: self.get_enums(&xsd_schema.simple_types),
-        }]
+    pub(crate) fn get_namespaces(&self, xsd_schemas: &[XsdSchema]) -> Vec<SchemataNamespace> {
+        // Prefixes are declared per document, so type references are expanded before documents are mixed
+        let mut xsd_schemas: Vec<XsdSchema> = xsd_schemas.iter().cloned().map(|mut xsd_schema| {
+            xsd_schema.expand_type_names();
+            xsd_schema
+        }).collect();
+
+        // Element references may point to a global element of any of the documents
+        Self::inherit_head_types(&mut xsd_schemas);
+        let roots = Self::get_root_elements(&xsd_schemas);
+        xsd_schemas.iter_mut().for_each(|xsd_schema| Self::resolve_element_refs(xsd_schema, &roots));
+
+        // Group references may point to a group of any of the documents too
+        let mixins = match self.groups {
+            Groups::Expand => Vec::new(),
+            Groups::Mixins => Self::get_mixins(&xsd_schemas, &roots),
+        };
+        let definitions = GroupDefinitions::new(&xsd_schemas, &mixins);
+        xsd_schemas.iter_mut().for_each(|xsd_schema| definitions.resolve_group_refs(xsd_schema));
+
+        // Types may derive from or refer to types declared in any of the documents, and are told
+        // apart by `{namespace}name`, as references name them
+        let complex_types: Vec<XsdComplexType> = xsd_schemas.iter()
+            .flat_map(|s| s.complex_types.iter().map(|ct| XsdComplexType { name: Self::expanded_type_name(s, &ct.name), ..ct.clone() }))
+            .collect();
+        let simple_types: Vec<XsdSimpleType> = xsd_schemas.iter()
+            .flat_map(|s| s.simple_types.iter().map(|st| XsdSimpleType { name: Self::expanded_type_name(s, &st.name), ..st.clone() }))
+            .collect();
+        let (mut complex_offset, mut simple_offset) = (0, 0);
+
+        // Group schemas and enums by namespace, keeping the order in which namespaces first appear
+        let mut namespaces: Vec<SchemataNamespace> = Vec::new();
+        for (document, xsd_schema) in xsd_schemas.iter().enumerate() {
+            let target_namespace = xsd_schema.target_namespace.as_deref();
+            let name = Self::namespace_name(target_namespace);
+            let declared_complex_types = &complex_types[complex_offset..complex_offset + xsd_schema.complex_types.len()];
+            let declared_simple_types = &simple_types[simple_offset..simple_offset + xsd_schema.simple_types.len()];
+            complex_offset += declared_complex_types.len();
+            simple_offset += declared_simple_types.len();
+
+            let mut schemas = self.get_schemas(declared_complex_types, &complex_types, &simple_types, target_namespace);
+            for (schema, complex_type) in schemas.iter_mut().zip(declared_complex_types) {
+                Self::append_annotation(&mut schema.annotations, &self.get_includes(complex_type, &complex_types, &mixins));
+            }
+            let mut aliases = self.get_aliases(declared_simple_types, &simple_types, target_namespace);
+            for root in roots.iter().filter(|root| root.document == document) {
+                self.add_root_schema(root, xsd_schema, &mut schemas, &mut aliases, &complex_types, &simple_types);
+            }
+            schemas.extend(self.get_mixin_schemas(document, xsd_schema, &complex_types, &simple_types, &mixins));
+            let enums = self.get_enums(&xsd_schema.simple_types);
+            let comment = Self::get_comment(xsd_schema.comment.as_deref());
+
+            match namespaces.iter_mut().find(|ns| ns.name == name) {
+                Some(namespace) => {
+                    namespace.comment = match (namespace.comment.take(), comment) {
+                        (Some(first), Some(comment)) => Some(format!("{}\n\n{}", first, comment)),
+                        (first, comment) => first.or(comment),
+                    };
+                    namespace.schemas.extend(schemas);
+                    namespace.enums.extend(enums);
+                    namespace.aliases.extend(aliases);
+                },
+                None => namespaces.push(SchemataNamespace { name, comment, schemas, enums, aliases }),
+            }
+        }
+        namespaces.iter_mut().for_each(Self::name_inline_schemas);
+
+        // Constraints find the fields they apply to through inline schemas, so before those are hoisted
+        let constraints = self.get_identity_constraints(&xsd_schemas, &roots, &mixins);
+        Self::apply_identity_constraints(&mut namespaces, &constraints);
+        if self.inline_schemas == InlineSchemas::Hoist {
+            for namespace in &mut namespaces {
+                namespace.schemas = std::mem::take(&mut namespace.schemas).into_iter().flat_map(Self::hoist).collect();
+            }
+        } else {
+            namespaces.iter().flat_map(|namespace| &namespace.schemas).for_each(|schema| Self::warn_inline_annotations(&schema.fields));
+        }
+        namespaces
+    }
+
+    /// Inline schemas are written without annotations, which only hoisted schemas keep.
+    fn warn_inline_annotations(fields: &[SchemataField]) {
+        for inline_schema in fields.iter().filter_map(|field| field.inline_schema.as_ref()) {
+            if !inline_schema.annotations.is_empty() {
+                log::warn!("Inline schema `{}` is written without `{}`; hoist inline schemas to keep it", inline_schema.name, inline_schema.annotations);
+            }
+            Self::warn_inline_annotations(&inline_schema.fields);
+        }
+    }
+
+    /// Prefixes the name of every inline schema and enum with the name of the schema that holds
+    /// it, which [`Self::get_fields`] leaves out, and numbers names already taken in the
+    /// namespace. Schemata has no inline enums, so those move to the enums of the namespace.
+    pub(crate) fn name_inline_schemas(namespace: &mut SchemataNamespace) {
+        let mut taken: HashSet<String> = namespace.schemas.iter().map(|schema| schema.name.clone())
+            .chain(namespace.enums.iter().map(|e| e.name.clone()))
+            .chain(namespace.aliases.iter().map(|alias| alias.name.clone()))
+            .collect();
+        let mut inline_enums = Vec::new();
+        for schema in &mut namespace.schemas {
+            Self::qualify_inline_schemas(&schema.name, &mut schema.fields, &mut taken, &mut inline_enums);
+        }
+        namespace.enums.extend(inline_enums);
+    }
+
+    fn qualify_inline_schemas(parent: &str, fields: &mut [SchemataField], taken: &mut HashSet<String>, inline_enums: &mut Vec<SchemataEnum>) {
+        for field in fields {
+            if let Some(inline_schema) = &mut field.inline_schema {
+                let name = Self::unique_name(format!("{}{}", parent, inline_schema.name), taken);
+                Self::qualify_inline_schemas(&name, &mut inline_schema.fields, taken, inline_enums);
+                field.type_name = if field.type_name.ends_with("[]") { format!("{}[]", name) } else { name.clone() };
+                inline_schema.name = name;
+            }
+            if let Some(mut inline_enum) = field.inline_enum.take() {
+                let name = Self::unique_name(format!("{}{}", parent, inline_enum.name), taken);
+                field.type_name = if field.type_name.ends_with("[]") { format!("{}[]", name) } else { name.clone() };
+                inline_enum.name = name;
+                inline_enums.push(inline_enum);
+            }
+        }
+    }
+
+    /// `name`, or `name` numbered from 2 if it is already taken.
+    fn unique_name(name: String, taken: &mut HashSet<String>) -> String {
+        let mut unique = name.clone();
+        let mut suffix = 2;
+        while !taken.insert(unique.clone()) {
+            unique = format!("{}{}", name, suffix);
+            suffix += 1;
+        }
+        unique
+    }
+
+    /// The schema followed by the schemas inlined in it, depth first, with fields referring to
+    /// them by name.
+    fn hoist(mut schema: SchemataSchema) -> Vec<SchemataSchema> {
+        let inline_schemas: Vec<SchemataSchema> = schema.fields.iter_mut()
+            .filter_map(|field| field.inline_schema.take())
+            .collect();
+        let mut schemas = vec![schema];
+        schemas.extend(inline_schemas.into_iter().flat_map(Self::hoist));
+        schemas
+    }
+
+    /// The PascalCase form of an element name, used to name its anonymous type.
+    fn pascal_case(name: &str) -> String {
+        name.split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+            })
+            .collect()
     }
 
-    pub(crate) fn get_schemas(&self, complex_types: &[XsdComplexType]) -> Vec<SchemataSchema> {
-        complex_types.iter().map(|ct| {
+    /// Schemas for the complex types `declared` in one document, with base and simple types
+    /// looked up among all of `complex_types` and `simple_types`.
+    pub(crate) fn get_schemas(&self, declared: &[XsdComplexType], complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Vec<SchemataSchema> {
+        declared.iter().map(|ct| {
+            let extends = ct.derivation.as_ref()
+                .filter(|derivation| self.inheritance == Inheritance::Extends && Self::extends_schema(derivation, complex_types));
+            let fields = match extends {
+                Some(_) => self.get_own_fields(ct, complex_types, simple_types, namespace),
+                None => self.get_type_fields(ct, complex_types, simple_types, namespace),
+            };
+
+            let mut annotations = extends.map(|derivation| format!("@extends({})", Self::schemata_type(&derivation.base, namespace))).unwrap_or_default();
+            Self::append_annotation(&mut annotations, &self.get_extensible(ct, complex_types));
+            Self::append_annotation(&mut annotations, &Self::get_appinfo_annotations(&ct.appinfo));
+
             SchemataSchema {
-                name: ct.name.clone().unwrap_or_else(|| "UnnamedSchema".to_string()),
-                comment: None,
-                fields: self.get_fields(&ct.sequence),
+                name: ct.name.as_deref().map_or_else(|| "UnnamedSchema".to_string(), |name| Self::local_name(name).to_string()),
+                annotations,
+                comment: Self::get_comment(ct.comment.as_deref()),
+                fields,
             }
         }).collect()
     }
 
-    pub(crate) fn get_fields(&self, elements: &[XsdElement]) -> Vec<SchemataField> {
-        elements.iter().map(|e| {
-            SchemataField {
-                name: e.name.clone(),
-                type_name: e.type_name.clone().unwrap_or_else(|| "string".to_string()),
-                nullable: e.min_occurs.as_ref().map_or(false, |m| m.to_string() == "0"),
-                annotations: self.get_annotations(e),
-                comment: e.comment.clone(),
-                inline_schema: e.complex_type.as_ref().map(|ct| SchemataSchema {
-                    name: "InlineSchema".to_string(),
-                    comment: None,
-                    fields: self.get_fields(&ct.sequence),
-                }),
+    /// Global elements of every document, named after the element in PascalCase. A name taken by
+    /// a type of the namespace gets an `Element` suffix, numbered if that is taken too.
+    fn get_root_elements(xsd_schemas: &[XsdSchema]) -> Vec<RootElement> {
+        let mut roots: Vec<RootElement> = Vec::new();
+        for (document, xsd_schema) in xsd_schemas.iter().enumerate() {
+            let same_namespace = || xsd_schemas.iter().filter(|other| other.target_namespace == xsd_schema.target_namespace);
+            let declares_complex_type = |name: &str| same_namespace()
+                .any(|other| other.complex_types.iter().any(|ct| ct.name.as_deref() == Some(name)));
+            let declares_type = |name: &str| declares_complex_type(name) || same_namespace()
+                .any(|other| other.simple_types.iter().any(|st| st.name.as_deref() == Some(name)));
+
+            for element in &xsd_schema.elements {
+                let base = Self::pascal_case(&element.name);
+                let annotates_type = element.type_name.as_deref()
+                    .is_some_and(|type_name| split_expanded_name(type_name) == (xsd_schema.target_namespace.as_deref(), base.as_str()))
+                    && declares_complex_type(&base);
+
+                let mut schema_name = base.clone();
+                if !annotates_type {
+                    let namespace_roots = || roots.iter().filter(|root| xsd_schemas[root.document].target_namespace == xsd_schema.target_namespace);
+                    let taken = |name: &str| declares_type(name) || namespace_roots().any(|root| root.schema_name == name);
+                    let mut suffix = 1;
+                    while taken(&schema_name) {
+                        schema_name = match suffix {
+                            1 => format!("{}Element", base),
+                            _ => format!("{}Element{}", base, suffix),
+                        };
+                        suffix += 1;
+                    }
+                }
+
+                roots.push(RootElement {
+                    document,
+                    name: Self::expanded_name(xsd_schema.target_namespace.as_deref(), &element.name),
+                    element: element.clone(),
+                    schema_name,
+                    annotates_type,
+                });
+            }
+        }
+        roots
+    }
+
+    /// Gives the global elements declared without a type the type of the head of their
+    /// substitution group, as XSD does, following heads that have no type either.
+    fn inherit_head_types(xsd_schemas: &mut [XsdSchema]) {
+        let globals: Vec<(String, XsdElement)> = xsd_schemas.iter()
+            .flat_map(|xsd_schema| xsd_schema.elements.iter()
+                .map(|element| (Self::expanded_name(xsd_schema.target_namespace.as_deref(), &element.name), element.clone())))
+            .collect();
+        let untyped = |element: &XsdElement| element.type_name.is_none() && element.complex_type.is_none() && element.simple_type.is_none();
+
+        for element in xsd_schemas.iter_mut().flat_map(|xsd_schema| xsd_schema.elements.iter_mut()).filter(|element| untyped(element)) {
+            let mut visited: Vec<&str> = Vec::new();
+            let mut head_name = element.substitution_group.as_deref();
+            while let Some(name) = head_name.filter(|name| !visited.contains(name)) {
+                visited.push(name);
+                let Some((_, head)) = globals.iter().find(|(global, _)| global == name) else {
+                    break;
+                };
+                if !untyped(head) {
+                    element.type_name.clone_from(&head.type_name);
+                    element.complex_type.clone_from(&head.complex_type);
+                    element.simple_type.clone_from(&head.simple_type);
+                    break;
+                }
+                head_name = head.substitution_group.as_deref();
+            }
+        }
+    }
+
+    /// The Schemata namespace for a target namespace. URIs and URNs are not identifiers, so
+    /// `http://example.com/orders` is written as `com.example.orders` and `urn:example:orders` as
+    /// `example.orders`; parts that are not identifiers are patched with underscores.
+    pub(crate) fn namespace_name(target_namespace: Option<&str>) -> String {
+        let Some(target_namespace) = target_namespace else {
+            return "default".to_string();
+        };
+        let parts: Vec<&str> = match target_namespace.split_once("://") {
+            Some((_, rest)) => {
+                let rest = rest.split(['?', '#']).next().unwrap_or_default();
+                let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+                let host = authority.rsplit('@').next().unwrap_or_default().split(':').next().unwrap_or_default();
+                let host = host.strip_prefix("www.").unwrap_or(host);
+                host.rsplit('.').chain(path.split('/')).collect()
+            },
+            None => match target_namespace.get(..4) {
+                Some(scheme) if scheme.eq_ignore_ascii_case("urn:") => target_namespace[4..].split(':').collect(),
+                _ => target_namespace.split(['.', '/', ':']).collect(),
+            },
+        };
+        let identifiers: Vec<String> = parts.iter()
+            .flat_map(|part| part.split('.'))
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let identifier: String = part.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+                if identifier.starts_with(|c: char| c.is_ascii_digit()) { format!("_{}", identifier) } else { identifier }
+            })
+            .collect();
+        if identifiers.is_empty() { "default".to_string() } else { identifiers.join(".") }
+    }
+
+    /// The `{namespace}name` of a component declared for a target namespace, as references name it.
+    fn expanded_name(target_namespace: Option<&str>, name: &str) -> String {
+        match target_namespace {
+            Some(namespace) => format!("{{{}}}{}", namespace, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// The `{namespace}name` of a type a document declares; anonymous types have no name.
+    fn expanded_type_name(xsd_schema: &XsdSchema, name: &Option<String>) -> Option<String> {
+        name.as_deref().map(|name| Self::expanded_name(xsd_schema.target_namespace.as_deref(), name))
+    }
+
+    /// Model groups and attribute groups of every document, named after the group in
+    /// PascalCase. A name taken by a type, a root element or another group of the namespace gets
+    /// a `Group` or `Attributes` suffix, numbered if that is taken too.
+    fn get_mixins(xsd_schemas: &[XsdSchema], roots: &[RootElement]) -> Vec<Mixin> {
+        let mut mixins: Vec<Mixin> = Vec::new();
+        for (document, xsd_schema) in xsd_schemas.iter().enumerate() {
+            let same_namespace = |other: usize| xsd_schemas[other].target_namespace == xsd_schema.target_namespace;
+            let declared = xsd_schema.groups.iter().map(|group| (&group.name, false))
+                .chain(xsd_schema.attribute_groups.iter().map(|group| (&group.name, true)));
+
+            for (name, attributes) in declared {
+                let base = Self::pascal_case(name);
+                let suffix = if attributes { "Attributes" } else { "Group" };
+                let taken = |candidate: &str| {
+                    xsd_schemas.iter().enumerate().filter(|(other, _)| same_namespace(*other)).any(|(_, other)| {
+                        other.complex_types.iter().any(|ct| ct.name.as_deref() == Some(candidate))
+                            || other.simple_types.iter().any(|st| st.name.as_deref() == Some(candidate))
+                    })
+                        || roots.iter().any(|root| same_namespace(root.document) && root.schema_name == candidate)
+                        || mixins.iter().any(|mixin| same_namespace(mixin.document) && mixin.schema_name == candidate)
+                };
+                let mut schema_name = base.clone();
+                let mut number = 1;
+                while taken(&schema_name) {
+                    schema_name = match number {
+                        1 => format!("{}{}", base, suffix),
+                        _ => format!("{}{}{}", base, suffix, number),
+                    };
+                    number += 1;
+                }
+
+                mixins.push(Mixin { document, name: Self::expanded_name(xsd_schema.target_namespace.as_deref(), name), attributes, schema_name });
             }
+        }
+        mixins
+    }
+
+    /// `@include`s of the mixins for the group references [`GroupDefinitions::resolve_group_refs`]
+    /// kept in a complex type. With [`Inheritance::Flatten`], those of the base types whose
+    /// fields are copied come first, and a restriction includes the attribute groups of its base.
+    fn get_includes(&self, complex_type: &XsdComplexType, complex_types: &[XsdComplexType], mixins: &[Mixin]) -> String {
+        let mut types = vec![complex_type];
+        let mut restricted = None;
+        while let Some(derivation) = types[types.len() - 1].derivation.as_ref().filter(|_| self.inheritance == Inheritance::Flatten) {
+            let Some(base) = Self::base_type(derivation, complex_types).filter(|base| !types.iter().any(|t| std::ptr::eq(*t, *base))) else {
+                break;
+            };
+            if derivation.method == XsdDerivationMethod::Restriction && !derivation.simple_content {
+                restricted = Some(base);
+                break;
+            }
+            types.push(base);
+        }
+
+        // Base types first, as their fields come first
+        let mut references: Vec<(&str, bool)> = restricted.iter()
+            .flat_map(|base| base.attribute_groups.iter().map(|reference| (reference.as_str(), true)))
+            .collect();
+        for complex_type in types.iter().rev() {
+            let group_refs = complex_type.content.iter().flat_map(|content| &content.particles).filter_map(|particle| match particle {
+                XsdParticle::Group(reference) => Some((reference.reference.as_str(), false)),
+                _ => None,
+            });
+            references.extend(group_refs);
+            references.extend(complex_type.attribute_groups.iter().map(|reference| (reference.as_str(), true)));
+        }
+
+        let mut includes: Vec<String> = Vec::new();
+        for (reference, attributes) in references {
+            let Some(mixin) = mixins.iter().find(|mixin| mixin.name == reference && mixin.attributes == attributes) else {
+                continue;
+            };
+            let include = format!("@include({})", mixin.schema_name);
+            if !includes.contains(&include) {
+                includes.push(include);
+            }
+        }
+        includes.join(" ")
+    }
+
+    /// Schemas marked `@mixin` for the groups a document declares, with the fields of a model
+    /// group's content or of an attribute group's attributes.
+    fn get_mixin_schemas(&self, document: usize, xsd_schema: &XsdSchema, complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], mixins: &[Mixin]) -> Vec<SchemataSchema> {
+        mixins.iter().filter(|mixin| mixin.document == document).filter_map(|mixin| {
+            let name = Self::local_name(&mixin.name);
+            let (group, comment, appinfo) = match mixin.attributes {
+                false => {
+                    let group = xsd_schema.groups.iter().find(|group| group.name == name)?;
+                    (XsdComplexType { content: group.content.clone(), ..Default::default() }, &group.comment, &group.appinfo)
+                },
+                true => {
+                    let group = xsd_schema.attribute_groups.iter().find(|group| group.name == name)?;
+                    let complex_type = XsdComplexType {
+                        attributes: group.attributes.clone(),
+                        attribute_groups: group.attribute_groups.clone(),
+                        ..Default::default()
+                    };
+                    (complex_type, &group.comment, &group.appinfo)
+                },
+            };
+
+            let mut annotations = "@mixin".to_string();
+            Self::append_annotation(&mut annotations, &self.get_includes(&group, complex_types, mixins));
+            Self::append_annotation(&mut annotations, &self.get_extensible(&group, complex_types));
+            Self::append_annotation(&mut annotations, &Self::get_appinfo_annotations(appinfo));
+            Some(SchemataSchema {
+                name: mixin.schema_name.clone(),
+                annotations,
+                comment: Self::get_comment(comment.as_deref()),
+                fields: self.get_own_fields(&group, complex_types, simple_types, xsd_schema.target_namespace.as_deref()),
+            })
         }).collect()
     }
 
-    pub(crate) fn get_enums(&self, simple_types: &[XsdSimpleType]) -> Vec<SchemataEnum> {
+    /// Replaces every element declared with `ref` in a document by the global element it
+    /// refers to, keeping the occurrence bounds of the reference. Elements of an anonymous type
+    /// are typed by their root schema. A reference to the head of a substitution group is typed
+    /// by the union of the types of the elements that may appear in its place: the head unless
+    /// it is abstract, and the members of the group, directly or through other members.
+    fn resolve_element_refs(xsd_schema: &mut XsdSchema, roots: &[RootElement]) {
+        fn resolve_compositor(compositor: &mut XsdCompositor, roots: &[RootElement], namespace: Option<&str>) {
+            for particle in &mut compositor.particles {
+                match particle {
+                    XsdParticle::Element(element) => resolve_element(element, roots, namespace),
+                    XsdParticle::Compositor(nested) => resolve_compositor(nested, roots, namespace),
+                    XsdParticle::Group(_) | XsdParticle::Any(_) => {},
+                }
+            }
+        }
+        fn resolve_complex_type(complex_type: &mut XsdComplexType, roots: &[RootElement], namespace: Option<&str>) {
+            if let Some(content) = &mut complex_type.content {
+                resolve_compositor(content, roots, namespace);
+            }
+        }
+        fn root_type(root: &RootElement) -> Option<String> {
+            match &root.element.complex_type {
+                Some(_) => Some(SchemataGenerator::expanded_name(split_expanded_name(&root.name).0, &root.schema_name)),
+                None => root.element.type_name.clone(),
+            }
+        }
+        // The elements that may appear for `head`, itself first, when it heads a substitution group
+        fn substitutes<'a>(head: &'a RootElement, roots: &'a [RootElement]) -> Vec<&'a RootElement> {
+            let mut members = vec![head];
+            let mut index = 0;
+            while index < members.len() {
+                let name = &members[index].name;
+                let direct = roots.iter()
+                    .filter(|root| root.element.substitution_group.as_ref() == Some(name))
+                    .filter(|root| !members.iter().any(|member| std::ptr::eq(*member, *root)));
+                members.extend(direct.collect::<Vec<_>>());
+                index += 1;
+            }
+            if members.len() == 1 {
+                return Vec::new();
+            }
+            members.retain(|member| !member.element.is_abstract);
+            members
+        }
+        fn resolve_element(element: &mut XsdElement, roots: &[RootElement], namespace: Option<&str>) {
+            if let Some(complex_type) = &mut element.complex_type {
+                resolve_complex_type(complex_type, roots, namespace);
+            }
+            let Some(reference) = element.reference.take() else {
+                return;
+            };
+            let Some(root) = roots.iter().find(|root| root.name == reference) else {
+                log::warn!("Element `{}` is not declared in any loaded schema and is written as string", reference);
+                element.name = SchemataGenerator::local_name(&reference).to_string();
+                return;
+            };
+            element.name = root.element.name.clone();
+            element.type_name = root_type(root);
+            element.simple_type = root.element.simple_type.clone();
+            element.nillable = root.element.nillable;
+
+            let mut types: Vec<String> = Vec::new();
+            for substitute in substitutes(root, roots) {
+                let type_name = root_type(substitute).unwrap_or_else(|| "string".to_string());
+                if !types.contains(&type_name) {
+                    types.push(type_name);
+                }
+            }
+            match types.as_slice() {
+                [] if root.element.is_abstract => {
+                    log::warn!("Abstract element `{}` has no substitutes and is written with its own type", root.element.name);
+                },
+                [] => {},
+                [type_name] => {
+                    element.type_name = Some(type_name.clone());
+                    element.simple_type = None;
+                },
+                _ => {
+                    let members: Vec<String> = types.iter().map(|type_name| SchemataGenerator::schemata_type(type_name, namespace)).collect();
+                    element.type_name = Some(format!("union<{}>", members.join(", ")));
+                    element.simple_type = None;
+                },
+            }
+            if element.comment.is_none() {
+                element.comment = root.element.comment.clone();
+            }
+            if element.appinfo.is_empty() {
+                element.appinfo = root.element.appinfo.clone();
+            }
+        }
+
+        let namespace = xsd_schema.target_namespace.clone();
+        let namespace = namespace.as_deref();
+        xsd_schema.elements.iter_mut().for_each(|element| resolve_element(element, roots, namespace));
+        xsd_schema.complex_types.iter_mut().for_each(|complex_type| resolve_complex_type(complex_type, roots, namespace));
+        for content in xsd_schema.groups.iter_mut().filter_map(|group| group.content.as_mut()) {
+            resolve_compositor(content, roots, namespace);
+        }
+    }
+
+    /// Writes a global element marked `@root`:
+    /// - an element of an anonymous complex type as a schema of its fields,
+    /// - an element of a named complex type as an alias of the type, `type Root = Type`, so the
+    ///   type's schema is written once; if the type has the element's name, its own schema is
+    ///   marked instead,
+    /// - an element of a simple type as a schema with a `value` field of that type.
+    ///
+    /// Abstract elements cannot be roots. Only the schema of an anonymous type is written for
+    /// them, unmarked, as substitutes may have it.
+    ///
+    /// `xsd_schema` is the root's document, with references resolved.
+    fn add_root_schema(&self, root: &RootElement, xsd_schema: &XsdSchema, schemas: &mut Vec<SchemataSchema>, aliases: &mut Vec<SchemataAlias>, complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType]) {
+        let Some(element) = xsd_schema.elements.iter().find(|element| element.name == root.element.name) else {
+            return;
+        };
+        let namespace = xsd_schema.target_namespace.as_deref();
+        if element.is_abstract && element.complex_type.is_none() && !root.annotates_type {
+            return;
+        }
+        let mut annotations = match (element.is_abstract, root.schema_name == element.name) {
+            (true, _) => String::new(),
+            (false, true) => "@root".to_string(),
+            (false, false) => format!("@root(name = \"{}\")", element.name),
+        };
+
+        if root.annotates_type {
+            if let Some(schema) = schemas.iter_mut().find(|schema| schema.name == root.schema_name) {
+                Self::append_annotation(&mut annotations, &schema.annotations);
+                schema.annotations = annotations;
+            }
+            return;
+        }
+
+        let named_complex_type = element.type_name.as_deref()
+            .filter(|type_name| split_expanded_name(type_name).0 != Some(XSD_NAMESPACE))
+            .filter(|type_name| !simple_types.iter().any(|st| st.name.as_deref() == Some(type_name)));
+        let (comment, fields) = match (&element.complex_type, named_complex_type) {
+            (Some(complex_type), _) => {
+                Self::append_annotation(&mut annotations, &self.get_extensible(complex_type, complex_types));
+                (element.comment.as_deref().or(complex_type.comment.as_deref()), self.get_type_fields(complex_type, complex_types, simple_types, namespace))
+            },
+            (None, Some(type_name)) if complex_types.iter().any(|ct| ct.name.as_deref() == Some(type_name)) => {
+                Self::append_annotation(&mut annotations, &Self::get_appinfo_annotations(&element.appinfo));
+                aliases.push(SchemataAlias {
+                    name: root.schema_name.clone(),
+                    type_name: Self::schemata_type(type_name, namespace),
+                    annotations,
+                    comment: Self::get_comment(element.comment.as_deref()),
+                });
+                return;
+            },
+            (None, Some(type_name)) => {
+                log::warn!("Type `{}` is not declared in any loaded schema; the fields of root element `{}` are left out", type_name, element.name);
+                (element.comment.as_deref(), Vec::new())
+            },
+            (None, None) => {
+                let value = XsdElement {
+                    name: "value".to_string(),
+                    type_name: element.type_name.clone(),
+                    simple_type: element.simple_type.clone(),
+                    ..Default::default()
+                };
+                (element.comment.as_deref(), self.get_fields(&[value], complex_types, simple_types, namespace))
+            },
+        };
+        Self::append_annotation(&mut annotations, &Self::get_appinfo_annotations(&element.appinfo));
+
+        schemas.push(SchemataSchema {
+            name: root.schema_name.clone(),
+            annotations,
+            comment: Self::get_comment(comment),
+            fields,
+        });
+    }
+
+    /// The identity constraints of every element, with the schema written for the element's
+    /// type: the root schema or the inline schema of an anonymous type, or the schema of a named
+    /// type. A root element of a named type is an alias of the type, so its constraints start
+    /// from the type's schema.
+    fn get_identity_constraints(&self, xsd_schemas: &[XsdSchema], roots: &[RootElement], mixins: &[Mixin]) -> Vec<IdentityConstraint> {
+        fn collect(element: &XsdElement, location: SchemaLocation, target_namespace: Option<&str>, constraints: &mut Vec<IdentityConstraint>) {
+            for constraint in &element.identity_constraints {
+                constraints.push(IdentityConstraint {
+                    name: SchemataGenerator::expanded_name(target_namespace, &constraint.name),
+                    location: location.clone(),
+                    constraint: constraint.clone(),
+                });
+            }
+            if let Some(complex_type) = &element.complex_type {
+                collect_type(complex_type, &location, target_namespace, constraints);
+            }
+        }
+        fn collect_type(complex_type: &XsdComplexType, location: &SchemaLocation, target_namespace: Option<&str>, constraints: &mut Vec<IdentityConstraint>) {
+            for element in complex_type.elements() {
+                let location = match (&element.complex_type, element.type_name.as_deref()) {
+                    (None, Some(type_name)) => type_location(type_name, &location.0),
+                    _ => {
+                        let mut location = location.clone();
+                        location.1.push(element.name.clone());
+                        location
+                    },
+                };
+                collect(element, location, target_namespace, constraints);
+            }
+        }
+        fn type_location(type_name: &str, namespace: &str) -> SchemaLocation {
+            let type_namespace = split_expanded_name(type_name).0;
+            let namespace = match type_namespace {
+                Some(target_namespace) => SchemataGenerator::namespace_name(Some(target_namespace)),
+                None => namespace.to_string(),
+            };
+            (namespace, vec![SchemataGenerator::schemata_type(type_name, type_namespace)])
+        }
+
+        let mut constraints = Vec::new();
+        for (document, xsd_schema) in xsd_schemas.iter().enumerate() {
+            let target_namespace = xsd_schema.target_namespace.as_deref();
+            let namespace = Self::namespace_name(target_namespace);
+            for root in roots.iter().filter(|root| root.document == document) {
+                let Some(element) = xsd_schema.elements.iter().find(|element| element.name == root.element.name) else {
+                    continue;
+                };
+                let location = match (&element.complex_type, element.type_name.as_deref()) {
+                    (None, Some(type_name)) => type_location(type_name, &namespace),
+                    _ => (namespace.clone(), vec![root.schema_name.clone()]),
+                };
+                collect(element, location, target_namespace, &mut constraints);
+            }
+            for complex_type in &xsd_schema.complex_types {
+                let location = (namespace.clone(), vec![complex_type.name.clone().unwrap_or_default()]);
+                collect_type(complex_type, &location, target_namespace, &mut constraints);
+            }
+            for mixin in mixins.iter().filter(|mixin| mixin.document == document && !mixin.attributes) {
+                let Some(group) = xsd_schema.groups.iter().find(|group| group.name == Self::local_name(&mixin.name)) else {
+                    continue;
+                };
+                let complex_type = XsdComplexType { content: group.content.clone(), ..Default::default() };
+                collect_type(&complex_type, &(namespace.clone(), vec![mixin.schema_name.clone()]), target_namespace, &mut constraints);
+            }
+        }
+        constraints
+    }
+
+    /// Writes keys as `@id`, unique constraints as `@unique` and keyrefs as
+    /// `@relation(schema = Target, field = "key")` on the field their single `field` XPath
+    /// selects, in the schema their selector leads to. Constraints with XPaths that do not
+    /// follow fields step by step, or with several fields, are left out with a warning.
+    fn apply_identity_constraints(namespaces: &mut [SchemataNamespace], constraints: &[IdentityConstraint]) {
+        // Keyrefs refer to the fields keys and unique constraints were written on
+        let mut keys: Vec<(&str, String, String)> = Vec::new();
+        let (keyrefs, others): (Vec<&IdentityConstraint>, Vec<&IdentityConstraint>) = constraints.iter()
+            .partition(|constraint| constraint.constraint.kind == XsdIdentityConstraintKind::KeyRef);
+
+        for constraint in others.into_iter().chain(keyrefs) {
+            let Some((location, field_name)) = Self::constraint_field(namespaces, constraint) else {
+                continue;
+            };
+            let annotation = match constraint.constraint.kind {
+                XsdIdentityConstraintKind::Key => "@id".to_string(),
+                XsdIdentityConstraintKind::Unique => "@unique".to_string(),
+                XsdIdentityConstraintKind::KeyRef => {
+                    let refer = constraint.constraint.refer.as_deref().unwrap_or_default();
+                    let Some((_, schema, field)) = keys.iter().find(|(name, _, _)| *name == refer) else {
+                        log::warn!("Skipping keyref `{}`: the key `{}` it refers to was not written", constraint.constraint.name, refer);
+                        continue;
+                    };
+                    format!("@relation(schema = {}, field = \"{}\")", schema, field)
+                },
+            };
+
+            let Some(schema) = Self::schema_at_mut(namespaces, &location) else {
+                continue;
+            };
+            let schema_name = schema.name.clone();
+            let Some(field) = schema.fields.iter_mut().find(|field| field.name == field_name) else {
+                continue;
+            };
+            if field.type_name.ends_with("[]") {
+                log::warn!("Skipping {} `{}`: field `{}` of `{}` is an array", Self::constraint_kind(constraint), constraint.constraint.name, field_name, schema_name);
+                continue;
+            }
+            if constraint.constraint.kind == XsdIdentityConstraintKind::Key {
+                field.nullable = false;
+            }
+            if !field.annotations.split(' ').any(|existing| existing == annotation) {
+                Self::append_annotation(&mut field.annotations, &annotation);
+            }
+            if constraint.constraint.kind != XsdIdentityConstraintKind::KeyRef {
+                keys.push((&constraint.name, schema_name, field_name));
+            }
+        }
+    }
+
+    /// The schema a constraint's selector leads to and the name of the field its field XPath
+    /// selects there. Selectors are relative paths of child elements, like `item` or
+    /// `./items/item`; fields are a child element or an attribute, like `sku` or `@sku`.
+    fn constraint_field(namespaces: &[SchemataNamespace], constraint: &IdentityConstraint) -> Option<(SchemaLocation, String)> {
+        let xsd = &constraint.constraint;
+        let kind = Self::constraint_kind(constraint);
+        let [field_xpath] = xsd.fields.as_slice() else {
+            log::warn!("Skipping {} `{}`: constraints on several fields are not supported", kind, xsd.name);
+            return None;
+        };
+        let Some(steps) = Self::xpath_steps(&xsd.selector) else {
+            log::warn!("Skipping {} `{}`: selector `{}` cannot be mapped to fields", kind, xsd.name, xsd.selector);
+            return None;
+        };
+
+        let mut location = constraint.location.clone();
+        for step in steps {
+            let field = Self::schema_at(namespaces, &location)
+                .and_then(|schema| schema.fields.iter().find(|field| field.name == step && Self::xml_attribute(field).is_none()));
+            let Some(field) = field else {
+                log::warn!("Skipping {} `{}`: selector `{}` does not lead to a schema", kind, xsd.name, xsd.selector);
+                return None;
+            };
+            location = match &field.inline_schema {
+                Some(_) => {
+                    location.1.push(step.to_string());
+                    location
+                },
+                None => {
+                    let type_name = field.type_name.trim_end_matches("[]");
+                    let namespace = namespaces.iter()
+                        .filter(|namespace| namespace.schemas.iter().any(|schema| schema.name == type_name))
+                        .min_by_key(|namespace| namespace.name != location.0);
+                    let Some(namespace) = namespace else {
+                        log::warn!("Skipping {} `{}`: selector `{}` does not lead to a schema", kind, xsd.name, xsd.selector);
+                        return None;
+                    };
+                    (namespace.name.clone(), vec![type_name.to_string()])
+                },
+            };
+        }
+
+        let field_path = field_xpath.trim().trim_start_matches("./");
+        let (attribute, name) = match field_path.strip_prefix('@') {
+            Some(name) => (true, name),
+            None => (false, field_path),
+        };
+        let name = Self::local_name(name);
+        let field = Self::xpath_steps(field_path.trim_start_matches('@'))
+            .filter(|steps| steps.len() == 1)
+            .and_then(|_| Self::schema_at(namespaces, &location))
+            .and_then(|schema| schema.fields.iter().find(|field| match Self::xml_attribute(field) {
+                Some(xml_name) => attribute && xml_name == name,
+                None => !attribute && field.name == name,
+            }));
+        let Some(field) = field else {
+            log::warn!("Skipping {} `{}`: field `{}` cannot be mapped to a field", kind, xsd.name, field_xpath);
+            return None;
+        };
+        Some((location, field.name.clone()))
+    }
+
+    /// The local names of the child steps of a relative XPath, or `None` if it uses anything
+    /// else: descendants, wildcards, predicates, unions or axes.
+    fn xpath_steps(xpath: &str) -> Option<Vec<&str>> {
+        let xpath = xpath.trim().trim_start_matches("./");
+        if xpath == "." {
+            return Some(Vec::new());
+        }
+        xpath.split('/')
+            .filter(|step| *step != ".")
+            .map(|step| {
+                let name = step.split_once(':').map_or(step, |(_, local)| local);
+                let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+                valid.then_some(name)
+            })
+            .collect()
+    }
+
+    /// The XML name of a field written for an attribute, or `None` for an element.
+    fn xml_attribute(field: &SchemataField) -> Option<&str> {
+        if let Some(start) = field.annotations.find("@xmlAttribute(name = \"") {
+            let rest = &field.annotations[start + "@xmlAttribute(name = \"".len()..];
+            return rest.split('"').next();
+        }
+        field.annotations.split(' ').any(|annotation| annotation == "@xmlAttribute").then_some(field.name.as_str())
+    }
+
+    fn constraint_kind(constraint: &IdentityConstraint) -> &'static str {
+        match constraint.constraint.kind {
+            XsdIdentityConstraintKind::Key => "key",
+            XsdIdentityConstraintKind::KeyRef => "keyref",
+            XsdIdentityConstraintKind::Unique => "unique constraint",
+        }
+    }
+
+    fn schema_at<'a>(namespaces: &'a [SchemataNamespace], location: &SchemaLocation) -> Option<&'a SchemataSchema> {
+        let (top, path) = location.1.split_first()?;
+        let namespace = namespaces.iter().find(|namespace| namespace.name == location.0)?;
+        let mut schema = namespace.schemas.iter().find(|schema| &schema.name == top)?;
+        for step in path {
+            schema = schema.fields.iter().find(|field| &field.name == step)?.inline_schema.as_ref()?;
+        }
+        Some(schema)
+    }
+
+    fn schema_at_mut<'a>(namespaces: &'a mut [SchemataNamespace], location: &SchemaLocation) -> Option<&'a mut SchemataSchema> {
+        let (top, path) = location.1.split_first()?;
+        let namespace = namespaces.iter_mut().find(|namespace| namespace.name == location.0)?;
+        let mut schema = namespace.schemas.iter_mut().find(|schema| &schema.name == top)?;
+        for step in path {
+            schema = schema.fields.iter_mut().find(|field| &field.name == step)?.inline_schema.as_mut()?;
+        }
+        Some(schema)
+    }
+
+    /// `@extensible` for a complex type open to content it does not declare, through an `any`
+    /// element or an `anyAttribute`. With [`Inheritance::Flatten`], the wildcards of the base
+    /// types it extends count too, as their fields are copied.
+    fn get_extensible(&self, complex_type: &XsdComplexType, complex_types: &[XsdComplexType]) -> String {
+        fn has_any(compositor: &XsdCompositor) -> bool {
+            compositor.particles.iter().any(|particle| match particle {
+                XsdParticle::Any(_) => true,
+                XsdParticle::Compositor(nested) => has_any(nested),
+                XsdParticle::Element(_) | XsdParticle::Group(_) => false,
+            })
+        }
+
+        let mut types = vec![complex_type];
+        while let Some(derivation) = types[types.len() - 1].derivation.as_ref().filter(|_| self.inheritance == Inheritance::Flatten) {
+            let base = Self::base_type(derivation, complex_types)
+                .filter(|_| derivation.method == XsdDerivationMethod::Extension)
+                .filter(|base| !types.iter().any(|t| std::ptr::eq(*t, *base)));
+            let Some(base) = base else {
+                break;
+            };
+            types.push(base);
+        }
+        let extensible = types.iter().any(|t| t.any_attribute.is_some() || t.content.as_ref().is_some_and(has_any));
+        if extensible { "@extensible".to_string() } else { String::new() }
+    }
+
+    /// Whether a derivation is written as `@extends`: an extension of a complex type. Bases of
+    /// `complexContent` are complex types even if they are declared in another document.
+    fn extends_schema(derivation: &XsdDerivation, complex_types: &[XsdComplexType]) -> bool {
+        derivation.method == XsdDerivationMethod::Extension
+            && (!derivation.simple_content || Self::base_type(derivation, complex_types).is_some())
+    }
+
+    /// The complex type a derivation is based on, by its `{namespace}name`.
+    fn base_type<'a>(derivation: &XsdDerivation, complex_types: &'a [XsdComplexType]) -> Option<&'a XsdComplexType> {
+        complex_types.iter().find(|ct| ct.name.as_deref() == Some(derivation.base.as_str()))
+    }
+
+    /// The local part of a type reference, whether it is an expanded `{namespace}local` name or
+    /// a `prefix:local` QName.
+    fn local_name(name: &str) -> &str {
+        name.rsplit(['}', ':']).next().unwrap_or(name)
+    }
+
+    /// The Schemata type for an XSD type reference, as written in the namespace of the target
+    /// namespace `namespace`: the basic type a built-in type maps to, the local name of a type
+    /// declared in the same namespace, or `namespace.Name` for a type of another namespace.
+    pub(crate) fn schemata_type(type_name: &str, namespace: Option<&str>) -> String {
+        match split_expanded_name(type_name) {
+            (Some(XSD_NAMESPACE), local) => match Self::basic_type(local) {
+                Some(basic) => basic.to_string(),
+                None => local.to_string(),
+            },
+            (Some(type_namespace), local) if Self::namespace_name(Some(type_namespace)) != Self::namespace_name(namespace) => {
+                format!("{}.{}", Self::namespace_name(Some(type_namespace)), local)
+            },
+            _ => Self::local_name(type_name).to_string(),
+        }
+    }
+
+    /// The type of a restricted simple type; decimals with a `totalDigits` facet keep their
+    /// precision and scale.
+    fn restricted_type(restriction: &XsdRestriction, namespace: Option<&str>) -> String {
+        let type_name = Self::schemata_type(&restriction.base, namespace);
+        match restriction.total_digits {
+            Some(precision) if type_name == "decimal" => {
+                format!("decimal({}, {})", precision, restriction.fraction_digits.unwrap_or(0))
+            },
+            _ => type_name,
+        }
+    }
+
+    /// The basic type an XSD built-in type maps to, from the type mapping table.
+    fn basic_type(xsd_type: &str) -> Option<&'static str> {
+        if xsd_type == "anyType" {
+            log::warn!("`anyType` has no Schemata equivalent and is written as string");
+            return Some("string");
+        }
+        let basic = type_mapping::from_xsd(xsd_type).map(|basic| basic.name);
+        if basic.is_none() {
+            log::warn!("`{}` is not an XSD built-in type", xsd_type);
+        }
+        basic
+    }
+
+    /// Fields of a complex type with the ones it inherits from its base types first.
+    pub(crate) fn get_type_fields(&self, complex_type: &XsdComplexType, complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Vec<SchemataField> {
+        let mut chain = complex_type.name.iter().cloned().collect();
+        self.get_derived_fields(complex_type, complex_types, simple_types, namespace, &mut chain)
+    }
+
+    /// Fields a complex type declares itself: its content, then its attributes.
+    fn get_own_fields(&self, complex_type: &XsdComplexType, complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Vec<SchemataField> {
+        let mut fields = self.get_content_fields(complex_type, complex_types, simple_types, namespace);
+        fields.extend(self.get_attribute_fields(&complex_type.attributes, &fields, simple_types, namespace));
+        fields
+    }
+
+    /// Fields of a complex type together with the ones it takes from its base: those of a base
+    /// complex type, recursively, or a `value` field holding the text of simple content. A
+    /// `complexContent` restriction restates the content it keeps and only inherits the
+    /// attributes it does not declare again, after its own fields.
+    /// `chain` holds the names of the types already visited, to stop at circular derivations.
+    fn get_derived_fields(&self, complex_type: &XsdComplexType, complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], namespace: Option<&str>, chain: &mut Vec<String>) -> Vec<SchemataField> {
+        let Some(derivation) = &complex_type.derivation else {
+            return self.get_own_fields(complex_type, complex_types, simple_types, namespace);
+        };
+
+        let mut fields = match Self::base_type(derivation, complex_types) {
+            Some(base) if chain.iter().any(|name| base.name.as_ref() == Some(name)) => {
+                log::warn!("Type `{}` derives from itself; its inherited fields are left out", derivation.base);
+                Vec::new()
+            },
+            Some(base) if derivation.method == XsdDerivationMethod::Extension || derivation.simple_content => {
+                chain.push(base.name.clone().unwrap_or_default());
+                let fields = self.get_derived_fields(base, complex_types, simple_types, namespace, chain);
+                chain.pop();
+                fields
+            },
+            Some(base) => {
+                let mut fields = self.get_own_fields(complex_type, complex_types, simple_types, namespace);
+                let attributes: Vec<XsdAttribute> = base.attributes.iter()
+                    .filter(|attribute| !complex_type.attributes.iter().any(|own| own.name == attribute.name))
+                    .cloned()
+                    .collect();
+                fields.extend(self.get_attribute_fields(&attributes, &fields, simple_types, namespace));
+                return fields;
+            },
+            None if derivation.simple_content => {
+                let value = XsdElement {
+                    name: "value".to_string(),
+                    type_name: Some(derivation.base.clone()),
+                    ..Default::default()
+                };
+                self.get_fields(&[value], complex_types, simple_types, namespace)
+            },
+            None => {
+                log::warn!("Base type `{}` is not declared in any loaded schema; its fields are left out", derivation.base);
+                Vec::new()
+            },
+        };
+        fields.extend(self.get_own_fields(complex_type, complex_types, simple_types, namespace));
+        fields
+    }
+
+    /// Elements of a named simple type refer to its enum or alias. Elements of an anonymous
+    /// simple type take the type it stands for, with the facets of a restriction written as
+    /// annotations. Elements of an anonymous complex type or enumeration get an inline schema or
+    /// enum named after the element alone, see [`Self::name_inline_schemas`].
+    ///
+    /// Elements that may occur more than once become arrays, with bounds other than zero and
+    /// unbounded written as `@size(min = .., max = ..)`. An absent array is an empty one, so
+    /// arrays are not nullable.
+    pub(crate) fn get_fields(&self, elements: &[XsdElement], complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Vec<SchemataField> {
+        elements.iter()
+            .map(|e| self.get_field(e, Self::occurs(e.min_occurs.as_deref(), e.max_occurs.as_deref()), complex_types, simple_types, namespace))
+            .collect()
+    }
+
+    /// The field for an element that occurs `min` to `max` times, see [`Self::get_fields`].
+    fn get_field(&self, e: &XsdElement, (min, max): Occurs, complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], namespace: Option<&str>) -> SchemataField {
+        let simple_type = e.simple_type.as_ref().filter(|_| e.type_name.is_none());
+        let restriction = simple_type.and_then(Self::facet_restriction);
+        let repeated = max.is_none_or(|max| max > 1);
+        let mut annotations = if repeated { Self::get_size_annotation(min, max) } else { String::new() };
+        if let Some(restriction) = restriction {
+            Self::append_annotation(&mut annotations, &Self::get_facet_annotations(restriction));
+        }
+        Self::append_annotation(&mut annotations, &Self::get_appinfo_annotations(&e.appinfo));
+
+        let inline_enum = simple_type.and_then(|st| Self::get_enum(Self::pascal_case(&e.name), st));
+        let mut type_name = match (&e.type_name, simple_type.and_then(|st| Self::simple_type_name(st, simple_types, namespace)), &e.complex_type, &inline_enum) {
+            (Some(type_name), _, _, _) => Self::schemata_type(type_name, namespace),
+            (None, Some(type_name), _, _) => type_name,
+            (None, None, Some(_), _) => Self::pascal_case(&e.name),
+            (None, None, None, Some(inline_enum)) => inline_enum.name.clone(),
+            (None, None, None, None) => "string".to_string(),
+        };
+        if repeated {
+            if Self::is_list(&type_name, simple_types, namespace) {
+                log::warn!("Element `{}` repeats a list type; arrays of arrays are not supported, so its values form one array", e.name);
+            } else {
+                type_name.push_str("[]");
+            }
+        }
+
+        SchemataField {
+            name: e.name.clone(),
+            type_name,
+            nullable: !repeated && (min == 0 || e.nillable),
+            annotations,
+            comment: Self::get_comment(e.comment.as_deref()),
+            inline_schema: e.complex_type.as_ref().map(|ct| SchemataSchema {
+                name: Self::pascal_case(&e.name),
+                annotations: self.get_extensible(ct, complex_types),
+                comment: None,
+                fields: self.get_type_fields(ct, complex_types, simple_types, namespace),
+            }),
+            inline_enum,
+        }
+    }
+
+    /// Attributes become fields marked `@xmlAttribute`, nullable unless they are required.
+    /// `default` and `fixed` values become `@default` and `@const`. An attribute named like one of
+    /// the `taken` fields gets an `Attribute` suffix, with its XML name on the marker. Anonymous
+    /// enumerations become enums named like those of elements, see [`Self::get_fields`].
+    pub(crate) fn get_attribute_fields(&self, attributes: &[XsdAttribute], taken: &[SchemataField], simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Vec<SchemataField> {
+        let mut fields: Vec<SchemataField> = Vec::new();
+        for attribute in attributes {
+            if attribute.name.is_empty() {
+                log::warn!("Skipping an attribute without a name; attribute references are not supported");
+                continue;
+            }
+            if attribute.use_type == "prohibited" {
+                continue;
+            }
+
+            let type_name = Some(attribute.type_name.as_str()).filter(|t| !t.is_empty());
+            let simple_type = attribute.simple_type.as_ref().filter(|_| type_name.is_none());
+            let restriction = simple_type.and_then(Self::facet_restriction);
+            let clashes = taken.iter().chain(&fields).any(|field| field.name == attribute.name);
+            let mut annotations = if clashes {
+                format!("@xmlAttribute(name = \"{}\")", attribute.name)
+            } else {
+                "@xmlAttribute".to_string()
+            };
+            for (name, value) in [("default", &attribute.default), ("const", &attribute.fixed)] {
+                if let Some(value) = value {
+                    Self::append_annotation(&mut annotations, &format!("@{}(value = \"{}\")", name, value.replace('"', "'")));
+                }
+            }
+            if let Some(restriction) = restriction {
+                Self::append_annotation(&mut annotations, &Self::get_facet_annotations(restriction));
+            }
+            Self::append_annotation(&mut annotations, &Self::get_appinfo_annotations(&attribute.appinfo));
+
+            let name = if clashes { format!("{}Attribute", attribute.name) } else { attribute.name.clone() };
+            let inline_enum = simple_type.and_then(|st| Self::get_enum(Self::pascal_case(&name), st));
+            fields.push(SchemataField {
+                name,
+                type_name: match (type_name, simple_type.and_then(|st| Self::simple_type_name(st, simple_types, namespace)), &inline_enum) {
+                    (Some(type_name), _, _) => Self::schemata_type(type_name, namespace),
+                    (None, Some(type_name), _) => type_name,
+                    (None, None, Some(inline_enum)) => inline_enum.name.clone(),
+                    (None, None, None) => "string".to_string(),
+                },
+                nullable: attribute.use_type != "required",
+                annotations,
+                comment: Self::get_comment(attribute.comment.as_deref()),
+                inline_schema: None,
+                inline_enum,
+            });
+        }
+        fields
+    }
+
+    /// Fields for the content model of a complex type. Nested groups are flattened: the
+    /// occurrence bounds of a group apply to every element in it, and the alternatives of a
+    /// choice become nullable fields tagged with `@oneof(name = "choice")`.
+    pub(crate) fn get_content_fields(&self, complex_type: &XsdComplexType, complex_types: &[XsdComplexType], simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Vec<SchemataField> {
+        let Some(content) = &complex_type.content else {
+            return Vec::new();
+        };
+        let mut particles = Vec::new();
+        Self::flatten(content, (1, Some(1)), None, &mut 0, &mut particles);
+
+        particles.into_iter().map(|(element, occurs, oneof)| {
+            let mut field = self.get_field(element, occurs, complex_types, simple_types, namespace);
+            if let Some(oneof) = oneof {
+                Self::append_annotation(&mut field.annotations, &format!("@oneof(name = \"{}\")", oneof));
+            }
+            field
+        }).collect()
+    }
+
+    /// Collects the elements of `compositor` with their effective occurrence bounds and the
+    /// choice they are an alternative of. `oneof` is the choice enclosing the compositor.
+    fn flatten<'a>(compositor: &'a XsdCompositor, outer: Occurs, oneof: Option<String>, choices: &mut usize, out: &mut Vec<(&'a XsdElement, Occurs, Option<String>)>) {
+        let mut bounds = Self::multiply(outer, Self::occurs(compositor.min_occurs.as_deref(), compositor.max_occurs.as_deref()));
+        let mut oneof = oneof;
+        if oneof.is_some() && compositor.particles.len() > 1 {
+            log::warn!("A choice alternative with several particles cannot be tagged; its elements become nullable fields");
+            oneof = None;
+        }
+        if compositor.kind == XsdCompositorKind::Choice && compositor.particles.len() > 1 {
+            // Only one alternative is present, so none of them is required
+            bounds.0 = 0;
+            // Alternatives of a repeated choice can all be present, so they are not a union
+            oneof = (bounds.1 == Some(1)).then(|| {
+                *choices += 1;
+                if *choices == 1 { "choice".to_string() } else { format!("choice{}", choices) }
+            });
+        }
+
+        for particle in &compositor.particles {
+            match particle {
+                XsdParticle::Element(element) => {
+                    let own = Self::occurs(element.min_occurs.as_deref(), element.max_occurs.as_deref());
+                    out.push((element, Self::multiply(bounds, own), oneof.clone()));
+                },
+                XsdParticle::Compositor(nested) => Self::flatten(nested, bounds, oneof.clone(), choices, out),
+                // References left by [`Self::resolve_group_refs`] are written as `@include`s, and
+                // wildcards as `@extensible`
+                XsdParticle::Group(_) | XsdParticle::Any(_) => {},
+            }
+        }
+    }
+
+    fn occurs(min_occurs: Option<&str>, max_occurs: Option<&str>) -> Occurs {
+        let min = min_occurs.and_then(|min| min.parse().ok()).unwrap_or(1);
+        let max = match max_occurs {
+            Some("unbounded") => None,
+            Some(max) => Some(max.parse().unwrap_or(1)),
+            None => Some(1),
+        };
+        (min, max)
+    }
+
+    fn multiply(outer: Occurs, inner: Occurs) -> Occurs {
+        (outer.0 * inner.0, outer.1.zip(inner.1).map(|(outer, inner)| outer * inner))
+    }
+
+    /// The restriction of a simple type, unless it is an enumeration.
+    fn facet_restriction(simple_type: &XsdSimpleType) -> Option<&XsdRestriction> {
+        simple_type.restriction.as_ref().filter(|r| r.enumeration.is_empty() && !r.base.is_empty())
+    }
+
+    /// The Schemata type a simple type stands for: the base type of a restriction, an array of
+    /// the item type of a list, or a union of the member types of a union. Enumerations are
+    /// enums of their own, and types whose parts were declared inline have none.
+    fn simple_type_name(simple_type: &XsdSimpleType, simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Option<String> {
+        if let Some(restriction) = Self::facet_restriction(simple_type) {
+            return Some(Self::restricted_type(restriction, namespace));
+        }
+        if let Some(item_type) = simple_type.list.as_deref().filter(|item_type| !item_type.is_empty()) {
+            return Some(format!("{}[]", Self::schemata_type(item_type, namespace)));
+        }
+
+        let member_types = simple_type.union.as_ref().filter(|member_types| !member_types.is_empty())?;
+        let mut members = Vec::new();
+        Self::union_members(member_types, simple_types, namespace, &mut simple_type.name.iter().cloned().collect(), &mut members);
+        match members.as_slice() {
+            [member] => Some(member.clone()),
+            _ => Some(format!("union<{}>", members.join(", "))),
+        }
+    }
+
+    /// Collects the Schemata types of the members of a union, without repeats. Unions cannot be
+    /// nested, so members that are unions themselves contribute their own members. `visited`
+    /// holds the unions already expanded, to stop at circular definitions.
+    fn union_members(member_types: &[String], simple_types: &[XsdSimpleType], namespace: Option<&str>, visited: &mut Vec<String>, members: &mut Vec<String>) {
+        for member_type in member_types {
+            let nested = simple_types.iter()
+                .find(|st| st.name.as_deref() == Some(member_type.as_str()))
+                .and_then(|st| st.union.as_ref());
+            match nested {
+                Some(nested) if !visited.contains(member_type) => {
+                    visited.push(member_type.clone());
+                    Self::union_members(nested, simple_types, namespace, visited, members);
+                },
+                Some(_) => log::warn!("Union `{}` has itself as a member", Self::local_name(member_type)),
+                None => {
+                    let member = Self::schemata_type(member_type, namespace);
+                    if !members.contains(&member) {
+                        members.push(member);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Named simple types that are not enumerations become aliases of the type they stand for,
+    /// annotated with the facets of a restriction.
+    pub(crate) fn get_aliases(&self, simple_types: &[XsdSimpleType], all_simple_types: &[XsdSimpleType], namespace: Option<&str>) -> Vec<SchemataAlias> {
         simple_types.iter()
             .filter_map(|st| {
-                st.restriction.as_ref().map(|r| SchemataEnum {
-                    name: st.name.clone().unwrap_or_else(|| "UnnamedEnum".to_string()),
-                    comment: None,
-                    values: r.enumeration.clone(),
-                })
-            }).collect()
+                let name = Self::local_name(st.name.as_deref()?).to_string();
+                let Some(type_name) = Self::simple_type_name(st, all_simple_types, namespace) else {
+                    if st.restriction.as_ref().is_none_or(|r| r.enumeration.is_empty()) {
+                        log::warn!("Simple type `{}` declares its parts inline and is left out", name);
+                    }
+                    return None;
+                };
+                let mut annotations = Self::facet_restriction(st).map(Self::get_facet_annotations).unwrap_or_default();
+                Self::append_annotation(&mut annotations, &Self::get_appinfo_annotations(&st.appinfo));
+                Some(SchemataAlias { name, type_name, annotations, comment: Self::get_comment(st.comment.as_deref()) })
+            })
+            .collect()
+    }
+
+    /// Restrictions without enumeration values only constrain their base type and are not enums.
+    pub(crate) fn get_enums(&self, simple_types: &[XsdSimpleType]) -> Vec<SchemataEnum> {
+        simple_types.iter()
+            .filter_map(|st| Self::get_enum(st.name.clone().unwrap_or_else(|| "UnnamedEnum".to_string()), st))
+            .collect()
+    }
+
+    /// The enum `name` for a simple type that is an enumeration.
+    fn get_enum(name: String, simple_type: &XsdSimpleType) -> Option<SchemataEnum> {
+        simple_type.restriction.as_ref().filter(|r| !r.enumeration.is_empty()).map(|r| SchemataEnum {
+            name,
+            annotations: Self::get_appinfo_annotations(&simple_type.appinfo),
+            comment: Self::get_comment(simple_type.comment.as_deref()),
+            values: r.enumeration.clone(),
+            value_comments: r.enumeration_comments.iter()
+                .filter_map(|(value, comment)| Some((value.clone(), Self::get_comment(Some(comment))?)))
+                .collect::<BTreeMap<_, _>>(),
+            value_annotations: BTreeMap::new(),
+        })
+    }
+
+    /// `@size` with the bounds of a repeated element that differ from zero and unbounded.
+    fn get_size_annotation(min: u64, max: Option<u64>) -> String {
+        let bounds: Vec<String> = [("min", Some(min).filter(|min| *min > 0)), ("max", max)].into_iter()
+            .filter_map(|(name, bound)| bound.map(|bound| format!("{} = {}", name, bound)))
+            .collect();
+        if bounds.is_empty() {
+            return String::new();
+        }
+        format!("@size({})", bounds.join(", "))
+    }
+
+    /// Whether a field type is an array already: a list, or an alias of one.
+    fn is_list(type_name: &str, simple_types: &[XsdSimpleType], namespace: Option<&str>) -> bool {
+        let mut type_name = type_name.to_string();
+        let mut visited = HashSet::new();
+        while !type_name.ends_with("[]") {
+            let written = |st: &&XsdSimpleType| st.name.as_deref().is_some_and(|name| Self::schemata_type(name, namespace) == type_name);
+            let Some(st) = simple_types.iter().find(written) else {
+                return false;
+            };
+            match Self::simple_type_name(st, simple_types, namespace) {
+                Some(aliased) if visited.insert(type_name.clone()) => type_name = aliased,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    fn append_annotation(annotations: &mut String, annotation: &str) {
+        if !annotation.is_empty() {
+            if !annotations.is_empty() {
+                annotations.push(' ');
+            }
+            annotations.push_str(annotation);
+        }
+    }
+
+    /// Writes appinfo as `@appinfo("...")`, or `@appinfo(source = "...", value = "...")` when it
+    /// names its source.
+    pub(crate) fn get_appinfo_annotations(appinfo: &[XsdAppInfo]) -> String {
+        let mut annotations = Vec::new();
+        for info in appinfo {
+            if info.content.contains('"') || info.source.as_deref().is_some_and(|s| s.contains('"')) {
+                log::warn!("Dropping appinfo `{}`: Schemata strings cannot contain quotes", info.content);
+                continue;
+            }
+            annotations.push(match (&info.source, info.content.as_str()) {
+                (Some(source), "") => format!("@appinfo(source = \"{}\")", source),
+                (Some(source), content) => format!("@appinfo(source = \"{}\", value = \"{}\")", source, content),
+                (None, content) => format!("@appinfo(\"{}\")", content),
+            });
+        }
+        annotations.join(" ")
+    }
+
+    /// Documentation as a comment, with long lines wrapped at word boundaries.
+    pub(crate) fn get_comment(documentation: Option<&str>) -> Option<String> {
+        let documentation = documentation?.trim();
+        if documentation.is_empty() {
+            return None;
+        }
+
+        let mut lines = Vec::new();
+        for line in documentation.lines() {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let mut current = String::new();
+            for word in line.split_whitespace() {
+                if !current.is_empty() && indent.len() + current.len() + 1 + word.len() > COMMENT_WIDTH {
+                    lines.push(format!("{}{}", indent, current));
+                    current.clear();
+                }
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(word);
+            }
+            lines.push(format!("{}{}", indent, current));
+        }
+        Some(lines.join("\n"))
     }
 
-    pub(crate) fn get_annotations(&self, element: &XsdElement) -> String {
+    /// Writes restriction facets as annotations, such as `@pattern("[0-9]{5}")` or `@maxLength(10)`.
+    pub(crate) fn get_facet_annotations(restriction: &XsdRestriction) -> String {
         let mut annotations = Vec::new();
 
-        if let Some(min_occurs) = &element.min_occurs {
-            annotations.push(format!("@minOccurs({})", min_occurs));
+        if let Some(pattern) = &restriction.pattern {
+            if pattern.contains('"') {
+                log::warn!("Dropping pattern `{}`: Schemata strings cannot contain quotes", pattern);
+            } else {
+                annotations.push(format!("@pattern(\"{}\")", pattern));
+            }
         }
-        if let Some(max_occurs) = &element.max_occurs {
-            annotations.push(format!("@maxOccurs({})", max_occurs));
+        let lengths = [
+            ("length", restriction.length),
+            ("minLength", restriction.min_length),
+            ("maxLength", restriction.max_length),
+            ("totalDigits", restriction.total_digits),
+            ("fractionDigits", restriction.fraction_digits),
+        ];
+        for (name, value) in lengths {
+            if let Some(value) = value {
+                annotations.push(format!("@{}({})", name, value));
+            }
+        }
+        let bounds = [
+            ("minInclusive", &restriction.min_inclusive),
+            ("maxInclusive", &restriction.max_inclusive),
+            ("minExclusive", &restriction.min_exclusive),
+            ("maxExclusive", &restriction.max_exclusive),
+        ];
+        for (name, value) in bounds {
+            if let Some(value) = value {
+                // Bounds of dates and times are kept as strings
+                if value.parse::<f64>().is_ok() {
+                    annotations.push(format!("@{}({})", name, value));
+                } else {
+                    annotations.push(format!("@{}(\"{}\")", name, value));
+                }
+            }
         }
 
         annotations.join(" ")
     }
-}
\ No newline at end of file
+}
+
+impl<'a> GroupDefinitions<'a> {
+    fn new(xsd_schemas: &[XsdSchema], mixins: &'a [Mixin]) -> Self {
+        Self {
+            groups: xsd_schemas.iter()
+                .flat_map(|xsd_schema| xsd_schema.groups.iter().map(|group| (SchemataGenerator::expanded_name(xsd_schema.target_namespace.as_deref(), &group.name), group.clone())))
+                .collect(),
+            attribute_groups: xsd_schemas.iter()
+                .flat_map(|xsd_schema| xsd_schema.attribute_groups.iter().map(|group| (SchemataGenerator::expanded_name(xsd_schema.target_namespace.as_deref(), &group.name), group.clone())))
+                .collect(),
+            mixins,
+        }
+    }
+
+    /// Writes the content of the model group a reference names in place of the reference,
+    /// bounded like the reference, and adds the attributes of the attribute groups a type
+    /// refers to after its own. References that are written as the `@include` of a mixin are
+    /// kept: with [`Groups::Mixins`], those made directly from the content of a named type or
+    /// group that occur exactly once outside a choice.
+    fn resolve_group_refs(&self, xsd_schema: &mut XsdSchema) {
+        for complex_type in &mut xsd_schema.complex_types {
+            self.resolve_complex_type(complex_type, true, &mut Vec::new());
+        }
+        for complex_type in xsd_schema.elements.iter_mut().filter_map(|element| element.complex_type.as_mut()) {
+            self.resolve_complex_type(complex_type, false, &mut Vec::new());
+        }
+        let namespace = xsd_schema.target_namespace.as_deref();
+        for group in &mut xsd_schema.groups {
+            let mut stack = vec![SchemataGenerator::expanded_name(namespace, &group.name)];
+            if let Some(content) = &mut group.content {
+                self.resolve_compositor(content, true, &mut stack);
+            }
+        }
+        for group in &mut xsd_schema.attribute_groups {
+            let mut stack = vec![SchemataGenerator::expanded_name(namespace, &group.name)];
+            self.resolve_attribute_groups(&mut group.attribute_groups, &mut group.attributes, &mut group.any_attribute, true, &mut stack);
+        }
+    }
+
+    fn has_mixin(&self, reference: &str, attributes: bool) -> bool {
+        self.mixins.iter().any(|mixin| mixin.name == reference && mixin.attributes == attributes)
+    }
+
+    /// `named` tells whether references may be kept as mixins. `stack` holds the groups being
+    /// expanded, to stop at circular references, which may go through the anonymous types of
+    /// elements.
+    fn resolve_complex_type(&self, complex_type: &mut XsdComplexType, named: bool, stack: &mut Vec<String>) {
+        if let Some(content) = &mut complex_type.content {
+            let once = SchemataGenerator::occurs(content.min_occurs.as_deref(), content.max_occurs.as_deref()) == (1, Some(1));
+            self.resolve_compositor(content, named && once, stack);
+        }
+        self.resolve_attribute_groups(&mut complex_type.attribute_groups, &mut complex_type.attributes, &mut complex_type.any_attribute, named, stack);
+    }
+
+    fn resolve_compositor(&self, compositor: &mut XsdCompositor, keep: bool, stack: &mut Vec<String>) {
+        let keep = keep && compositor.kind != XsdCompositorKind::Choice;
+        for particle in std::mem::take(&mut compositor.particles) {
+            match particle {
+                XsdParticle::Element(mut element) => {
+                    if let Some(complex_type) = &mut element.complex_type {
+                        self.resolve_complex_type(complex_type, false, stack);
+                    }
+                    compositor.particles.push(XsdParticle::Element(element));
+                },
+                XsdParticle::Compositor(mut nested) => {
+                    self.resolve_compositor(&mut nested, false, stack);
+                    compositor.particles.push(XsdParticle::Compositor(nested));
+                },
+                XsdParticle::Any(wildcard) => compositor.particles.push(XsdParticle::Any(wildcard)),
+                XsdParticle::Group(reference) => {
+                    let once = SchemataGenerator::occurs(reference.min_occurs.as_deref(), reference.max_occurs.as_deref()) == (1, Some(1));
+                    if keep && once && self.has_mixin(&reference.reference, false) {
+                        compositor.particles.push(XsdParticle::Group(reference));
+                        continue;
+                    }
+                    if stack.contains(&reference.reference) {
+                        log::warn!("Group `{}` contains itself; the nested reference is left out", reference.reference);
+                        continue;
+                    }
+                    let Some((_, group)) = self.groups.iter().find(|(name, _)| *name == reference.reference) else {
+                        log::warn!("Group `{}` is not declared in any loaded schema and is left out", reference.reference);
+                        continue;
+                    };
+                    let Some(mut content) = group.content.clone() else {
+                        continue;
+                    };
+                    content.min_occurs = reference.min_occurs;
+                    content.max_occurs = reference.max_occurs;
+                    stack.push(reference.reference);
+                    self.resolve_compositor(&mut content, false, stack);
+                    stack.pop();
+                    compositor.particles.push(XsdParticle::Compositor(content));
+                },
+            }
+        }
+    }
+
+    /// A wildcard of an attribute group is taken by the type when it has none of its own.
+    fn resolve_attribute_groups(&self, references: &mut Vec<String>, attributes: &mut Vec<XsdAttribute>, any_attribute: &mut Option<XsdWildcard>, keep: bool, stack: &mut Vec<String>) {
+        for reference in std::mem::take(references) {
+            if keep && self.has_mixin(&reference, true) {
+                references.push(reference);
+                continue;
+            }
+            if stack.contains(&reference) {
+                log::warn!("Attribute group `{}` contains itself; the nested reference is left out", reference);
+                continue;
+            }
+            let Some((_, group)) = self.attribute_groups.iter().find(|(name, _)| *name == reference) else {
+                log::warn!("Attribute group `{}` is not declared in any loaded schema and is left out", reference);
+                continue;
+            };
+            attributes.extend(group.attributes.iter().cloned());
+            if any_attribute.is_none() {
+                any_attribute.clone_from(&group.any_attribute);
+            }
+            stack.push(reference);
+            self.resolve_attribute_groups(&mut group.attribute_groups.clone(), attributes, any_attribute, false, stack);
+            stack.pop();
+        }
+    }
+}
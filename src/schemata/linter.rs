@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use super::analyzer::{ResolvedType, SymbolTable, KNOWN_ANNOTATIONS};
+use super::ast::{Annotation, Document, Field, Schema};
+use crate::diagnostics::Diagnostic;
+
+/// Style checks for documents that already passed semantic analysis. Findings are warnings;
+/// none of them stop a document from being generated.
+///
+/// - schema, enum and alias names are `PascalCase`, field names `camelCase` and enum values
+///   `SCREAMING_SNAKE_CASE`
+/// - annotations are ones the analyzer knows
+/// - schemas have fields and enums are referenced by some field
+pub struct SchemataLinter<'a> {
+    symbols: &'a SymbolTable,
+    /// Qualified names of the enums used as a field type
+    used_enums: HashSet<String>,
+    warnings: Vec<Diagnostic>,
+}
+
+impl<'a> SchemataLinter<'a> {
+    pub fn lint(document: &Document, symbols: &'a SymbolTable) -> Vec<Diagnostic> {
+        let mut linter = Self {
+            symbols,
+            used_enums: HashSet::new(),
+            warnings: Vec::new(),
+        };
+
+        for namespace in &document.namespaces {
+            for schema in &namespace.schemas {
+                let name = schema.name.as_deref().unwrap_or_default();
+                if !Self::is_pascal_case(name) {
+                    linter.warnings.push(Diagnostic::warning(format!("Schema `{}` should have a PascalCase name", name))
+                        .with_span(schema.span)
+                        .with_help("schema names are PascalCase"));
+                }
+                linter.lint_schema(&namespace.name, name, schema);
+            }
+        }
+
+        for namespace in &document.namespaces {
+            for enumeration in &namespace.enums {
+                if !Self::is_pascal_case(&enumeration.name) {
+                    linter.warnings.push(Diagnostic::warning(format!("Enum `{}` should have a PascalCase name", enumeration.name))
+                        .with_span(enumeration.span)
+                        .with_help("enum names are PascalCase"));
+                }
+                for value in enumeration.values.iter().filter(|v| !Self::is_screaming_snake_case(v)) {
+                    linter.warnings.push(Diagnostic::warning(format!("Enum value `{}` should be SCREAMING_SNAKE_CASE", value))
+                        .with_span(enumeration.span)
+                        .with_label(format!("in `{}`", enumeration.name)));
+                }
+                linter.lint_annotations(&enumeration.annotations);
+                if !linter.used_enums.contains(&format!("{}.{}", namespace.name, enumeration.name)) {
+                    linter.warnings.push(Diagnostic::warning(format!("Enum `{}` is never used", enumeration.name))
+                        .with_span(enumeration.span));
+                }
+            }
+        }
+        for namespace in &document.namespaces {
+            for alias in &namespace.aliases {
+                if !Self::is_pascal_case(&alias.name) {
+                    linter.warnings.push(Diagnostic::warning(format!("Alias `{}` should have a PascalCase name", alias.name))
+                        .with_span(alias.span)
+                        .with_help("alias names are PascalCase"));
+                }
+                linter.lint_annotations(&alias.annotations);
+            }
+        }
+        linter.warnings
+    }
+
+    fn lint_schema(&mut self, namespace: &str, name: &str, schema: &Schema) {
+        self.lint_annotations(&schema.annotations);
+        // A schema extending another, such as an alias of it, or including mixins has their fields,
+        // and an extensible one may have any
+        if schema.fields.is_empty() && !schema.annotations.iter().any(|a| matches!(a.name.as_str(), "extends" | "include" | "extensible")) {
+            self.warnings.push(Diagnostic::warning(format!("Schema `{}` has no fields", name)).with_span(schema.span));
+        }
+
+        for field in &schema.fields {
+            if !Self::is_camel_case(&field.name) {
+                self.warnings.push(Diagnostic::warning(format!("Field `{}` should have a camelCase name", field.name))
+                    .with_span(field.span)
+                    .with_help("field names are camelCase"));
+            }
+            self.lint_annotations(&field.annotations);
+            self.use_type(namespace, field);
+            if let Some(inline) = &field.inline_schema {
+                self.lint_schema(namespace, &field.type_ref.name, inline);
+            }
+        }
+    }
+
+    fn lint_annotations(&mut self, annotations: &[Annotation]) {
+        for annotation in annotations {
+            if !KNOWN_ANNOTATIONS.iter().any(|(name, _)| *name == annotation.name) {
+                self.warnings.push(Diagnostic::warning(format!("Unknown annotation @{}", annotation.name))
+                    .with_span(annotation.span)
+                    .with_note("unknown annotations are passed through unchecked"));
+            }
+        }
+    }
+
+    fn use_type(&mut self, namespace: &str, field: &Field) {
+        if let Ok(ResolvedType::Enum(symbol)) = self.symbols.resolve(namespace, &field.type_ref) {
+            self.used_enums.insert(symbol.qualified_name());
+        }
+    }
+
+    fn is_pascal_case(name: &str) -> bool {
+        name.starts_with(|c: char| c.is_ascii_uppercase()) && name.chars().all(|c| c.is_ascii_alphanumeric())
+    }
+
+    fn is_camel_case(name: &str) -> bool {
+        name.starts_with(|c: char| c.is_ascii_lowercase()) && name.chars().all(|c| c.is_ascii_alphanumeric())
+    }
+
+    fn is_screaming_snake_case(name: &str) -> bool {
+        name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    }
+}
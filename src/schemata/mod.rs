@@ -1,4 +1,16 @@
+pub mod analyzer;
+pub mod ast;
+pub mod diff;
+mod formatter;
 mod generator;
-mod tests;
+mod linter;
+mod parser;
+pub(crate) mod tests;
+pub mod type_mapping;
 pub mod types;
-pub use generator::SchemataGenerator;
\ No newline at end of file
+pub use analyzer::SemanticAnalyzer;
+pub use diff::SchemataDiff;
+pub use formatter::SchemataFormatter;
+pub use generator::{Groups, Inheritance, InlineSchemas, SchemataGenerator};
+pub use linter::SchemataLinter;
+pub use parser::SchemataParser;
\ No newline at end of file
@@ -0,0 +1,439 @@
+use std::collections::BTreeMap;
+use pest::iterators::Pair;
+use pest::Parser;
+use pest_derive::Parser;
+
+use super::ast::*;
+use crate::diagnostics::{Diagnostic, Span};
+
+#[derive(Parser)]
+#[grammar = "../grammars/schemata.pest"]
+pub(crate) struct SchemataGrammar;
+
+pub struct SchemataParser;
+
+/// `#` comments of a document keyed by line.
+///
+/// pest attaches implicit comments to whichever rule happened to be skipping whitespace,
+/// so comments are associated with declarations by position instead of by tree shape.
+pub(crate) struct Comments {
+    lines: BTreeMap<usize, Comment>,
+}
+
+struct Comment {
+    text: String,
+    /// True when nothing but whitespace precedes the comment on its line.
+    own_line: bool,
+}
+
+impl Comments {
+    fn collect(file: &Pair<Rule>) -> Self {
+        let lines = file.clone().into_inner().flatten()
+            .filter(|p| p.as_rule() == Rule::COMMENT)
+            .map(|p| {
+                let start = p.as_span().start_pos();
+                let comment = Comment {
+                    text: p.as_str().trim_start_matches('#').trim().to_string(),
+                    own_line: start.line_of().trim_start().starts_with('#'),
+                };
+                (start.line_col().0, comment)
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// The block of whole-line comments directly above `line`.
+    fn leading(&self, line: usize) -> Option<String> {
+        let mut block = Vec::new();
+        let mut current = line;
+        while current > 1 {
+            current -= 1;
+            match self.lines.get(&current) {
+                Some(comment) if comment.own_line => block.push(comment.text.as_str()),
+                _ => break,
+            }
+        }
+        if block.is_empty() {
+            return None;
+        }
+        block.reverse();
+        Some(block.join("\n"))
+    }
+
+    /// The comment following code on `line`.
+    fn trailing(&self, line: usize) -> Option<String> {
+        self.lines.get(&line)
+            .filter(|comment| !comment.own_line)
+            .map(|comment| comment.text.clone())
+    }
+}
+
+impl SchemataParser {
+    pub fn parse(input: &str) -> Result<Document, Box<dyn std::error::Error>> {
+        let file = SchemataGrammar::parse(Rule::file, input)
+            .map_err(Self::syntax_error)?
+            .next()
+            .ok_or("Empty parse tree")?;
+        let comments = Comments::collect(&file);
+
+        let mut document = Document { namespaces: Vec::new() };
+        // Namespace set by the latest statement-style declaration
+        let mut current: Option<String> = None;
+
+        for pair in file.into_inner() {
+            match pair.as_rule() {
+                Rule::namespace_declaration => {
+                    let name = Self::first_identifier(pair.clone())?;
+                    Self::parse_namespace_comment(&pair, document.namespace_entry(&name), &comments);
+                    current = Some(name);
+                },
+                Rule::namespace_block => {
+                    let name = Self::first_identifier(pair.clone())?;
+                    let namespace = document.namespace_entry(&name);
+                    Self::parse_namespace_comment(&pair, namespace, &comments);
+                    for item in pair.into_inner() {
+                        Self::parse_item(item, namespace, &comments)?;
+                    }
+                },
+                Rule::schema | Rule::r#enum | Rule::type_alias => {
+                    let name = current.as_deref().ok_or_else(|| {
+                        Diagnostic::error("Declaration outside of a namespace")
+                            .with_span(Self::span(&pair))
+                            .with_help("add a `namespace a.b;` line before it, or wrap it in `namespace a.b { ... }`")
+                    })?;
+                    Self::parse_item(pair, document.namespace_entry(name), &comments)?;
+                },
+                _ => {}
+            }
+        }
+
+        Ok(document)
+    }
+
+    /// Keeps the comment above the first declaration of a namespace that has one.
+    fn parse_namespace_comment(pair: &Pair<Rule>, namespace: &mut Namespace, comments: &Comments) {
+        if namespace.comment.is_none() {
+            namespace.comment = comments.leading(Self::start_line(pair));
+        }
+    }
+
+    /// Adds a top-level `schema`, `enum` or `type` alias to `namespace`.
+    fn parse_item(pair: Pair<Rule>, namespace: &mut Namespace, comments: &Comments) -> Result<(), Box<dyn std::error::Error>> {
+        match pair.as_rule() {
+            Rule::schema => namespace.schemas.push(Self::parse_schema(pair, comments)?),
+            Rule::r#enum => namespace.enums.push(Self::parse_enum(pair, comments)?),
+            Rule::type_alias => namespace.aliases.push(Self::parse_type_alias(pair, comments)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Builds a named `schema` or an `inline_schema`; both share the same body layout.
+    pub(crate) fn parse_schema(pair: Pair<Rule>, comments: &Comments) -> Result<Schema, Box<dyn std::error::Error>> {
+        let start = pair.as_span().start();
+        let mut schema = Schema {
+            name: None,
+            annotations: Vec::new(),
+            comment: None,
+            fields: Vec::new(),
+            span: Span::new(start, start + "schema".len()),
+        };
+        if pair.as_rule() == Rule::schema {
+            schema.comment = comments.leading(Self::start_line(&pair));
+        }
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::identifier => {
+                    schema.name = Some(inner.as_str().to_string());
+                    schema.span = Self::span(&inner);
+                },
+                Rule::annotation => schema.annotations.push(Self::parse_annotation(inner)?),
+                Rule::field => schema.fields.push(Self::parse_field(inner, comments)?),
+                _ => {}
+            }
+        }
+
+        Ok(schema)
+    }
+
+    pub(crate) fn parse_field(pair: Pair<Rule>, comments: &Comments) -> Result<Field, Box<dyn std::error::Error>> {
+        let start_line = Self::start_line(&pair);
+        let mut end_line = start_line;
+        let mut name = None;
+        let mut span = Self::span(&pair);
+        let mut type_ref = None;
+        let mut annotations = Vec::new();
+        let mut inline_schema = None;
+
+        for inner in pair.into_inner() {
+            end_line = end_line.max(Self::code_end_line(&inner));
+            match inner.as_rule() {
+                Rule::identifier => {
+                    name = Some(inner.as_str().to_string());
+                    span = Self::span(&inner);
+                },
+                Rule::r#type => type_ref = Some(Self::parse_type(inner)?),
+                Rule::annotation => annotations.push(Self::parse_annotation(inner)?),
+                Rule::inline_schema => inline_schema = Some(Self::parse_schema(inner, comments)?),
+                _ => {}
+            }
+        }
+
+        Ok(Field {
+            name: name.ok_or("Field without a name")?,
+            type_ref: type_ref.ok_or("Field without a type")?,
+            annotations,
+            // A trailing comment wins over a leading one
+            comment: comments.trailing(end_line).or_else(|| comments.leading(start_line)),
+            inline_schema,
+            span,
+        })
+    }
+
+    pub(crate) fn parse_type(pair: Pair<Rule>) -> Result<TypeRef, Box<dyn std::error::Error>> {
+        let mut type_ref = TypeRef {
+            name: String::new(),
+            basic: false,
+            decimal: None,
+            map: None,
+            union: None,
+            array: false,
+            nullable: false,
+            span: Self::span(&pair),
+        };
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::basic_type => {
+                    type_ref.name = inner.as_str().to_string();
+                    type_ref.basic = true;
+                    type_ref.span = Self::span(&inner);
+                },
+                Rule::decimal_type => {
+                    type_ref.name = "decimal".to_string();
+                    type_ref.basic = true;
+                    type_ref.span = Self::span(&inner);
+                    let mut numbers = inner.into_inner().map(|n| n.as_str().parse::<u32>());
+                    let precision = numbers.next().ok_or("Decimal without precision")??;
+                    let scale = numbers.next().ok_or("Decimal without scale")??;
+                    type_ref.decimal = Some((precision, scale));
+                },
+                Rule::map_type => {
+                    type_ref.name = "map".to_string();
+                    type_ref.basic = true;
+                    type_ref.span = Self::span(&inner);
+                    let mut types = inner.into_inner().map(Self::parse_type);
+                    let key = types.next().ok_or("Map without a key type")??;
+                    let value = types.next().ok_or("Map without a value type")??;
+                    type_ref.map = Some(Box::new((key, value)));
+                },
+                Rule::union_type => {
+                    type_ref.name = "union".to_string();
+                    type_ref.basic = true;
+                    type_ref.span = Self::span(&inner);
+                    type_ref.union = Some(inner.into_inner().map(Self::parse_type).collect::<Result<_, _>>()?);
+                },
+                Rule::complex_type => {
+                    type_ref.name = inner.as_str().to_string();
+                    type_ref.span = Self::span(&inner);
+                },
+                Rule::array => type_ref.array = true,
+                Rule::nullable => type_ref.nullable = true,
+                _ => {}
+            }
+        }
+
+        if type_ref.name.is_empty() {
+            return Err("Type without a name".into());
+        }
+        Ok(type_ref)
+    }
+
+    pub(crate) fn parse_type_alias(pair: Pair<Rule>, comments: &Comments) -> Result<TypeAlias, Box<dyn std::error::Error>> {
+        let comment = comments.leading(Self::start_line(&pair));
+        let mut name = None;
+        let mut span = Self::span(&pair);
+        let mut type_ref = None;
+        let mut annotations = Vec::new();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::identifier => {
+                    name = Some(inner.as_str().to_string());
+                    span = Self::span(&inner);
+                },
+                Rule::r#type => type_ref = Some(Self::parse_type(inner)?),
+                Rule::annotation => annotations.push(Self::parse_annotation(inner)?),
+                _ => {}
+            }
+        }
+
+        Ok(TypeAlias {
+            name: name.ok_or("Type alias without a name")?,
+            type_ref: type_ref.ok_or("Type alias without a type")?,
+            annotations,
+            comment,
+            span,
+        })
+    }
+
+    pub(crate) fn parse_annotation(pair: Pair<Rule>) -> Result<Annotation, Box<dyn std::error::Error>> {
+        let span = Self::span(&pair);
+        let mut name = None;
+        let mut args = Vec::new();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::identifier => name = Some(inner.as_str().to_string()),
+                Rule::annotation_args => {
+                    for arg in inner.into_inner().filter(|p| p.as_rule() == Rule::annotation_arg) {
+                        args.push(Self::parse_annotation_arg(arg)?);
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        Ok(Annotation {
+            name: name.ok_or("Annotation without a name")?,
+            args,
+            span,
+        })
+    }
+
+    fn parse_annotation_arg(pair: Pair<Rule>) -> Result<AnnotationArg, Box<dyn std::error::Error>> {
+        let mut name = None;
+        let mut value = None;
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::identifier => name = Some(inner.as_str().to_string()),
+                Rule::literal => value = Some(Self::parse_literal(inner)?),
+                _ => {}
+            }
+        }
+
+        Ok(AnnotationArg {
+            name,
+            value: value.ok_or("Annotation argument without a value")?,
+        })
+    }
+
+    fn parse_literal(pair: Pair<Rule>) -> Result<Literal, Box<dyn std::error::Error>> {
+        let inner = pair.into_inner().next().ok_or("Empty literal")?;
+        match inner.as_rule() {
+            Rule::string => {
+                let content = inner.into_inner().next().map(|p| p.as_str()).unwrap_or_default();
+                Ok(Literal::String(content.to_string()))
+            },
+            Rule::number => Ok(Literal::Number(inner.as_str().to_string())),
+            Rule::identifier => Ok(Literal::Identifier(inner.as_str().to_string())),
+            rule => Err(format!("Unexpected literal: {:?}", rule).into()),
+        }
+    }
+
+    /// Values take the comment trailing them or the block above them, unless they share a line
+    /// with the enum's name.
+    pub(crate) fn parse_enum(pair: Pair<Rule>, comments: &Comments) -> Result<Enum, Box<dyn std::error::Error>> {
+        let start_line = Self::start_line(&pair);
+        let comment = comments.leading(start_line);
+        let mut name = None;
+        let mut span = Self::span(&pair);
+        let mut annotations = Vec::new();
+        let mut values = Vec::new();
+        let mut value_comments = BTreeMap::new();
+        let mut value_annotations = BTreeMap::new();
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::identifier if name.is_none() => {
+                    name = Some(inner.as_str().to_string());
+                    span = Self::span(&inner);
+                },
+                Rule::enum_value => {
+                    let line = Self::start_line(&inner);
+                    let value_comment = (line != start_line)
+                        .then(|| comments.trailing(line).or_else(|| comments.leading(line)))
+                        .flatten();
+                    let mut parts = inner.into_inner();
+                    let value = parts.next().ok_or("Enum value without a name")?.as_str().to_string();
+                    let annotations = parts
+                        .filter(|part| part.as_rule() == Rule::annotation)
+                        .map(Self::parse_annotation)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(value_comment) = value_comment {
+                        value_comments.insert(value.clone(), value_comment);
+                    }
+                    if !annotations.is_empty() {
+                        value_annotations.insert(value.clone(), annotations);
+                    }
+                    values.push(value);
+                },
+                Rule::annotation => annotations.push(Self::parse_annotation(inner)?),
+                _ => {}
+            }
+        }
+
+        Ok(Enum {
+            name: name.ok_or("Enum without a name")?,
+            annotations,
+            comment,
+            values,
+            value_comments,
+            value_annotations,
+            span,
+        })
+    }
+
+    fn first_identifier(pair: Pair<Rule>) -> Result<String, Box<dyn std::error::Error>> {
+        pair.into_inner()
+            .find(|p| p.as_rule() == Rule::identifier)
+            .map(|p| p.as_str().to_string())
+            .ok_or_else(|| "Missing identifier".into())
+    }
+
+    fn span(pair: &Pair<Rule>) -> Span {
+        Span::new(pair.as_span().start(), pair.as_span().end())
+    }
+
+    fn syntax_error(error: pest::error::Error<Rule>) -> Diagnostic {
+        let error = error.renamed_rules(|rule| match rule {
+            Rule::identifier | Rule::complex_type => "identifier".to_string(),
+            Rule::basic_type | Rule::decimal_type | Rule::map_type | Rule::union_type => "basic type".to_string(),
+            Rule::type_alias => "type alias".to_string(),
+            Rule::r#type => "type".to_string(),
+            Rule::array => "`[]`".to_string(),
+            Rule::nullable => "`?`".to_string(),
+            Rule::annotation => "annotation".to_string(),
+            Rule::inline_schema => "inline schema".to_string(),
+            Rule::namespace_block | Rule::namespace_declaration => "namespace".to_string(),
+            Rule::EOI => "end of file".to_string(),
+            other => format!("{:?}", other),
+        });
+        let span = match error.location {
+            pest::error::InputLocation::Pos(pos) => Span::new(pos, pos + 1),
+            pest::error::InputLocation::Span((start, end)) => Span::new(start, end),
+        };
+        Diagnostic::error("Syntax error")
+            .with_span(span)
+            .with_label(error.variant.message().to_string())
+    }
+
+    fn start_line(pair: &Pair<Rule>) -> usize {
+        pair.as_span().start_pos().line_col().0
+    }
+
+    /// Line of the last token of `pair`. Non-atomic rules may end with skipped whitespace and
+    /// comments, so only leaf tokens and brace-terminated inline schemas are trusted.
+    fn code_end_line(pair: &Pair<Rule>) -> usize {
+        match pair.as_rule() {
+            Rule::COMMENT => 0,
+            Rule::inline_schema => pair.as_span().end_pos().line_col().0,
+            _ => pair.clone().into_inner()
+                .map(|inner| Self::code_end_line(&inner))
+                .max()
+                .unwrap_or_else(|| pair.as_span().end_pos().line_col().0),
+        }
+    }
+}
@@ -0,0 +1,271 @@
+#[cfg(test)]
+mod tests {
+    use crate::schemata::analyzer::{ResolvedType, SemanticErrorKind, SymbolKind};
+    use crate::schemata::{SchemataParser, SemanticAnalyzer};
+
+    fn error_kinds(input: &str) -> Vec<SemanticErrorKind> {
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        match SemanticAnalyzer::analyze(&document) {
+            Ok(_) => vec![],
+            Err(errors) => errors.0.iter().map(|e| e.kind).collect(),
+        }
+    }
+
+    #[test]
+    fn test_analyze_example_file() {
+        let document = SchemataParser::parse(include_str!("../../../schemata-example.schema")).unwrap();
+        let symbols = SemanticAnalyzer::analyze(&document).expect("Example file should be valid");
+
+        assert_eq!(symbols.get("com.example.people.User").map(|s| s.kind), Some(SymbolKind::Schema));
+        assert_eq!(symbols.get("com.example.ecommerce.OrderStatus").map(|s| s.kind), Some(SymbolKind::Enum));
+        // Inline schemas are registered under the field's type name
+        assert!(symbols.get("com.example.ecommerce.OrderItem").is_some());
+    }
+
+    #[test]
+    fn test_resolve_across_namespaces() {
+        let input = r#"
+        namespace com.example.people;
+
+        schema User {
+            orders Order[]
+            status com.example.ecommerce.OrderStatus
+        }
+
+        namespace com.example.ecommerce {
+            schema Order {
+                user User
+            }
+
+            enum OrderStatus {
+                PENDING
+            }
+        }
+        "#;
+        let document = SchemataParser::parse(input).unwrap();
+        let symbols = SemanticAnalyzer::analyze(&document).expect("Document should be valid");
+
+        let user = &document.namespaces[0].schemas[0];
+        match symbols.resolve("com.example.people", &user.fields[0].type_ref) {
+            Ok(ResolvedType::Schema(symbol)) => assert_eq!(symbol.qualified_name(), "com.example.ecommerce.Order"),
+            other => panic!("Unexpected resolution: {:?}", other),
+        }
+        match symbols.resolve("com.example.people", &user.fields[1].type_ref) {
+            Ok(ResolvedType::Enum(symbol)) => assert_eq!(symbol.name, "OrderStatus"),
+            other => panic!("Unexpected resolution: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_and_ambiguous_types() {
+        let input = r#"
+        namespace a;
+        schema Address { street string }
+        schema User {
+            profile Profle?
+            address Address
+        }
+
+        namespace b;
+        schema Address { street string }
+        schema Account {
+            address Address
+            billing Address
+        }
+
+        namespace c;
+        schema Customer {
+            address Address
+        }
+        "#;
+        assert_eq!(error_kinds(input), vec![SemanticErrorKind::UnknownType, SemanticErrorKind::AmbiguousType]);
+    }
+
+    #[test]
+    fn test_invalid_parameterized_types() {
+        let input = r#"
+        namespace test;
+        schema Sensor { id string }
+        schema Reading {
+            amount decimal(2, 4)
+            bySensor map<Sensor, int>
+            byName map<string?, int>
+            values map<string, Sensr>
+            valid map<long, decimal(10, 2)[]>
+        }
+        "#;
+        assert_eq!(error_kinds(input), vec![
+            SemanticErrorKind::InvalidType,
+            SemanticErrorKind::InvalidType,
+            SemanticErrorKind::InvalidType,
+            SemanticErrorKind::UnknownType,
+        ]);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let input = r#"
+        namespace test;
+        schema User {
+            id int
+            id string
+        }
+        enum User { A, B, A }
+        "#;
+        assert_eq!(error_kinds(input), vec![
+            SemanticErrorKind::DuplicateDeclaration,
+            SemanticErrorKind::DuplicateField,
+            SemanticErrorKind::DuplicateEnumValue,
+        ]);
+    }
+
+    #[test]
+    fn test_illegal_annotation_targets() {
+        let input = r#"
+        namespace test;
+        schema User @unique {
+            tags string[] @id
+            status Status @relation(field = "statusId")
+            name string @custom(value = "kept")
+        }
+        enum Status @id { ACTIVE }
+        "#;
+        assert_eq!(error_kinds(input), vec![SemanticErrorKind::IllegalAnnotationTarget; 4]);
+    }
+
+    #[test]
+    fn test_relation_to_a_key_names_a_schema() {
+        let input = r#"
+        namespace test;
+        schema Product { sku string @id }
+        schema Offer {
+            sku string @relation(schema = Product, field = "sku")
+            code string @relation(schema = Status, field = "code")
+            other string @relation(schema = Missing, field = "sku")
+            status Status @relation(field = "statusId")
+        }
+        enum Status { ACTIVE }
+        "#;
+        assert_eq!(error_kinds(input), vec![
+            SemanticErrorKind::IllegalAnnotationTarget,
+            SemanticErrorKind::UnknownType,
+            SemanticErrorKind::IllegalAnnotationTarget,
+        ]);
+    }
+
+    #[test]
+    fn test_size_requires_an_array() {
+        let input = r#"
+        namespace test;
+        schema Order {
+            lines string[] @size(min = 1, max = 10)
+            note string @size(max = 1)
+        }
+        "#;
+        assert_eq!(error_kinds(input), vec![SemanticErrorKind::IllegalAnnotationTarget]);
+    }
+
+    #[test]
+    fn test_extends_names_a_schema() {
+        let input = r#"
+        namespace test;
+        schema Employee { name string }
+        schema Manager @extends(Employee) { reports int }
+        schema Intern @extends(Student) { mentor Employee }
+        schema Loop @extends(Loop) { id int }
+        schema Coded @extends(Status) { code string }
+        enum Status { ACTIVE }
+        "#;
+        assert_eq!(error_kinds(input), vec![
+            SemanticErrorKind::UnknownType,
+            SemanticErrorKind::IllegalAnnotationTarget,
+            SemanticErrorKind::IllegalAnnotationTarget,
+        ]);
+    }
+
+    #[test]
+    fn test_include_names_a_schema() {
+        let input = r#"
+        namespace test;
+        schema Contact @mixin { email string }
+        schema Customer @include(Contact) @include(Audit) { name string }
+        schema Supplier @include(Status) @include { name string }
+        enum Status { ACTIVE }
+        "#;
+        assert_eq!(error_kinds(input), vec![
+            SemanticErrorKind::UnknownType,
+            SemanticErrorKind::IllegalAnnotationTarget,
+            SemanticErrorKind::IllegalAnnotationTarget,
+        ]);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let input = r#"
+        namespace test;
+        type Sku = string @maxLength(value = "9");
+        type Skus = Sku[];
+        type Size = union<int, string>;
+        schema Item {
+            sku Sku
+            related Skus?
+            size Size
+            nested Skus[]
+            sizes union<int, Size>
+            optional union<int?, string>
+        }
+        type Loop = union<int, Other>;
+        type Other = Loop;
+        "#;
+        assert_eq!(error_kinds(input), vec![
+            SemanticErrorKind::InvalidType,
+            SemanticErrorKind::InvalidType,
+            SemanticErrorKind::InvalidType,
+            SemanticErrorKind::CyclicAlias,
+            SemanticErrorKind::CyclicAlias,
+        ]);
+
+        let document = SchemataParser::parse(r#"
+        namespace test;
+        type Sku = string @maxLength(value = "9");
+        type Skus = Sku[];
+        schema Item {
+            sku Sku @maxLength(value = "5")
+            related Skus?
+        }
+        "#).unwrap();
+        let symbols = SemanticAnalyzer::analyze(&document).expect("Document should be valid");
+        assert!(matches!(symbols.get("test.Sku").map(|s| s.kind), Some(SymbolKind::Alias)));
+
+        // Fields take the aliased type, and the alias annotations they do not override
+        let expanded = symbols.expand_aliases(&document);
+        let fields = &expanded.namespaces[0].schemas[0].fields;
+        assert_eq!(fields[0].type_ref.to_string(), "string");
+        assert_eq!(fields[0].annotations.len(), 1);
+        assert_eq!(fields[0].annotations[0].args[0].value, crate::schemata::ast::Literal::String("5".to_string()));
+        assert_eq!(fields[1].type_ref.to_string(), "string[]?");
+        assert_eq!(fields[1].annotations[0].name, "maxLength");
+    }
+
+    #[test]
+    fn test_error_diagnostics() {
+        use crate::diagnostics;
+
+        let input = "namespace test;\nschema Profile { id int }\nschema User {\n    profile Profle?\n}\n";
+        let document = SchemataParser::parse(input).unwrap();
+        let errors = SemanticAnalyzer::analyze(&document).expect_err("Analysis should fail");
+
+        let rendered = diagnostics::report(&errors, "user.schema", input);
+        assert_eq!(rendered, [
+            "error: Unknown type `Profle`",
+            " --> user.schema:4:13",
+            "  |",
+            "4 |     profile Profle?",
+            "  |             ^^^^^^ not declared in any namespace",
+            "  |",
+            "  = note: in `test.User.profile`",
+            "  = help: did you mean `Profile`?",
+            "",
+        ].join("\n"));
+    }
+}
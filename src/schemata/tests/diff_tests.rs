@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use crate::schemata::diff::ChangeKind;
+    use crate::schemata::{SchemataDiff, SchemataParser};
+
+    #[test]
+    fn test_compare() {
+        let old = SchemataParser::parse(r#"
+        namespace shop;
+        schema Order {
+            id int @id
+            note string
+            legacy string
+        }
+        enum Status { PENDING, SHIPPED }
+        schema Cart { id int }
+        "#).unwrap();
+        let new = SchemataParser::parse(r#"
+        namespace shop;
+        enum Status { PENDING, DELIVERED }
+        # Reordered and commented
+        schema Order {
+            note string? # Now optional
+            id int @id @generated("UUID")
+            total float
+        }
+
+        namespace billing;
+        schema Invoice { id int }
+        "#).unwrap();
+
+        let changes: Vec<String> = SchemataDiff::compare(&old, &new).iter().map(|c| c.to_string()).collect();
+        assert_eq!(changes, vec![
+            "~ shop.Order.id: annotations `@id` -> `@id @generated(\"UUID\")`",
+            "~ shop.Order.note: type `string` -> `string?`",
+            "- shop.Order.legacy",
+            "+ shop.Order.total: `float`",
+            "- shop.Cart",
+            "- shop.Status.SHIPPED",
+            "+ shop.Status.DELIVERED",
+            "+ billing",
+        ]);
+    }
+
+    #[test]
+    fn test_compare_identical() {
+        let source = include_str!("../../../schemata-example.schema");
+        let document = SchemataParser::parse(source).unwrap();
+        assert!(SchemataDiff::compare(&document, &document).is_empty());
+
+        let mut changed = document.clone();
+        changed.namespaces[1].schemas[0].fields[4].inline_schema.as_mut().unwrap().fields.pop();
+        let changes = SchemataDiff::compare(&document, &changed);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Removed);
+        assert_eq!(changes[0].path, "com.example.ecommerce.Order.items.order");
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use crate::schemata::{SchemataFormatter, SchemataParser};
+
+    #[test]
+    fn test_format() {
+        let input = r#"
+# The shop
+namespace shop {
+  # An order
+  # placed by a user
+  schema Order @table(name="orders") {
+    id int @id   # Primary key
+    note string?      # Free text
+    # Inline schema
+    items OrderItem[] schema {
+        sku string
+      quantity int @default(value="1") }
+  }
+  enum Status { PENDING, SHIPPED }
+}
+"#;
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let formatted = SchemataFormatter::format(&document);
+
+        assert_eq!(formatted, [
+            "# The shop",
+            "namespace shop;",
+            "",
+            "# An order",
+            "# placed by a user",
+            "schema Order @table(name = \"orders\") {",
+            "    id int @id   # Primary key",
+            "    note string? # Free text",
+            "    # Inline schema",
+            "    items OrderItem[] schema {",
+            "        sku string",
+            "        quantity int @default(value = \"1\")",
+            "    }",
+            "}",
+            "",
+            "enum Status {",
+            "    PENDING,",
+            "    SHIPPED,",
+            "}",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_format_enum_value_comments() {
+        let input = r#"
+namespace shop;
+enum Status {
+    PENDING,  # Waiting for payment
+    # Handed to the carrier,
+    # tracked from here on
+    SHIPPED,
+    RETURNED, # Back in stock
+}
+"#;
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let status = &document.namespaces[0].enums[0];
+        assert_eq!(status.value_comments["SHIPPED"], "Handed to the carrier,\ntracked from here on");
+
+        assert_eq!(SchemataFormatter::format(&document), [
+            "namespace shop;",
+            "",
+            "enum Status {",
+            "    PENDING,  # Waiting for payment",
+            "    # Handed to the carrier,",
+            "    # tracked from here on",
+            "    SHIPPED,",
+            "    RETURNED, # Back in stock",
+            "}",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_format_enum_value_annotations() {
+        let input = r#"
+namespace shop;
+enum Status {
+  UNKNOWN @value(number = "0"),
+  SHIPPED @value(number = "5"), # Handed over
+  RETURNED }
+"#;
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+
+        assert_eq!(SchemataFormatter::format(&document), [
+            "namespace shop;",
+            "",
+            "enum Status {",
+            "    UNKNOWN @value(number = \"0\"),",
+            "    SHIPPED @value(number = \"5\"), # Handed over",
+            "    RETURNED,",
+            "}",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_format_aliases() {
+        let input = r#"
+namespace shop;
+enum Status { OPEN }
+# A stock keeping unit
+type   Sku=string   @maxLength(value="9") ;
+type Size = union< int ,Sku[] >;
+"#;
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let formatted = SchemataFormatter::format(&document);
+
+        assert_eq!(formatted, [
+            "namespace shop;",
+            "",
+            "enum Status {",
+            "    OPEN,",
+            "}",
+            "",
+            "# A stock keeping unit",
+            "type Sku = string @maxLength(value = \"9\");",
+            "",
+            "type Size = union<int, Sku[]>;",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_format_is_idempotent() {
+        let document = SchemataParser::parse(include_str!("../../../schemata-example.schema")).unwrap();
+        let formatted = SchemataFormatter::format(&document);
+        let reparsed = SchemataParser::parse(&formatted).expect("Formatted output should parse");
+
+        assert_eq!(SchemataFormatter::format(&reparsed), formatted);
+        assert_eq!(reparsed.namespaces.len(), document.namespaces.len());
+        let fields = |d: &crate::schemata::ast::Document| d.namespaces[0].schemas[0].fields.iter()
+            .map(|f| (f.name.clone(), f.comment.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(fields(&reparsed), fields(&document));
+    }
+}
@@ -1,8 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::schemata::SchemataGenerator;
-    use super::*;
-    use crate::xsd::types::{XsdAttribute, XsdComplexType, XsdElement, XsdRestriction, XsdSchema, XsdSimpleType};
+    use crate::schemata::{Inheritance, SchemataGenerator};
+    use crate::xsd::types::{XsdComplexType, XsdCompositor, XsdElement, XsdRestriction, XsdSchema, XsdSimpleType};
 
     #[test]
     fn test_new_generator() {
@@ -15,16 +14,73 @@ mod tests {
         let generator = SchemataGenerator::new().unwrap();
         let xsd_schema = XsdSchema {
             target_namespace: Some("http://example.com".to_string()),
+            comment: None,
             complex_types: vec![],
             simple_types: vec![],
             elements: vec![],
+            groups: vec![],
+            attribute_groups: vec![],
             imported_schemas: vec![],
+            included_schemas: vec![],
             namespaces: Default::default(),
         };
 
-        let namespaces = generator.get_namespaces(&xsd_schema);
+        let namespaces = generator.get_namespaces(&[xsd_schema]);
         assert_eq!(namespaces.len(), 1);
-        assert_eq!(namespaces[0].name, "http://example.com");
+        assert_eq!(namespaces[0].name, "com.example");
+    }
+
+    #[test]
+    fn test_namespace_names() {
+        let cases = [
+            (Some("http://example.com/orders"), "com.example.orders"),
+            (Some("https://www.example.com:8080/schemas/v1.2/orders.xsd#top"), "com.example.schemas.v1._2.orders.xsd"),
+            (Some("urn:example:orders"), "example.orders"),
+            (Some("URN:oasis:names:tc:ubl:2-1"), "oasis.names.tc.ubl._2_1"),
+            (Some("com.example.people"), "com.example.people"),
+            (Some("http://"), "default"),
+            (None, "default"),
+        ];
+        for (target_namespace, expected) in cases {
+            assert_eq!(SchemataGenerator::namespace_name(target_namespace), expected, "{:?}", target_namespace);
+        }
+    }
+
+    #[test]
+    fn test_get_namespaces_groups_by_target_namespace() {
+        let generator = SchemataGenerator::new().unwrap();
+        let schema_with = |namespace: &str, type_name: &str| XsdSchema {
+            target_namespace: Some(namespace.to_string()),
+            complex_types: vec![
+                XsdComplexType {
+                    name: Some(type_name.to_string()),
+                    content: None,
+                    attributes: vec![],
+                    attribute_groups: vec![],
+                    any_attribute: None,
+                    mixed: false,
+                    derivation: None,
+                    comment: None,
+                    appinfo: vec![],
+                },
+            ],
+            ..Default::default()
+        };
+        let xsd_schemas = vec![
+            schema_with("com.example.people", "User"),
+            schema_with("com.example.ecommerce", "Order"),
+            schema_with("com.example.people", "Profile"),
+        ];
+
+        let namespaces = generator.get_namespaces(&xsd_schemas);
+        assert_eq!(namespaces.len(), 2);
+        assert_eq!(namespaces[0].name, "com.example.people");
+        assert_eq!(namespaces[0].schemas.len(), 2);
+        assert_eq!(namespaces[1].name, "com.example.ecommerce");
+
+        let generated_code = generator.generate_all(&xsd_schemas).unwrap();
+        assert!(generated_code.contains("namespace com.example.people;"));
+        assert!(generated_code.contains("namespace com.example.ecommerce;"));
     }
 
     #[test]
@@ -33,13 +89,18 @@ mod tests {
         let complex_types = vec![
             XsdComplexType {
                 name: Some("TestType".to_string()),
-                sequence: vec![],
+                content: None,
                 attributes: vec![],
+                attribute_groups: vec![],
+                any_attribute: None,
                 mixed: false,
+                derivation: None,
+                comment: None,
+                appinfo: vec![],
             },
         ];
 
-        let schemas = generator.get_schemas(&complex_types);
+        let schemas = generator.get_schemas(&complex_types, &complex_types, &[], None);
         assert_eq!(schemas.len(), 1);
         assert_eq!(schemas[0].name, "TestType");
     }
@@ -51,15 +112,21 @@ mod tests {
             XsdElement {
                 name: "testField".to_string(),
                 type_name: Some("string".to_string()),
+                reference: None,
                 min_occurs: Some("0".to_string()),
                 max_occurs: Some("1".to_string()),
                 complex_type: None,
                 simple_type: None,
+                substitution_group: None,
+                is_abstract: false,
+                nillable: false,
                 comment: None,
+                appinfo: vec![],
+                identity_constraints: vec![],
             },
         ];
 
-        let fields = generator.get_fields(&elements);
+        let fields = generator.get_fields(&elements, &[], &[], None);
         assert_eq!(fields.len(), 1);
         assert_eq!(fields[0].name, "testField");
         assert_eq!(fields[0].type_name, "string");
@@ -75,6 +142,7 @@ mod tests {
                 restriction: Some(XsdRestriction {
                     base: "string".to_string(),
                     enumeration: vec!["Value1".to_string(), "Value2".to_string()],
+                    enumeration_comments: Default::default(),
                     pattern: None,
                     min_inclusive: None,
                     max_inclusive: None,
@@ -88,6 +156,8 @@ mod tests {
                 }),
                 list: None,
                 union: None,
+                comment: None,
+                appinfo: vec![],
             },
         ];
 
@@ -98,20 +168,857 @@ mod tests {
     }
 
     #[test]
-    fn test_get_annotations() {
+    fn test_occurrence_bounds_are_not_annotations() {
         let generator = SchemataGenerator::new().unwrap();
         let element = XsdElement {
             name: "testField".to_string(),
             type_name: Some("string".to_string()),
+            reference: None,
             min_occurs: Some("0".to_string()),
             max_occurs: Some("unbounded".to_string()),
             complex_type: None,
             simple_type: None,
+            substitution_group: None,
+            is_abstract: false,
+            nillable: false,
             comment: None,
+            appinfo: vec![],
+            identity_constraints: vec![],
+        };
+
+        // Bounds are written as nullability, array types and `@size`
+        let fields = generator.get_fields(&[element], &[], &[], None);
+        assert_eq!(fields[0].type_name, "string[]");
+        assert!(!fields[0].nullable);
+        assert_eq!(fields[0].annotations, "");
+    }
+
+    #[test]
+    fn test_facets_become_annotations() {
+        let generator = SchemataGenerator::new().unwrap();
+        let simple_types = vec![
+            XsdSimpleType {
+                name: Some("Percentage".to_string()),
+                restriction: Some(XsdRestriction {
+                    base: "float".to_string(),
+                    enumeration: Vec::new(),
+                    enumeration_comments: Default::default(),
+                    pattern: None,
+                    min_inclusive: Some("0".to_string()),
+                    max_inclusive: Some("100".to_string()),
+                    min_exclusive: None,
+                    max_exclusive: None,
+                    length: None,
+                    min_length: None,
+                    max_length: None,
+                    total_digits: Some(5),
+                    fraction_digits: None,
+                }),
+                list: None,
+                union: None,
+                comment: None,
+                appinfo: vec![],
+            },
+        ];
+        let elements = vec![
+            XsdElement {
+                name: "discount".to_string(),
+                type_name: Some("tns:Percentage".to_string()),
+                reference: None,
+                min_occurs: Some("0".to_string()),
+                max_occurs: None,
+                complex_type: None,
+                simple_type: None,
+                substitution_group: None,
+                is_abstract: false,
+                nillable: false,
+                comment: None,
+                appinfo: vec![],
+                identity_constraints: vec![],
+            },
+        ];
+
+        // The facets of a named type belong to its alias, which the field refers to
+        let fields = generator.get_fields(&elements, &[], &simple_types, None);
+        assert_eq!(fields[0].type_name, "Percentage");
+        assert!(fields[0].nullable);
+        assert_eq!(fields[0].annotations, "");
+        assert!(generator.get_enums(&simple_types).is_empty());
+        let aliases = generator.get_aliases(&simple_types, &simple_types, None);
+        assert_eq!(aliases[0].name, "Percentage");
+        assert_eq!(aliases[0].type_name, "float");
+        assert_eq!(aliases[0].annotations, "@totalDigits(5) @minInclusive(0) @maxInclusive(100)");
+    }
+
+    #[test]
+    fn test_compositors_are_flattened() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+            <xs:complexType name="Payment">
+                <xs:sequence>
+                    <xs:element name="amount" type="float"/>
+                    <xs:choice>
+                        <xs:element name="card" type="string"/>
+                        <xs:element name="iban" type="string"/>
+                    </xs:choice>
+                    <xs:sequence minOccurs="0" maxOccurs="3">
+                        <xs:element name="note" type="string" maxOccurs="2"/>
+                    </xs:sequence>
+                    <xs:choice maxOccurs="unbounded">
+                        <xs:element name="tag" type="string"/>
+                        <xs:element name="label" type="string"/>
+                    </xs:choice>
+                </xs:sequence>
+            </xs:complexType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let generator = SchemataGenerator::new().unwrap();
+        let fields = generator.get_content_fields(&schema.complex_types[0], &[], &[], None);
+
+        let summary: Vec<(&str, &str, bool, &str)> = fields.iter()
+            .map(|f| (f.name.as_str(), f.type_name.as_str(), f.nullable, f.annotations.as_str()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("amount", "float", false, ""),
+            ("card", "string", true, "@oneof(name = \"choice\")"),
+            ("iban", "string", true, "@oneof(name = \"choice\")"),
+            ("note", "string[]", false, "@size(max = 6)"),
+            ("tag", "string[]", false, ""),
+            ("label", "string[]", false, ""),
+        ]);
+    }
+
+    #[test]
+    fn test_repeated_elements_become_arrays() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:simpleType name="Skus">
+                <xs:list itemType="xs:string"/>
+            </xs:simpleType>
+            <xs:complexType name="Order">
+                <xs:sequence>
+                    <xs:element name="line" type="xs:string" minOccurs="0" maxOccurs="unbounded"/>
+                    <xs:element name="phone" type="xs:string" minOccurs="2" maxOccurs="3"/>
+                    <xs:element name="coupon" type="xs:string" minOccurs="1" maxOccurs="unbounded"/>
+                    <xs:element name="note" type="xs:string" minOccurs="0" maxOccurs="1"/>
+                    <xs:element name="skus" type="tns:Skus" maxOccurs="4"/>
+                    <xs:sequence maxOccurs="2">
+                        <xs:element name="gift" type="xs:string"/>
+                    </xs:sequence>
+                </xs:sequence>
+            </xs:complexType>
+        </xs:schema>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let generator = SchemataGenerator::new().unwrap();
+        let fields = generator.get_content_fields(&schema.complex_types[0], &[], &schema.simple_types, None);
+
+        let summary: Vec<String> = fields.iter()
+            .map(|f| format!("{} {}{} {}", f.name, f.type_name, if f.nullable { "?" } else { "" }, f.annotations))
+            .collect();
+        assert_eq!(summary, vec![
+            "line string[] ",
+            "phone string[] @size(min = 2, max = 3)",
+            "coupon string[] @size(min = 1)",
+            "note string? ",
+            // A list is an array already
+            "skus Skus @size(min = 1, max = 4)",
+            "gift string[] @size(min = 1, max = 2)",
+        ]);
+    }
+
+    #[test]
+    fn test_derived_types() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="hr" targetNamespace="hr">
+            <xs:complexType name="Person">
+                <xs:sequence>
+                    <xs:element name="name" type="string"/>
+                </xs:sequence>
+            </xs:complexType>
+            <xs:complexType name="Employee">
+                <xs:complexContent>
+                    <xs:extension base="tns:Person">
+                        <xs:sequence>
+                            <xs:element name="salary" type="float"/>
+                        </xs:sequence>
+                    </xs:extension>
+                </xs:complexContent>
+            </xs:complexType>
+            <xs:complexType name="Manager">
+                <xs:complexContent>
+                    <xs:extension base="tns:Employee">
+                        <xs:sequence>
+                            <xs:element name="reports" type="int"/>
+                        </xs:sequence>
+                    </xs:extension>
+                </xs:complexContent>
+            </xs:complexType>
+            <xs:complexType name="Anonymous">
+                <xs:complexContent>
+                    <xs:restriction base="tns:Person">
+                        <xs:sequence>
+                            <xs:element name="name" type="string" minOccurs="0"/>
+                        </xs:sequence>
+                    </xs:restriction>
+                </xs:complexContent>
+            </xs:complexType>
+            <xs:complexType name="Salary">
+                <xs:simpleContent>
+                    <xs:extension base="float"/>
+                </xs:simpleContent>
+            </xs:complexType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let summary = |inheritance| {
+            let generator = SchemataGenerator::new().unwrap().with_inheritance(inheritance);
+            generator.get_namespaces(std::slice::from_ref(&schema)).remove(0).schemas.into_iter()
+                .map(|s| {
+                    let fields: Vec<String> = s.fields.iter().map(|f| format!("{} {}", f.name, f.type_name)).collect();
+                    (s.name, s.annotations, fields.join(", "))
+                })
+                .collect::<Vec<_>>()
         };
 
-        let annotations = generator.get_annotations(&element);
-        assert_eq!(annotations, "@minOccurs(0) @maxOccurs(unbounded)");
+        let row = |name: &str, annotations: &str, fields: &str| (name.to_string(), annotations.to_string(), fields.to_string());
+        assert_eq!(summary(Inheritance::Flatten), vec![
+            row("Person", "", "name string"),
+            row("Employee", "", "name string, salary float"),
+            row("Manager", "", "name string, salary float, reports int"),
+            row("Anonymous", "", "name string"),
+            row("Salary", "", "value float"),
+        ]);
+        assert_eq!(summary(Inheritance::Extends), vec![
+            row("Person", "", "name string"),
+            row("Employee", "@extends(Person)", "salary float"),
+            row("Manager", "@extends(Employee)", "reports int"),
+            row("Anonymous", "", "name string"),
+            row("Salary", "", "value float"),
+        ]);
+    }
+
+    #[test]
+    fn test_documentation_becomes_comments() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+            <xs:annotation><xs:documentation>Orders of the shop.</xs:documentation></xs:annotation>
+            <xs:complexType name="Order">
+                <xs:annotation>
+                    <xs:documentation>An order.</xs:documentation>
+                    <xs:appinfo source="urn:table">orders</xs:appinfo>
+                </xs:annotation>
+                <xs:sequence>
+                    <xs:element name="id" type="string">
+                        <xs:annotation><xs:documentation>Primary key</xs:documentation></xs:annotation>
+                    </xs:element>
+                    <xs:element name="note" type="string">
+                        <xs:annotation>
+                            <xs:documentation>A note the customer attaches to the order when submitting it, shown to the warehouse staff when packing.</xs:documentation>
+                        </xs:annotation>
+                    </xs:element>
+                </xs:sequence>
+            </xs:complexType>
+            <xs:simpleType name="Status">
+                <xs:annotation><xs:appinfo>legacy</xs:appinfo></xs:annotation>
+                <xs:restriction base="string">
+                    <xs:enumeration value="OPEN">
+                        <xs:annotation><xs:documentation>Not paid</xs:documentation></xs:annotation>
+                    </xs:enumeration>
+                    <xs:enumeration value="PAID"/>
+                </xs:restriction>
+            </xs:simpleType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let generated = SchemataGenerator::new().unwrap().generate(schema).unwrap();
+        let lines: Vec<&str> = generated.lines().map(str::trim_end).filter(|line| !line.trim().is_empty()).collect();
+
+        assert_eq!(lines, vec![
+            "# Orders of the shop.",
+            "namespace shop;",
+            "# An order.",
+            "schema Order @appinfo(source = \"urn:table\", value = \"orders\") {",
+            "    id string # Primary key",
+            "    # A note the customer attaches to the order when submitting it, shown to the warehouse staff when",
+            "    # packing.",
+            "    note string",
+            "}",
+            "enum Status @appinfo(\"legacy\") {",
+            "    # Not paid",
+            "    OPEN,",
+            "    PAID,",
+            "}",
+        ]);
+    }
+
+    #[test]
+    fn test_attributes_become_fields() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+            <xs:complexType name="Item">
+                <xs:sequence>
+                    <xs:element name="id" type="string"/>
+                </xs:sequence>
+                <xs:attribute name="id" type="int" use="required"/>
+                <xs:attribute name="rate" type="float" default="1.0"/>
+                <xs:attribute name="version" type="string" fixed="2"/>
+                <xs:attribute name="code">
+                    <xs:simpleType>
+                        <xs:restriction base="string">
+                            <xs:maxLength value="8"/>
+                        </xs:restriction>
+                    </xs:simpleType>
+                </xs:attribute>
+                <xs:attribute name="legacy" type="string" use="prohibited"/>
+            </xs:complexType>
+            <xs:complexType name="SmallItem">
+                <xs:complexContent>
+                    <xs:restriction base="Item">
+                        <xs:sequence>
+                            <xs:element name="id" type="string"/>
+                        </xs:sequence>
+                        <xs:attribute name="code" use="prohibited"/>
+                    </xs:restriction>
+                </xs:complexContent>
+            </xs:complexType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let generator = SchemataGenerator::new().unwrap();
+        let schemas = generator.get_schemas(&schema.complex_types, &schema.complex_types, &[], None);
+        let fields = |index: usize| -> Vec<String> {
+            schemas[index].fields.iter()
+                .map(|f| format!("{} {}{} {}", f.name, f.type_name, if f.nullable { "?" } else { "" }, f.annotations))
+                .collect()
+        };
+
+        assert_eq!(fields(0), vec![
+            "id string ",
+            "idAttribute int @xmlAttribute(name = \"id\")",
+            "rate float? @xmlAttribute @default(value = \"1.0\")",
+            "version string? @xmlAttribute @const(value = \"2\")",
+            "code string? @xmlAttribute @maxLength(8)",
+        ]);
+        // A restriction keeps the base's attributes unless it prohibits them
+        assert_eq!(fields(1), vec![
+            "id string ",
+            "idAttribute int @xmlAttribute(name = \"id\")",
+            "rate float? @xmlAttribute @default(value = \"1.0\")",
+            "version string? @xmlAttribute @const(value = \"2\")",
+        ]);
+    }
+
+    #[test]
+    fn test_type_names_are_resolved() {
+        use crate::xsd::XsdParser;
+
+        let schema = XsdParser::parse(r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:tns="urn:shop" targetNamespace="urn:shop">
+            <xsd:complexType name="Address"/>
+            <xsd:simpleType name="Code">
+                <xsd:restriction base="xsd:token"><xsd:maxLength value="4"/></xsd:restriction>
+            </xsd:simpleType>
+            <xsd:simpleType name="Amount">
+                <xsd:restriction base="xsd:decimal"><xsd:totalDigits value="10"/><xsd:fractionDigits value="2"/></xsd:restriction>
+            </xsd:simpleType>
+            <xsd:complexType name="Order">
+                <xsd:sequence>
+                    <xsd:element name="id" type="xsd:long"/>
+                    <xsd:element name="placed" type="xsd:dateTime"/>
+                    <xsd:element name="total" type="xsd:decimal"/>
+                    <xsd:element name="net" type="tns:Amount"/>
+                    <xsd:element name="paid" type="xsd:boolean"/>
+                    <xsd:element name="receipt" type="xsd:base64Binary"/>
+                    <xsd:element name="code" type="tns:Code"/>
+                    <xsd:element name="address" type="tns:Address"/>
+                </xsd:sequence>
+                <xsd:attribute name="href" type="xsd:anyURI"/>
+            </xsd:complexType>
+        </xsd:schema>"#.as_bytes()).expect("Failed to parse XSD");
+
+        let namespaces = SchemataGenerator::new().unwrap().get_namespaces(&[schema]);
+        let order = &namespaces[0].schemas[1];
+        let types: Vec<(&str, &str)> = order.fields.iter().map(|f| (f.name.as_str(), f.type_name.as_str())).collect();
+        assert_eq!(types, vec![
+            ("id", "long"),
+            ("placed", "datetime"),
+            ("total", "decimal"),
+            ("net", "Amount"),
+            ("paid", "bool"),
+            ("receipt", "bytes"),
+            ("code", "Code"),
+            ("address", "Address"),
+            ("href", "uri"),
+        ]);
+        let aliases: Vec<(&str, &str)> = namespaces[0].aliases.iter().map(|a| (a.name.as_str(), a.type_name.as_str())).collect();
+        assert_eq!(aliases, vec![("Code", "string"), ("Amount", "decimal(10, 2)")]);
+    }
+
+    #[test]
+    fn test_types_of_other_namespaces_are_qualified() {
+        use crate::schemata::{SchemataParser, SemanticAnalyzer};
+        use crate::xsd::XsdParser;
+
+        let a = XsdParser::parse(r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:a="com.a" xmlns:b="com.b" targetNamespace="com.a">
+            <xs:import namespace="com.b" schemaLocation="b.xsd"/>
+            <xs:complexType name="Address">
+                <xs:sequence><xs:element name="street" type="xs:string"/></xs:sequence>
+            </xs:complexType>
+            <xs:complexType name="Home">
+                <xs:complexContent>
+                    <xs:extension base="b:Address">
+                        <xs:sequence><xs:element name="rooms" type="xs:int"/></xs:sequence>
+                    </xs:extension>
+                </xs:complexContent>
+            </xs:complexType>
+            <xs:complexType name="Holder">
+                <xs:sequence>
+                    <xs:element name="addr" type="b:Address"/>
+                    <xs:element name="local" type="a:Address"/>
+                </xs:sequence>
+            </xs:complexType>
+        </xs:schema>"#.as_bytes()).expect("Failed to parse XSD");
+        let b = XsdParser::parse(r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="com.b">
+            <xs:complexType name="Address">
+                <xs:sequence><xs:element name="city" type="xs:string"/></xs:sequence>
+            </xs:complexType>
+        </xs:schema>"#.as_bytes()).expect("Failed to parse XSD");
+        let xsd_schemas = [a, b];
+
+        let summary = |inheritance| {
+            let generator = SchemataGenerator::new().unwrap().with_inheritance(inheritance);
+            let namespaces = generator.get_namespaces(&xsd_schemas);
+            let schemas: Vec<(String, String, String, String)> = namespaces.iter()
+                .flat_map(|namespace| namespace.schemas.iter().map(move |s| {
+                    let fields: Vec<String> = s.fields.iter().map(|f| format!("{} {}", f.name, f.type_name)).collect();
+                    (namespace.name.clone(), s.name.clone(), s.annotations.clone(), fields.join(", "))
+                }))
+                .collect();
+
+            let generated_code = generator.render(&namespaces).unwrap();
+            let document = SchemataParser::parse(&generated_code).expect("Generated code should parse");
+            SemanticAnalyzer::analyze(&document).expect("Generated code should be valid");
+            schemas
+        };
+        let row = |namespace: &str, name: &str, annotations: &str, fields: &str| {
+            (namespace.to_string(), name.to_string(), annotations.to_string(), fields.to_string())
+        };
+
+        assert_eq!(summary(Inheritance::Flatten), vec![
+            row("com.a", "Address", "", "street string"),
+            row("com.a", "Home", "", "city string, rooms int"),
+            row("com.a", "Holder", "", "addr com.b.Address, local Address"),
+            row("com.b", "Address", "", "city string"),
+        ]);
+        assert_eq!(summary(Inheritance::Extends), vec![
+            row("com.a", "Address", "", "street string"),
+            row("com.a", "Home", "@extends(com.b.Address)", "rooms int"),
+            row("com.a", "Holder", "", "addr com.b.Address, local Address"),
+            row("com.b", "Address", "", "city string"),
+        ]);
+    }
+
+    #[test]
+    fn test_simple_types_become_aliases() {
+        use crate::xsd::XsdParser;
+
+        let schema = XsdParser::parse(r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:simpleType name="Sku">
+                <xs:restriction base="xs:string"><xs:pattern value="[A-Z]{3}-[0-9]{4}"/></xs:restriction>
+            </xs:simpleType>
+            <xs:simpleType name="Skus">
+                <xs:list itemType="tns:Sku"/>
+            </xs:simpleType>
+            <xs:simpleType name="Size">
+                <xs:union memberTypes="xs:int xs:string"/>
+            </xs:simpleType>
+            <xs:complexType name="Item">
+                <xs:sequence>
+                    <xs:element name="sku" type="tns:Sku"/>
+                    <xs:element name="related" type="tns:Skus" minOccurs="0"/>
+                    <xs:element name="size" type="tns:Size"/>
+                    <xs:element name="tags">
+                        <xs:simpleType><xs:list itemType="xs:string"/></xs:simpleType>
+                    </xs:element>
+                </xs:sequence>
+            </xs:complexType>
+        </xs:schema>"#.as_bytes()).expect("Failed to parse XSD");
+
+        let namespaces = SchemataGenerator::new().unwrap().get_namespaces(&[schema]);
+        let aliases: Vec<(&str, &str, &str)> = namespaces[0].aliases.iter()
+            .map(|a| (a.name.as_str(), a.type_name.as_str(), a.annotations.as_str()))
+            .collect();
+        assert_eq!(aliases, vec![
+            ("Sku", "string", "@pattern(\"[A-Z]{3}-[0-9]{4}\")"),
+            ("Skus", "Sku[]", ""),
+            ("Size", "union<int, string>", ""),
+        ]);
+        let types: Vec<(&str, &str)> = namespaces[0].schemas[0].fields.iter().map(|f| (f.name.as_str(), f.type_name.as_str())).collect();
+        assert_eq!(types, vec![("sku", "Sku"), ("related", "Skus"), ("size", "Size"), ("tags", "string[]")]);
+    }
+
+    #[test]
+    fn test_inline_schemas_are_named_after_their_path() {
+        use crate::schemata::InlineSchemas;
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+            <xs:complexType name="Order">
+                <xs:sequence>
+                    <xs:element name="items" maxOccurs="unbounded">
+                        <xs:complexType>
+                            <xs:sequence>
+                                <xs:element name="sku" type="xs:string"/>
+                                <xs:element name="gift-options">
+                                    <xs:complexType>
+                                        <xs:sequence>
+                                            <xs:element name="wrap">
+                                                <xs:complexType>
+                                                    <xs:sequence><xs:element name="color" type="xs:string"/></xs:sequence>
+                                                </xs:complexType>
+                                            </xs:element>
+                                        </xs:sequence>
+                                    </xs:complexType>
+                                </xs:element>
+                            </xs:sequence>
+                        </xs:complexType>
+                    </xs:element>
+                </xs:sequence>
+            </xs:complexType>
+            <xs:complexType name="OrderItems"/>
+        </xs:schema>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+
+        let nested = SchemataGenerator::new().unwrap().get_namespaces(std::slice::from_ref(&schema));
+        let items = &nested[0].schemas[0].fields[0];
+        let options = &items.inline_schema.as_ref().unwrap().fields[1];
+        let wrap = &options.inline_schema.as_ref().unwrap().fields[0];
+        // `OrderItems` is declared, so the inline schema is numbered
+        assert_eq!(items.type_name, "OrderItems2[]");
+        assert_eq!(options.type_name, "OrderItems2GiftOptions");
+        assert_eq!(wrap.type_name, "OrderItems2GiftOptionsWrap");
+        assert_eq!(wrap.inline_schema.as_ref().unwrap().name, "OrderItems2GiftOptionsWrap");
+
+        let generated = SchemataGenerator::new().unwrap().generate(schema.clone()).unwrap();
+        assert!(generated.contains("\n            wrap OrderItems2GiftOptionsWrap\n            schema {\n                color string\n"));
+
+        let hoisted = SchemataGenerator::new().unwrap()
+            .with_inline_schemas(InlineSchemas::Hoist)
+            .get_namespaces(&[schema]);
+        let schemas: Vec<&str> = hoisted[0].schemas.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(schemas, vec!["Order", "OrderItems2", "OrderItems2GiftOptions", "OrderItems2GiftOptionsWrap", "OrderItems"]);
+        assert!(hoisted[0].schemas.iter().flat_map(|s| &s.fields).all(|f| f.inline_schema.is_none()));
+        assert_eq!(hoisted[0].schemas[0].fields[0].type_name, "OrderItems2[]");
+    }
+
+    #[test]
+    fn test_inline_enumerations_become_enums() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+            <xs:complexType name="Order">
+                <xs:sequence>
+                    <xs:element name="items" maxOccurs="unbounded">
+                        <xs:complexType>
+                            <xs:sequence>
+                                <xs:element name="kind">
+                                    <xs:simpleType>
+                                        <xs:restriction base="xs:string">
+                                            <xs:enumeration value="A"/>
+                                            <xs:enumeration value="B"/>
+                                        </xs:restriction>
+                                    </xs:simpleType>
+                                </xs:element>
+                            </xs:sequence>
+                        </xs:complexType>
+                    </xs:element>
+                </xs:sequence>
+                <xs:attribute name="status">
+                    <xs:simpleType>
+                        <xs:restriction base="xs:string">
+                            <xs:enumeration value="OPEN"/>
+                        </xs:restriction>
+                    </xs:simpleType>
+                </xs:attribute>
+            </xs:complexType>
+        </xs:schema>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+
+        let namespaces = SchemataGenerator::new().unwrap().get_namespaces(std::slice::from_ref(&schema));
+        let enums: Vec<(&str, &[String])> = namespaces[0].enums.iter().map(|e| (e.name.as_str(), e.values.as_slice())).collect();
+        assert_eq!(enums, vec![
+            ("OrderItemsKind", &["A".to_string(), "B".to_string()][..]),
+            ("OrderStatus", &["OPEN".to_string()][..]),
+        ]);
+        let order = &namespaces[0].schemas[0];
+        let kind = &order.fields[0].inline_schema.as_ref().unwrap().fields[0];
+        assert_eq!(kind.type_name, "OrderItemsKind");
+        assert_eq!(order.fields[1].type_name, "OrderStatus");
+
+        let generated = SchemataGenerator::new().unwrap().generate(schema).unwrap();
+        assert!(generated.contains("enum OrderItemsKind {"));
+        assert!(generated.contains("kind OrderItemsKind\n"));
+    }
+
+    #[test]
+    fn test_groups() {
+        use crate::schemata::Groups;
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:group name="Contact">
+                <xs:sequence>
+                    <xs:element name="email" type="xs:string"/>
+                    <xs:element name="phone" type="xs:string" minOccurs="0"/>
+                </xs:sequence>
+            </xs:group>
+            <xs:group name="Customer">
+                <xs:sequence>
+                    <xs:element name="loyalty" type="xs:int"/>
+                </xs:sequence>
+            </xs:group>
+            <xs:attributeGroup name="Audit">
+                <xs:attribute name="created" type="xs:dateTime" use="required"/>
+                <xs:attributeGroup ref="tns:Source"/>
+            </xs:attributeGroup>
+            <xs:attributeGroup name="Source">
+                <xs:attribute name="source" type="xs:string"/>
+            </xs:attributeGroup>
+            <xs:complexType name="Customer">
+                <xs:sequence>
+                    <xs:element name="name" type="xs:string"/>
+                    <xs:group ref="tns:Contact"/>
+                    <xs:group ref="tns:Customer" minOccurs="0"/>
+                    <xs:element name="address">
+                        <xs:complexType>
+                            <xs:sequence><xs:group ref="tns:Contact"/></xs:sequence>
+                        </xs:complexType>
+                    </xs:element>
+                </xs:sequence>
+                <xs:attributeGroup ref="tns:Audit"/>
+            </xs:complexType>
+        </xs:schema>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let summary = |schema: &crate::schemata::types::SchemataSchema| format!("{} [{}]",
+            [schema.name.as_str(), schema.annotations.as_str()].join(" ").trim_end(),
+            schema.fields.iter().map(|f| format!("{} {}{}", f.name, f.type_name, if f.nullable { "?" } else { "" })).collect::<Vec<_>>().join(", "));
+
+        // References are replaced by the content of the group
+        let expanded = SchemataGenerator::new().unwrap().get_namespaces(std::slice::from_ref(&schema));
+        let schemas: Vec<String> = expanded[0].schemas.iter().map(summary).collect();
+        assert_eq!(schemas, vec![
+            "Customer [name string, email string, phone string?, loyalty int?, address CustomerAddress, created datetime, source string?]",
+        ]);
+
+        // Only references made once from named types and groups become includes
+        let mixins = SchemataGenerator::new().unwrap().with_groups(Groups::Mixins).get_namespaces(&[schema]);
+        let schemas: Vec<String> = mixins[0].schemas.iter().map(summary).collect();
+        assert_eq!(schemas, vec![
+            "Customer @include(Contact) @include(Audit) [name string, loyalty int?, address CustomerAddress]",
+            "Contact @mixin [email string, phone string?]",
+            "CustomerGroup @mixin [loyalty int]",
+            "Audit @mixin @include(Source) [created datetime]",
+            "Source @mixin [source string?]",
+        ]);
+        let address = mixins[0].schemas[0].fields[2].inline_schema.as_ref().unwrap();
+        assert_eq!(address.fields.len(), 2);
+    }
+
+    #[test]
+    fn test_substitution_groups_and_wildcards() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:complexType name="Item">
+                <xs:sequence><xs:element name="title" type="xs:string"/></xs:sequence>
+                <xs:attributeGroup ref="tns:Open"/>
+            </xs:complexType>
+            <xs:complexType name="Book">
+                <xs:complexContent>
+                    <xs:extension base="tns:Item">
+                        <xs:sequence><xs:element name="isbn" type="xs:string"/></xs:sequence>
+                    </xs:extension>
+                </xs:complexContent>
+            </xs:complexType>
+            <xs:attributeGroup name="Open">
+                <xs:anyAttribute/>
+            </xs:attributeGroup>
+            <xs:element name="item" type="tns:Item" abstract="true"/>
+            <xs:element name="book" type="tns:Book" substitutionGroup="tns:item"/>
+            <xs:element name="novel" substitutionGroup="tns:book"/>
+            <xs:element name="dvd" substitutionGroup="tns:item">
+                <xs:complexType>
+                    <xs:sequence><xs:element name="minutes" type="xs:int"/></xs:sequence>
+                </xs:complexType>
+            </xs:element>
+            <xs:element name="order">
+                <xs:complexType>
+                    <xs:sequence>
+                        <xs:element ref="tns:item" maxOccurs="unbounded"/>
+                        <xs:element ref="tns:book" minOccurs="0"/>
+                        <xs:element ref="tns:dvd"/>
+                        <xs:element name="note" type="xs:string" nillable="true"/>
+                        <xs:element name="extra">
+                            <xs:complexType>
+                                <xs:sequence><xs:any maxOccurs="unbounded"/></xs:sequence>
+                            </xs:complexType>
+                        </xs:element>
+                    </xs:sequence>
+                </xs:complexType>
+            </xs:element>
+        </xs:schema>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let summary = |schema: &crate::schemata::types::SchemataSchema| format!("{} [{}]",
+            [schema.name.as_str(), schema.annotations.as_str()].join(" ").trim_end(),
+            schema.fields.iter().map(|f| format!("{} {}{}", f.name, f.type_name, if f.nullable { "?" } else { "" })).collect::<Vec<_>>().join(", "));
+
+        let namespaces = SchemataGenerator::new().unwrap().get_namespaces(&[schema]);
+        let schemas: Vec<String> = namespaces[0].schemas.iter().map(summary).collect();
+        // The abstract element has no root schema, and members without a type take the head's
+        assert_eq!(schemas, vec![
+            "Item @extensible [title string]",
+            r#"Book @root(name = "book") @extensible [title string, isbn string]"#,
+            r#"Dvd @root(name = "dvd") [minutes int]"#,
+            r#"Order @root(name = "order") [item union<Book, Dvd>[], book Book?, dvd Dvd, note string?, extra OrderExtra]"#,
+        ]);
+        let aliases: Vec<(&str, &str, &str)> = namespaces[0].aliases.iter().map(|a| (a.name.as_str(), a.type_name.as_str(), a.annotations.as_str())).collect();
+        assert_eq!(aliases, vec![("Novel", "Book", r#"@root(name = "novel")"#)]);
+        let extra = namespaces[0].schemas[3].fields[4].inline_schema.as_ref().unwrap();
+        assert_eq!(extra.annotations, "@extensible");
+    }
+
+    #[test]
+    fn test_identity_constraints_become_annotations() {
+        use crate::schemata::InlineSchemas;
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:complexType name="Product">
+                <xs:sequence>
+                    <xs:element name="name" type="xs:string"/>
+                    <xs:element name="barcode" type="xs:string" minOccurs="0"/>
+                </xs:sequence>
+                <xs:attribute name="sku" type="xs:string"/>
+            </xs:complexType>
+            <xs:element name="catalog">
+                <xs:complexType>
+                    <xs:sequence>
+                        <xs:element name="product" type="tns:Product" maxOccurs="unbounded"/>
+                        <xs:element name="offers">
+                            <xs:complexType>
+                                <xs:sequence>
+                                    <xs:element name="offer" maxOccurs="unbounded">
+                                        <xs:complexType>
+                                            <xs:sequence><xs:element name="sku" type="xs:string"/></xs:sequence>
+                                        </xs:complexType>
+                                    </xs:element>
+                                </xs:sequence>
+                            </xs:complexType>
+                        </xs:element>
+                    </xs:sequence>
+                </xs:complexType>
+                <xs:keyref name="offerProduct" refer="tns:productKey">
+                    <xs:selector xpath="tns:offers/tns:offer"/>
+                    <xs:field xpath="tns:sku"/>
+                </xs:keyref>
+                <xs:key name="productKey">
+                    <xs:selector xpath="tns:product"/>
+                    <xs:field xpath="@sku"/>
+                </xs:key>
+                <xs:unique name="productBarcode">
+                    <xs:selector xpath="./tns:product"/>
+                    <xs:field xpath="tns:barcode"/>
+                </xs:unique>
+                <xs:unique name="productName">
+                    <xs:selector xpath=".//tns:product"/>
+                    <xs:field xpath="tns:name"/>
+                </xs:unique>
+                <xs:unique name="productNameAndBarcode">
+                    <xs:selector xpath="tns:product"/>
+                    <xs:field xpath="tns:name"/>
+                    <xs:field xpath="tns:barcode"/>
+                </xs:unique>
+            </xs:element>
+        </xs:schema>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let annotations = |schema: &crate::schemata::types::SchemataSchema| schema.fields.iter()
+            .map(|f| format!("{}{} {}", f.name, if f.nullable { "?" } else { "" }, f.annotations).trim_end().to_string())
+            .collect::<Vec<_>>();
+
+        let namespaces = SchemataGenerator::new().unwrap().get_namespaces(std::slice::from_ref(&schema));
+        // The key makes the optional attribute required; descendant selectors and composite
+        // fields are left out
+        assert_eq!(annotations(&namespaces[0].schemas[0]), vec!["name", "barcode? @unique", "sku @xmlAttribute @id"]);
+        let offers = namespaces[0].schemas[1].fields[1].inline_schema.as_ref().unwrap();
+        let offer = offers.fields[0].inline_schema.as_ref().unwrap();
+        assert_eq!(annotations(offer), vec![r#"sku @relation(schema = Product, field = "sku")"#]);
+
+        // Inline schemas are found before they are hoisted
+        let hoisted = SchemataGenerator::new().unwrap().with_inline_schemas(InlineSchemas::Hoist).get_namespaces(&[schema]);
+        let offer = hoisted[0].schemas.iter().find(|schema| schema.name == "CatalogOffersOffer").unwrap();
+        assert_eq!(annotations(offer), vec![r#"sku @relation(schema = Product, field = "sku")"#]);
+    }
+
+    #[test]
+    fn test_global_elements_become_root_schemas() {
+        use crate::xsd::XsdParser;
+
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:element name="order">
+                <xs:annotation><xs:documentation>A placed order</xs:documentation></xs:annotation>
+                <xs:complexType>
+                    <xs:sequence>
+                        <xs:element ref="tns:customer"/>
+                        <xs:element ref="tns:line" maxOccurs="unbounded"/>
+                        <xs:element ref="tns:note" minOccurs="0"/>
+                        <xs:element ref="tns:missing"/>
+                    </xs:sequence>
+                </xs:complexType>
+            </xs:element>
+            <xs:element name="customer" type="tns:Customer"/>
+            <xs:element name="line" type="tns:LineType"/>
+            <xs:element name="note" type="xs:string"/>
+            <xs:complexType name="Customer"><xs:sequence><xs:element name="name" type="xs:string"/></xs:sequence></xs:complexType>
+            <xs:complexType name="LineType"><xs:sequence><xs:element name="sku" type="xs:string"/></xs:sequence></xs:complexType>
+            <xs:complexType name="Order"><xs:sequence><xs:element name="id" type="xs:string"/></xs:sequence></xs:complexType>
+        </xs:schema>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        let summary = |inheritance: Inheritance| -> (Vec<String>, Vec<String>) {
+            let namespaces = SchemataGenerator::new().unwrap().with_inheritance(inheritance).get_namespaces(std::slice::from_ref(&schema));
+            let schemas = namespaces[0].schemas.iter().map(|s| {
+                let fields: Vec<String> = s.fields.iter()
+                    .map(|f| format!("{} {}{}", f.name, f.type_name, if f.nullable { "?" } else { "" }))
+                    .collect();
+                format!("{} {} [{}]", s.name, s.annotations, fields.join(", "))
+            }).collect();
+            let aliases = namespaces[0].aliases.iter().map(|a| format!("{} = {} {}", a.name, a.type_name, a.annotations)).collect();
+            (schemas, aliases)
+        };
+
+        let (schemas, aliases) = summary(Inheritance::Flatten);
+        assert_eq!(schemas, vec![
+            "Customer @root(name = \"customer\") [name string]",
+            "LineType  [sku string]",
+            "Order  [id string]",
+            // `Order` is taken by a type
+            "OrderElement @root(name = \"order\") [customer Customer, line LineType[], note string?, missing string]",
+            "Note @root(name = \"note\") [value string]",
+        ]);
+        // The schema of a named type is not copied for its root element
+        assert_eq!(aliases, vec!["Line = LineType @root(name = \"line\")"]);
+        assert_eq!(summary(Inheritance::Extends).1, aliases);
+
+        let namespaces = SchemataGenerator::new().unwrap().get_namespaces(&[schema]);
+        assert_eq!(namespaces[0].schemas[3].comment.as_deref(), Some("A placed order"));
     }
 
     #[test]
@@ -119,22 +1026,34 @@ mod tests {
         let generator = SchemataGenerator::new().unwrap();
         let xsd_schema = XsdSchema {
             target_namespace: Some("http://example.com".to_string()),
+            comment: None,
             complex_types: vec![
                 XsdComplexType {
                     name: Some("TestType".to_string()),
-                    sequence: vec![
+                    content: Some(XsdCompositor::sequence(vec![
                         XsdElement {
                             name: "testField".to_string(),
                             type_name: Some("string".to_string()),
+                            reference: None,
                             min_occurs: Some("0".to_string()),
                             max_occurs: Some("1".to_string()),
                             complex_type: None,
                             simple_type: None,
+                            substitution_group: None,
+                            is_abstract: false,
+                            nillable: false,
                             comment: None,
+                            appinfo: vec![],
+                            identity_constraints: vec![],
                         },
-                    ],
+                    ])),
                     attributes: vec![],
+                    attribute_groups: vec![],
+                    any_attribute: None,
                     mixed: false,
+                    derivation: None,
+                    comment: None,
+                    appinfo: vec![],
                 },
             ],
             simple_types: vec![
@@ -143,6 +1062,7 @@ mod tests {
                     restriction: Some(XsdRestriction {
                         base: "string".to_string(),
                         enumeration: vec!["Value1".to_string(), "Value2".to_string()],
+                        enumeration_comments: Default::default(),
                         pattern: None,
                         min_inclusive: None,
                         max_inclusive: None,
@@ -156,10 +1076,15 @@ mod tests {
                     }),
                     list: None,
                     union: None,
+                    comment: None,
+                    appinfo: vec![],
                 },
             ],
             elements: vec![],
+            groups: vec![],
+            attribute_groups: vec![],
             imported_schemas: vec![],
+            included_schemas: vec![],
             namespaces: Default::default(),
         };
 
@@ -170,10 +1095,7 @@ mod tests {
 
         // Check for namespace declaration
         assert!(
-            generated_code.contains("namespace http://example.com") ||
-                generated_code.contains("namespace http://example/com") ||
-                generated_code.contains("namespace http.example.com") ||
-                generated_code.contains("namespace http_example_com"),
+            generated_code.contains("namespace com.example;"),
             "Generated code does not contain expected namespace. Generated code:\n{}",
             generated_code
         );
@@ -182,9 +1104,8 @@ mod tests {
         assert!(generated_code.contains("schema TestType"));
 
         // Check for field declaration
-        assert!(generated_code.contains("testField string"));
-        assert!(generated_code.contains("@minOccurs(0)"));
-        assert!(generated_code.contains("@maxOccurs(1)"));
+        assert!(generated_code.contains("testField string?"));
+        assert!(!generated_code.contains("@minOccurs") && !generated_code.contains("@maxOccurs"));
 
         // Check for enum declaration
         assert!(generated_code.contains("enum TestEnum"));
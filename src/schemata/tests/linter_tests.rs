@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    use crate::schemata::{SchemataLinter, SchemataParser, SemanticAnalyzer};
+
+    fn lint(input: &str) -> Vec<String> {
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let symbols = SemanticAnalyzer::analyze(&document).expect("Document should be valid");
+        SchemataLinter::lint(&document, &symbols).iter().map(|d| d.message.clone()).collect()
+    }
+
+    #[test]
+    fn test_lint_example_file() {
+        assert!(lint(include_str!("../../../schemata-example.schema")).is_empty());
+    }
+
+    #[test]
+    fn test_lint_warnings() {
+        let input = r#"
+        namespace shop;
+        schema order_line {
+            unit_price float @cents
+            kind Kind
+            extras Extra[] schema {}
+        }
+        schema Open @extensible {}
+        enum Kind { Physical, DIGITAL }
+        enum unused { A }
+        "#;
+        assert_eq!(lint(input), vec![
+            "Schema `order_line` should have a PascalCase name",
+            "Field `unit_price` should have a camelCase name",
+            "Unknown annotation @cents",
+            "Schema `Extra` has no fields",
+            "Enum value `Physical` should be SCREAMING_SNAKE_CASE",
+            "Enum `unused` should have a PascalCase name",
+            "Enum `unused` is never used",
+        ]);
+    }
+}
@@ -1,2 +1,14 @@
 #[cfg(test)]
-mod generator_tests;
\ No newline at end of file
+mod analyzer_tests;
+#[cfg(test)]
+mod diff_tests;
+#[cfg(test)]
+mod formatter_tests;
+#[cfg(test)]
+mod generator_tests;
+#[cfg(test)]
+mod linter_tests;
+#[cfg(test)]
+mod parser_tests;
+#[cfg(test)]
+pub(crate) mod support;
\ No newline at end of file
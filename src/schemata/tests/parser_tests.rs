@@ -0,0 +1,330 @@
+#[cfg(test)]
+mod tests {
+    use crate::schemata::ast::Literal;
+    use crate::schemata::SchemataParser;
+
+    #[test]
+    fn test_parse_schema_with_fields() {
+        let input = r#"
+        namespace com.example.people;
+
+        schema User {
+            id int @id
+            username string
+            email string?
+            orders Order[]
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let namespace = &document.namespaces[0];
+        assert_eq!(namespace.name, "com.example.people");
+        assert_eq!(namespace.schemas.len(), 1);
+
+        let user = &namespace.schemas[0];
+        assert_eq!(user.name, Some("User".to_string()));
+        assert_eq!(user.fields.len(), 4);
+
+        assert_eq!(user.fields[0].name, "id");
+        assert_eq!(user.fields[0].type_ref.name, "int");
+        assert!(user.fields[0].type_ref.basic);
+
+        assert!(user.fields[2].type_ref.nullable);
+        assert!(!user.fields[2].type_ref.array);
+
+        assert_eq!(user.fields[3].type_ref.name, "Order");
+        assert!(!user.fields[3].type_ref.basic);
+        assert!(user.fields[3].type_ref.array);
+    }
+
+    #[test]
+    fn test_parse_basic_types() {
+        let input = r#"
+        namespace test;
+        schema Reading {
+            at datetime
+            day date
+            amount decimal(10, 2)?
+            counts map<string, long[]>
+            nested map<int, map<string, Sensor?>>
+        }
+        "#;
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let fields = &document.namespaces[0].schemas[0].fields;
+
+        assert_eq!(fields[0].type_ref.name, "datetime");
+        assert_eq!(fields[1].type_ref.name, "date");
+        assert_eq!(fields[2].type_ref.decimal, Some((10, 2)));
+        assert!(fields[2].type_ref.nullable);
+        let (key, value) = fields[3].type_ref.map.as_deref().expect("Expected a map type");
+        assert_eq!((key.name.as_str(), value.name.as_str(), value.array), ("string", "long", true));
+        assert!(fields.iter().all(|field| field.type_ref.basic));
+        // Types print back the way they are written
+        let types: Vec<String> = fields.iter().map(|field| field.type_ref.to_string()).collect();
+        assert_eq!(types, vec!["datetime", "date", "decimal(10, 2)?", "map<string, long[]>", "map<int, map<string, Sensor?>>"]);
+
+        // Every type of the mapping table is a basic type of the grammar
+        for basic in crate::schemata::type_mapping::BASIC_TYPES {
+            let input = format!("namespace test; schema S {{ value {} }}", basic.name);
+            let document = SchemataParser::parse(&input).expect("Failed to parse document");
+            assert!(document.namespaces[0].schemas[0].fields[0].type_ref.basic, "`{}` is not a basic type", basic.name);
+        }
+        // Names starting like a basic type are references
+        let document = SchemataParser::parse("namespace test; schema S { value dateRange }").unwrap();
+        assert!(!document.namespaces[0].schemas[0].fields[0].type_ref.basic);
+    }
+
+    #[test]
+    fn test_parse_annotations() {
+        let input = r#"
+        namespace test;
+
+        schema Profile {
+            id int @id @generated("UUID")
+            user User @relation(field = "userId", onDelete = "CASCADE")
+            count int @default(0)
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let fields = &document.namespaces[0].schemas[0].fields;
+
+        let id_annotations = &fields[0].annotations;
+        assert_eq!(id_annotations.len(), 2);
+        assert_eq!(id_annotations[0].name, "id");
+        assert!(id_annotations[0].args.is_empty());
+        assert_eq!(id_annotations[1].name, "generated");
+        assert_eq!(id_annotations[1].args[0].name, None);
+        assert_eq!(id_annotations[1].args[0].value, Literal::String("UUID".to_string()));
+
+        let relation = &fields[1].annotations[0];
+        assert_eq!(relation.name, "relation");
+        assert_eq!(relation.args.len(), 2);
+        assert_eq!(relation.arg("field").map(|v| v.as_str()), Some("userId"));
+        assert_eq!(relation.arg("onDelete").map(|v| v.as_str()), Some("CASCADE"));
+
+        assert_eq!(fields[2].annotations[0].args[0].value, Literal::Number("0".to_string()));
+    }
+
+    #[test]
+    fn test_parse_inline_schema() {
+        let input = r#"
+        namespace test;
+
+        schema Order {
+            items OrderItem[] @relation(onDelete = "CASCADE") schema {
+                id int @id
+                quantity int
+            }
+            total float
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let order = &document.namespaces[0].schemas[0];
+        assert_eq!(order.fields.len(), 2);
+
+        let inline = order.fields[0].inline_schema.as_ref().expect("Missing inline schema");
+        assert_eq!(inline.name, None);
+        assert_eq!(inline.fields.len(), 2);
+        assert_eq!(inline.fields[1].name, "quantity");
+        assert_eq!(order.fields[1].name, "total");
+    }
+
+    #[test]
+    fn test_parse_enum() {
+        let input = r#"
+        namespace test;
+
+        enum OrderStatus {
+            PENDING,
+            COMPLETED,
+            CANCELLED,
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let enums = &document.namespaces[0].enums;
+        assert_eq!(enums.len(), 1);
+        assert_eq!(enums[0].name, "OrderStatus");
+        assert_eq!(enums[0].values, vec!["PENDING", "COMPLETED", "CANCELLED"]);
+    }
+
+    #[test]
+    fn test_parse_enum_value_annotations() {
+        let input = r#"
+        namespace test;
+
+        enum OrderStatus {
+            UNKNOWN @value(number = "0"),
+            PENDING,
+            SHIPPED @value(number = "10") @appinfo("legacy"),
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let status = &document.namespaces[0].enums[0];
+        assert_eq!(status.values, vec!["UNKNOWN", "PENDING", "SHIPPED"]);
+        assert_eq!(status.value_annotations["UNKNOWN"][0].name, "value");
+        assert_eq!(status.value_annotations["UNKNOWN"][0].arg("number").map(|v| v.as_str()), Some("0"));
+        assert!(!status.value_annotations.contains_key("PENDING"));
+        assert_eq!(status.value_annotations["SHIPPED"].len(), 2);
+    }
+
+    #[test]
+    fn test_parse_comment_before_enum_value_annotation() {
+        let input = r#"
+        namespace test;
+
+        enum Status {
+            ACTIVE @value(number = "1"),
+            INACTIVE # trailing
+                @value(number = "2"),
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let status = &document.namespaces[0].enums[0];
+        assert_eq!(status.values, vec!["ACTIVE", "INACTIVE"]);
+        assert_eq!(status.value_annotations["INACTIVE"].len(), 1);
+        assert_eq!(status.value_annotations["INACTIVE"][0].arg("number").map(|v| v.as_str()), Some("2"));
+        assert_eq!(status.value_comments["INACTIVE"], "trailing");
+    }
+
+    #[test]
+    fn test_parse_type_alias() {
+        let input = r#"
+        namespace test;
+
+        # A stock keeping unit
+        type Sku = string @pattern(value = "[A-Z]{3}-[0-9]{4}");
+        type Size = union<int, string[]>;
+
+        schema Item {
+            sku Sku
+            sizes union<int, Size>?
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        let aliases = &document.namespaces[0].aliases;
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases[0].name, "Sku");
+        assert_eq!(aliases[0].type_ref.name, "string");
+        assert_eq!(aliases[0].annotations[0].name, "pattern");
+        assert_eq!(aliases[0].comment, Some("A stock keeping unit".to_string()));
+
+        let size = &aliases[1].type_ref;
+        let members = size.union.as_ref().expect("Size should be a union");
+        assert_eq!(members.len(), 2);
+        assert!(members[1].array);
+        assert_eq!(size.to_string(), "union<int, string[]>");
+
+        let sizes = &document.namespaces[0].schemas[0].fields[1].type_ref;
+        assert!(sizes.nullable);
+        assert_eq!(sizes.to_string(), "union<int, Size>?");
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let input = r#"
+        # Accounts and their orders
+        namespace test;
+
+        # Schema for a user
+        # with two lines
+        schema User {
+            # Primary key
+            id int @id
+            email string? @unique # Nullable, unique constraint
+            orders Order[] # One-to-many relationship
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        assert_eq!(document.namespaces[0].comment, Some("Accounts and their orders".to_string()));
+        let user = &document.namespaces[0].schemas[0];
+        assert_eq!(user.comment, Some("Schema for a user\nwith two lines".to_string()));
+        assert_eq!(user.fields[0].comment, Some("Primary key".to_string()));
+        assert_eq!(user.fields[1].comment, Some("Nullable, unique constraint".to_string()));
+        assert_eq!(user.fields[2].comment, Some("One-to-many relationship".to_string()));
+    }
+
+    #[test]
+    fn test_parse_multiple_namespaces() {
+        let input = r#"
+        namespace com.example.people;
+
+        schema User {
+            id int @id
+        }
+
+        namespace com.example.ecommerce {
+            schema Order {
+                id int @id
+            }
+
+            enum OrderStatus {
+                PENDING
+            }
+        }
+
+        schema Profile {
+            id int @id
+        }
+
+        namespace com.example.billing;
+
+        schema Invoice {
+            id int @id
+        }
+        "#;
+
+        let document = SchemataParser::parse(input).expect("Failed to parse document");
+        assert_eq!(document.namespaces.len(), 3);
+
+        let people = document.namespace("com.example.people").expect("Missing people namespace");
+        let names: Vec<_> = people.schemas.iter().filter_map(|s| s.name.as_deref()).collect();
+        assert_eq!(names, vec!["User", "Profile"], "Block namespaces should not leak into the enclosing one");
+
+        let ecommerce = document.namespace("com.example.ecommerce").expect("Missing ecommerce namespace");
+        assert_eq!(ecommerce.schemas.len(), 1);
+        assert_eq!(ecommerce.enums.len(), 1);
+
+        let billing = document.namespace("com.example.billing").expect("Missing billing namespace");
+        assert_eq!(billing.schemas[0].name, Some("Invoice".to_string()));
+    }
+
+    #[test]
+    fn test_parse_example_file() {
+        let document = SchemataParser::parse(include_str!("../../../schemata-example.schema"))
+            .expect("Failed to parse schemata-example.schema");
+        assert_eq!(document.namespaces.len(), 2);
+        assert_eq!(document.namespaces[0].schemas.len(), 2);
+        assert_eq!(document.namespaces[1].name, "com.example.ecommerce");
+        assert_eq!(document.namespaces[1].enums[0].values, vec!["PENDING", "COMPLETED", "CANCELLED"]);
+    }
+
+    #[test]
+    fn test_parse_invalid_input() {
+        assert!(SchemataParser::parse("schema User { id int }").is_err());
+        assert!(SchemataParser::parse("namespace test; schema User { id }").is_err());
+    }
+
+    #[test]
+    fn test_syntax_error_diagnostic() {
+        use crate::diagnostics::Diagnostic;
+
+        let input = "namespace test;\nschema User {\n    id int @id(\n}\n";
+        let error = SchemataParser::parse(input).expect_err("Parsing should fail");
+        let diagnostic = error.downcast_ref::<Diagnostic>().expect("Expected a Diagnostic")
+            .clone()
+            .with_source("user.schema", input);
+        assert_eq!(diagnostic.line, Some(4));
+        assert_eq!(diagnostic.column, Some(1));
+        assert!(diagnostic.render(input).contains("4 | }"));
+
+        let error = SchemataParser::parse("schema User { id int }").expect_err("Parsing should fail");
+        assert_eq!(error.to_string(), "Declaration outside of a namespace");
+    }
+}
@@ -0,0 +1,38 @@
+use serde_json::Value;
+
+use crate::output::GeneratedFile;
+use crate::schemata::analyzer::SymbolTable;
+use crate::schemata::ast::Document;
+use crate::schemata::types::{SchemataNamespace, SchemataSchema};
+use crate::schemata::{SchemataParser, SemanticAnalyzer};
+
+/// The schema of a namespace with the given name, for the tests of converters to Schemata.
+pub(crate) fn schema<'a>(namespace: &'a SchemataNamespace, name: &str) -> &'a SchemataSchema {
+    namespace.schemas.iter().find(|s| s.name == name).expect("Schema not found")
+}
+
+/// Field name, type, nullability and annotations, for compact assertions.
+pub(crate) fn fields(schema: &SchemataSchema) -> Vec<(String, String, bool, String)> {
+    schema.fields.iter()
+        .map(|f| (f.name.clone(), f.type_name.clone(), f.nullable, f.annotations.clone()))
+        .collect()
+}
+
+pub(crate) fn field(name: &str, type_name: &str, nullable: bool, annotations: &str) -> (String, String, bool, String) {
+    (name.to_string(), type_name.to_string(), nullable, annotations.to_string())
+}
+
+/// The `generate` function of an exporter.
+type Generate = fn(&Document, &SymbolTable) -> Result<Vec<GeneratedFile>, Box<dyn std::error::Error>>;
+
+/// Files generated from `input` as `(path, parsed JSON)` pairs, for the tests of exporters that
+/// write JSON.
+pub(crate) fn json_files(input: &str, generate: Generate) -> Vec<(String, Value)> {
+    let document = SchemataParser::parse(input).expect("Failed to parse document");
+    let symbols = SemanticAnalyzer::analyze(&document).expect("Document should be valid");
+    generate(&document, &symbols)
+        .expect("Failed to generate files")
+        .into_iter()
+        .map(|file| (file.path, serde_json::from_str(&file.content).expect("Output should be JSON")))
+        .collect()
+}
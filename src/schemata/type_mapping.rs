@@ -0,0 +1,195 @@
+use serde_json::Value;
+
+use super::analyzer::{ResolvedType, SymbolTable};
+use super::ast::Field;
+
+/// A Schemata basic type and the types it corresponds to in the formats Schemata converts from
+/// and to.
+///
+/// Generators write the first type of each list. Importers read every type listed; a type
+/// listed in several rows is read as the type of the first of them, so `string` comes before
+/// the types that are only written as strings.
+#[derive(Debug)]
+pub struct BasicType {
+    pub name: &'static str,
+    /// Avro types and logical types
+    pub avro: &'static [(&'static str, Option<&'static str>)],
+    /// Protobuf scalar and well-known types
+    pub proto: &'static [&'static str],
+    /// JSON Schema for a value of the type
+    pub json_schema: &'static str,
+    /// XSD built-in types
+    pub xsd: &'static [&'static str],
+}
+
+/// Every basic type of the grammar. `decimal` takes its precision and scale from the type,
+/// as in `decimal(10, 2)`; `map<K, V>` is not listed, as every format writes it its own way.
+pub const BASIC_TYPES: &[BasicType] = &[
+    BasicType {
+        name: "int",
+        avro: &[("int", None)],
+        proto: &["int32", "sint32", "sfixed32"],
+        json_schema: r#"{ "type": "integer" }"#,
+        xsd: &["int", "short", "byte", "unsignedShort", "unsignedByte"],
+    },
+    BasicType {
+        name: "long",
+        avro: &[("long", None)],
+        proto: &["int64", "sint64", "sfixed64", "uint32", "fixed32", "uint64", "fixed64"],
+        json_schema: r#"{ "type": "integer" }"#,
+        xsd: &["long", "integer", "nonPositiveInteger", "negativeInteger", "nonNegativeInteger", "positiveInteger", "unsignedInt", "unsignedLong"],
+    },
+    BasicType {
+        name: "float",
+        avro: &[("float", None)],
+        proto: &["float"],
+        json_schema: r#"{ "type": "number" }"#,
+        xsd: &["float"],
+    },
+    BasicType {
+        name: "double",
+        avro: &[("double", None)],
+        proto: &["double"],
+        json_schema: r#"{ "type": "number" }"#,
+        xsd: &["double"],
+    },
+    BasicType {
+        name: "string",
+        avro: &[("string", None)],
+        proto: &["string"],
+        json_schema: r#"{ "type": "string" }"#,
+        xsd: &[
+            "string", "normalizedString", "token", "language", "Name", "NCName", "NMTOKEN", "NMTOKENS",
+            "ID", "IDREF", "IDREFS", "ENTITY", "ENTITIES", "QName", "NOTATION",
+            "gYear", "gYearMonth", "gMonth", "gMonthDay", "gDay", "anySimpleType", "anyAtomicType",
+        ],
+    },
+    BasicType {
+        name: "bool",
+        avro: &[("boolean", None)],
+        proto: &["bool"],
+        json_schema: r#"{ "type": "boolean" }"#,
+        xsd: &["boolean"],
+    },
+    BasicType {
+        name: "bytes",
+        avro: &[("bytes", None)],
+        proto: &["bytes"],
+        json_schema: r#"{ "type": "string", "contentEncoding": "base64" }"#,
+        xsd: &["base64Binary", "hexBinary"],
+    },
+    BasicType {
+        name: "datetime",
+        avro: &[
+            ("long", Some("timestamp-micros")), ("long", Some("timestamp-millis")),
+            ("long", Some("local-timestamp-micros")), ("long", Some("local-timestamp-millis")),
+        ],
+        proto: &["google.protobuf.Timestamp"],
+        json_schema: r#"{ "type": "string", "format": "date-time" }"#,
+        xsd: &["dateTime", "dateTimeStamp"],
+    },
+    BasicType {
+        name: "date",
+        avro: &[("int", Some("date"))],
+        proto: &["string"],
+        json_schema: r#"{ "type": "string", "format": "date" }"#,
+        xsd: &["date"],
+    },
+    BasicType {
+        name: "time",
+        avro: &[("long", Some("time-micros")), ("int", Some("time-millis"))],
+        proto: &["string"],
+        json_schema: r#"{ "type": "string", "format": "time" }"#,
+        xsd: &["time"],
+    },
+    BasicType {
+        name: "duration",
+        // Avro durations are 12-byte fixed types, which need a name of their own, so durations
+        // are written as plain bytes
+        avro: &[("bytes", None), ("fixed", Some("duration"))],
+        proto: &["google.protobuf.Duration"],
+        json_schema: r#"{ "type": "string", "format": "duration" }"#,
+        xsd: &["duration", "dayTimeDuration", "yearMonthDuration"],
+    },
+    BasicType {
+        name: "decimal",
+        avro: &[("bytes", Some("decimal")), ("fixed", Some("decimal"))],
+        proto: &["string"],
+        json_schema: r#"{ "type": "number" }"#,
+        xsd: &["decimal"],
+    },
+    BasicType {
+        name: "uuid",
+        avro: &[("string", Some("uuid"))],
+        proto: &["string"],
+        json_schema: r#"{ "type": "string", "format": "uuid" }"#,
+        xsd: &[],
+    },
+    BasicType {
+        name: "uri",
+        avro: &[("string", None)],
+        proto: &["string"],
+        json_schema: r#"{ "type": "string", "format": "uri" }"#,
+        xsd: &["anyURI"],
+    },
+];
+
+/// Basic types that can key a `map<K, V>`.
+pub const MAP_KEY_TYPES: &[&str] = &["string", "int", "long", "bool", "uuid", "uri"];
+
+pub fn basic_type(name: &str) -> Option<&'static BasicType> {
+    BASIC_TYPES.iter().find(|basic| basic.name == name)
+}
+
+/// The basic type an Avro type, or a type with a logical type, is read as.
+pub fn from_avro(type_name: &str, logical_type: Option<&str>) -> Option<&'static BasicType> {
+    BASIC_TYPES.iter().find(|basic| basic.avro.contains(&(type_name, logical_type)))
+}
+
+pub fn from_proto(type_name: &str) -> Option<&'static BasicType> {
+    BASIC_TYPES.iter().find(|basic| basic.proto.contains(&type_name))
+}
+
+pub fn from_xsd(type_name: &str) -> Option<&'static BasicType> {
+    BASIC_TYPES.iter().find(|basic| basic.xsd.contains(&type_name))
+}
+
+/// The value field of an `XEntry[]` inline schema with string keys, which formats with
+/// string-keyed maps write as a map.
+pub fn map_entry(field: &Field) -> Option<&Field> {
+    let inline = field.inline_schema.as_ref()?;
+    if !field.type_ref.array || !field.type_ref.name.ends_with("Entry") {
+        return None;
+    }
+    match inline.fields.as_slice() {
+        [key, value] if key.name == "key" && value.name == "value"
+            && key.type_ref.name == "string" && !key.type_ref.array && !key.type_ref.nullable
+            && !value.type_ref.array && value.inline_schema.is_none() => Some(value),
+        _ => None,
+    }
+}
+
+/// Converts a `@default` value to JSON of the field's type, for formats that write defaults as
+/// JSON. Of the basic types, only those the `format` can write a default for are `supported`;
+/// values that cannot be represented, like `now()` on a timestamp, are dropped with a warning.
+pub fn default_value(namespace: &str, field: &Field, value: &str, symbols: &SymbolTable, supported: &[&str], format: &str) -> Option<Value> {
+    let parsed = if field.type_ref.array || field.inline_schema.is_some() {
+        serde_json::from_str(value).ok()
+    } else {
+        match symbols.resolve(namespace, &field.type_ref) {
+            Ok(ResolvedType::Basic(name)) if supported.contains(&name.as_str()) => match name.as_str() {
+                "int" | "long" => value.parse::<i64>().ok().map(Value::from),
+                "float" | "double" | "decimal" => value.parse::<f64>().ok().map(Value::from),
+                "bool" => value.parse::<bool>().ok().map(Value::from),
+                _ => Some(Value::from(value)),
+            },
+            Ok(ResolvedType::Basic(_)) => None,
+            Ok(ResolvedType::Enum(_)) => Some(Value::from(value)),
+            _ => serde_json::from_str(value).ok(),
+        }
+    };
+    if parsed.is_none() {
+        log::warn!("Dropping default `{}` of field `{}`: it has no {} representation", value, field.name, format);
+    }
+    parsed
+}
@@ -1,20 +1,25 @@
+use std::collections::BTreeMap;
+
 use serde::Serialize;
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct SchemataNamespace {
     pub name: String,
+    pub comment: Option<String>,
     pub schemas: Vec<SchemataSchema>,
     pub enums: Vec<SchemataEnum>,
+    pub aliases: Vec<SchemataAlias>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct SchemataSchema {
     pub name: String,
+    pub annotations: String,
     pub comment: Option<String>,
     pub fields: Vec<SchemataField>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct SchemataField {
     pub name: String,
     pub type_name: String,
@@ -22,11 +27,27 @@ pub struct SchemataField {
     pub annotations: String,
     pub comment: Option<String>,
     pub inline_schema: Option<SchemataSchema>,
+    /// An enum declared inline, written among the enums of the namespace
+    pub inline_enum: Option<SchemataEnum>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct SchemataEnum {
     pub name: String,
+    pub annotations: String,
     pub comment: Option<String>,
     pub values: Vec<String>,
+    /// Comments of enum values, keyed by value
+    pub value_comments: BTreeMap<String, String>,
+    /// Annotations of enum values, as written after the value, keyed by value
+    pub value_annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchemataAlias {
+    pub name: String,
+    /// The aliased type, as written after `=`
+    pub type_name: String,
+    pub annotations: String,
+    pub comment: Option<String>,
 }
@@ -0,0 +1,120 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::parser::XsdParser;
+use super::types::XsdSchema;
+
+/// Loads an XSD document with every document it includes or imports, transitively.
+///
+/// Locations are resolved relative to the document that refers to them. Remote locations are
+/// only followed through the catalog, which maps them to local files; documents are loaded once
+/// each, so circular includes are harmless. Included documents without a target namespace take
+/// the namespace of the document including them.
+#[derive(Debug, Default)]
+pub struct XsdLoader {
+    catalog: HashMap<String, PathBuf>,
+}
+
+impl XsdLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads `path` instead of the document at `location` wherever it is included or imported.
+    pub fn with_catalog_entry(mut self, location: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.catalog.insert(location.into(), path.into());
+        self
+    }
+
+    /// Loads the document at `path` and the ones it refers to, root document first.
+    pub fn load(&self, path: &Path) -> Result<Vec<XsdSchema>, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        self.load_source(&source, Some(path))
+    }
+
+    /// Loads a document that was already read, and the ones it refers to. Locations in it are
+    /// resolved relative to `path`, or to the working directory if it has none.
+    ///
+    /// Errors in the root document are returned as they are; errors in other documents are
+    /// wrapped in an [`XsdLoadError`] naming that document.
+    pub fn load_source(&self, source: &str, path: Option<&Path>) -> Result<Vec<XsdSchema>, Box<dyn std::error::Error>> {
+        let mut visited = HashSet::new();
+        if let Some(path) = path {
+            visited.insert(Self::canonical(path));
+        }
+        let root = XsdParser::parse(source.as_bytes())?;
+        let base = path.and_then(Path::parent).unwrap_or(Path::new("")).to_path_buf();
+
+        let mut schemas = Vec::new();
+        self.visit(root, &base, &mut visited, &mut schemas)?;
+        Ok(schemas)
+    }
+
+    fn visit(&self, schema: XsdSchema, base: &Path, visited: &mut HashSet<PathBuf>, out: &mut Vec<XsdSchema>) -> Result<(), Box<dyn std::error::Error>> {
+        let namespace = schema.target_namespace.clone();
+        let references: Vec<(String, bool)> = schema.included_schemas.iter().map(|l| (l.clone(), true))
+            .chain(schema.imported_schemas.iter().map(|l| (l.clone(), false)))
+            .collect();
+        out.push(schema);
+
+        for (location, included) in references {
+            let Some(path) = self.resolve(&location, base) else {
+                log::warn!("Not loading {}: remote schemas are only read through the catalog", location);
+                continue;
+            };
+            if !visited.insert(Self::canonical(&path)) {
+                continue;
+            }
+            log::info!("Loading {}...", path.display());
+
+            let file = path.display().to_string();
+            let source = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Cannot read {}, referred to as {}: {}", file, location, e))?;
+            let mut schema = XsdParser::parse(source.as_bytes()).map_err(|error| XsdLoadError {
+                file: file.clone(),
+                source: source.clone(),
+                error,
+            })?;
+            if included && schema.target_namespace.is_none() {
+                schema.target_namespace = namespace.clone();
+            }
+            let base = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            self.visit(schema, &base, visited, out)?;
+        }
+        Ok(())
+    }
+
+    /// The local file for `location`: its catalog entry, or the path relative to `base` unless
+    /// it is a URL.
+    fn resolve(&self, location: &str, base: &Path) -> Option<PathBuf> {
+        if let Some(path) = self.catalog.get(location) {
+            return Some(path.clone());
+        }
+        if location.contains("://") {
+            return None;
+        }
+        Some(base.join(location.strip_prefix("file:").unwrap_or(location)))
+    }
+
+    fn canonical(path: &Path) -> PathBuf {
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// An error in a document reached through an include or import, with that document's source so
+/// that the error's spans can be shown.
+#[derive(Debug)]
+pub struct XsdLoadError {
+    pub file: String,
+    pub source: String,
+    pub error: Box<dyn std::error::Error>,
+}
+
+impl std::fmt::Display for XsdLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file, self.error)
+    }
+}
+
+impl std::error::Error for XsdLoadError {}
@@ -1,4 +1,7 @@
+mod loader;
 mod parser;
+pub mod qname;
 pub mod types;
 mod tests;
-pub use parser::XsdParser;
\ No newline at end of file
+pub use loader::{XsdLoadError, XsdLoader};
+pub use parser::{XsdParser, XsdParsingError};
\ No newline at end of file
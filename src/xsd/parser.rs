@@ -1,9 +1,9 @@
-use std::collections::HashMap;
 use quick_xml::events::{Event, BytesStart};
 use quick_xml::reader::Reader;
 use std::io::BufRead;
 
 use super::types::*;
+use crate::diagnostics::{Diagnostic, Span, ToDiagnostic};
 
 pub struct XsdParser;
 
@@ -12,79 +12,59 @@ impl XsdParser {
         let mut xml_reader = Reader::from_reader(reader);
         xml_reader.trim_text(true);
 
-        let mut buf = Vec::new();
-        let mut schema = XsdSchema::default();
-        let mut element_stack = Vec::new();
-        let mut namespace_map: HashMap<String, String> = HashMap::new();
+        let mut builder = SchemaBuilder::new();
+        builder.run(&mut xml_reader)?;
+        Ok(builder.finish())
+    }
 
-        loop {
-            match xml_reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) => {
-                    match e.local_name().as_ref() {
-                        b"schema" => {
-                            // Capture schema-level attributes and namespaces
-                            schema.target_namespace = Self::get_attribute(e, "targetNamespace", &xml_reader);
-                            // Collect namespace declarations
-                            for attr in e.attributes() {
-                                if let Ok(attr) = attr {
-                                    let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
-                                    if key.starts_with("xmlns:") {
-                                        let prefix = key.split(':').nth(1).unwrap_or_default();
-                                        if let Ok(value) = attr.decode_and_unescape_value(&xml_reader) {
-                                            namespace_map.insert(prefix.to_string(), value.to_string());
-                                        }
-                                    }
-                                }
-                            }
-                        },
-                        b"element" => {
-                            let element = Self::parse_element(e, &mut xml_reader)?;
-                            element_stack.push(element);
-                        },
-                        b"complexType" => {
-                            let complex_type = Self::parse_complex_type(e, &mut xml_reader)?;
-                            if complex_type.name.is_some() {
-                                schema.complex_types.push(complex_type);
-                            } else if let Some(element) = element_stack.last_mut() {
-                                element.complex_type = Some(complex_type);
-                            }
-                        },
-                        b"simpleType" => {
-                            let simple_type = Self::parse_simple_type(e, &mut xml_reader)?;
-                            schema.simple_types.push(simple_type);
-                        },
-                        b"include" | b"import" => {
-                          // Handle schema includes and imports
-                            let schema_location = Self::get_attribute(e, "schemaLocation", &xml_reader);
-                            if let Some(location) = schema_location {
-                                schema.imported_schemas.push(location);
-                            }
-                        },
-                        _ => {
-                            eprintln!("Unhandled top-level element: {:?}",
-                                String::from_utf8_lossy(e.name().as_ref()));
-                        }
-                    }
-                },
-                Ok(Event::End(ref e)) => {
-                    if e.local_name().as_ref() == b"element" {
-                        if let Some(element) = element_stack.pop() {
-                            schema.elements.push(element);
-                        }
-                    }
-                },
-                Ok(Event::Eof) => break,
-                Err(e) => {
-                    eprintln!("Error parsing XML: {:?}", e);
-                    return Err(Box::new(e));
-                },
-                _ => {}
+    /// Parses the component whose opening or self-closing tag `event` was just read, up to its
+    /// closing tag, into a schema holding it alone. Nothing is read after a self-closing tag.
+    pub(crate) fn parse_component<R: BufRead>(event: &Event, reader: &mut Reader<R>) -> Result<XsdSchema, Box<dyn std::error::Error>> {
+        let mut builder = SchemaBuilder::new();
+        match event {
+            Event::Start(e) => {
+                builder.start(e, reader);
+                builder.run(reader)?;
+            },
+            Event::Empty(e) => {
+                let frame = builder.open(e, reader);
+                builder.close(frame);
+            },
+            _ => return Err("Expected the tag of a component".into()),
+        }
+        Ok(builder.finish())
+    }
+
+    /// Parses an element whose opening or self-closing tag `event` was just read, up to its
+    /// closing tag: its inline type and its documentation.
+    pub fn parse_element<R: BufRead>(event: &Event, reader: &mut Reader<R>) -> Result<XsdElement, Box<dyn std::error::Error>> {
+        Self::parse_component(event, reader)?.elements.pop().ok_or_else(|| "Expected an <element>".into())
+    }
+
+    /// Captures the target namespace and the namespace declarations of the `schema` element.
+    fn parse_schema_attributes<R: BufRead>(e: &BytesStart, schema: &mut XsdSchema, reader: &Reader<R>) {
+        schema.target_namespace = Self::get_attribute(e, "targetNamespace", reader);
+        for attr in e.attributes().flatten() {
+            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+            // The default namespace is stored under an empty prefix
+            if key == "xmlns" || key.starts_with("xmlns:") {
+                let prefix = key.split(':').nth(1).unwrap_or_default();
+                if let Ok(value) = attr.decode_and_unescape_value(reader) {
+                    schema.namespaces.insert(prefix.to_string(), value.to_string());
+                }
+            }
+        }
+    }
+
+    /// Records the `schemaLocation` of an `include` or `import`. Imports without a location only
+    /// declare a namespace.
+    fn record_location<R: BufRead>(e: &BytesStart, schema: &mut XsdSchema, reader: &Reader<R>) {
+        if let Some(location) = Self::get_attribute(e, "schemaLocation", reader) {
+            match e.local_name().as_ref() {
+                b"include" => schema.included_schemas.push(location),
+                _ => schema.imported_schemas.push(location),
             }
-            buf.clear();
         }
-        // Store namespace mappings
-        schema.namespaces = namespace_map;
-        Ok(schema)
     }
 
     pub(crate) fn get_attribute<R: BufRead>(e: &BytesStart, attr_name: &str, reader: &Reader<R>) -> Option<String> {
@@ -97,7 +77,7 @@ impl XsdParser {
                     match a.decode_and_unescape_value(reader) {
                         Ok(val) => Some(val.into_owned()),
                         Err(e) => {
-                            eprintln!("Error decoding attribute {}: {:?}", attr_name, e);
+                            log::warn!("Error decoding attribute {}: {:?}", attr_name, e);
                             None
                         }
                     }
@@ -107,265 +87,556 @@ impl XsdParser {
             })
     }
 
-    pub fn parse_element<R: BufRead>(e: &BytesStart, reader: &mut Reader<R>) -> Result<XsdElement, Box<dyn std::error::Error>> {
-        let mut element = XsdElement {
-            name: Self::get_attribute(e, "name", reader).unwrap_or_default(),
+    /// An element declared by a self-closing tag, or the attributes of any element.
+    pub fn empty_element<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> XsdElement {
+        let name = Self::get_attribute(e, "name", reader);
+        Self::log_parsing_progress("element", name.as_deref());
+
+        XsdElement {
+            name: name.unwrap_or_default(),
             type_name: Self::get_attribute(e, "type", reader),
+            reference: Self::get_attribute(e, "ref", reader),
             min_occurs: Self::get_attribute(e, "minOccurs", reader),
             max_occurs: Self::get_attribute(e, "maxOccurs", reader),
-            complex_type: None,
-            simple_type: None,
-            comment: None,
-        };
+            substitution_group: Self::get_attribute(e, "substitutionGroup", reader),
+            is_abstract: Self::get_attribute(e, "abstract", reader).is_some_and(|v| v == "true"),
+            nillable: Self::get_attribute(e, "nillable", reader).is_some_and(|v| v == "true"),
+            ..Default::default()
+        }
+    }
 
-        // Parse nested complex type if present
-        if element.type_name.is_none() {
-            let mut buf = Vec::new();
-            loop {
-                match reader.read_event_into(&mut buf) {
-                    Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"complexType" => {
-                        element.complex_type = Some(Self::parse_complex_type(e, reader)?);
-                        break;
-                    },
-                    Ok(Event::End(ref e)) if e.local_name().as_ref() == b"element" => break,
-                    Ok(Event::Eof) => return Err("Unexpected end of file while parsing <element>".into()),
-                    Err(e) => return Err(Box::new(e)),
-                    _ => {}
-                }
-                buf.clear();
-            }
+    /// A complex type with the attributes of `e` and no content yet.
+    fn empty_complex_type<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> XsdComplexType {
+        XsdComplexType {
+            name: Self::get_attribute(e, "name", reader),
+            mixed: Self::get_attribute(e, "mixed", reader).is_some_and(|v| v == "true"),
+            ..Default::default()
         }
+    }
 
-        element.comment = Self::extract_comment(reader);
+    /// The base type of a `complexContent` or `simpleContent` extension or restriction. A
+    /// derivation without a `base` attribute is ignored.
+    fn parse_derivation<R: BufRead>(e: &BytesStart, simple_content: bool, reader: &Reader<R>) -> Option<XsdDerivation> {
+        let Some(base) = Self::get_attribute(e, "base", reader) else {
+            log::warn!("Ignoring a derivation without a base type");
+            return None;
+        };
+        let method = match e.local_name().as_ref() {
+            b"extension" => XsdDerivationMethod::Extension,
+            _ => XsdDerivationMethod::Restriction,
+        };
+        Some(XsdDerivation { base, method, simple_content })
+    }
 
-        Ok(element)
+    /// A `group ref` of a compositor. A group without `ref` is not allowed there and is ignored.
+    fn group_ref<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> Option<XsdGroupRef> {
+        let Some(reference) = Self::get_attribute(e, "ref", reader) else {
+            log::warn!("Ignoring a group without a `ref` inside a content model");
+            return None;
+        };
+        Some(XsdGroupRef {
+            reference,
+            min_occurs: Self::get_attribute(e, "minOccurs", reader),
+            max_occurs: Self::get_attribute(e, "maxOccurs", reader),
+        })
     }
 
-    pub(crate) fn parse_complex_type<R: BufRead>(e: &BytesStart, reader: &mut Reader<R>) -> Result<XsdComplexType, Box<dyn std::error::Error>> {
-        let mut complex_type = XsdComplexType {
-            name: Self::get_attribute(e, "name", reader),
-            sequence: Vec::new(),
-            attributes: Vec::new(),
-            mixed: Self::get_attribute(e, "mixed", reader)
-                .map(|v| v == "true")
-                    .unwrap_or(false),
+    /// An `any` element or `anyAttribute`.
+    fn wildcard<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> XsdWildcard {
+        XsdWildcard {
+            namespace: Self::get_attribute(e, "namespace", reader),
+            process_contents: Self::get_attribute(e, "processContents", reader),
+            min_occurs: Self::get_attribute(e, "minOccurs", reader),
+            max_occurs: Self::get_attribute(e, "maxOccurs", reader),
+        }
+    }
+
+    /// The content of a complex type that is a `group ref` alone: a sequence of the reference.
+    fn group_content<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> Option<XsdCompositor> {
+        let reference = Self::group_ref(e, reader)?;
+        Some(XsdCompositor {
+            kind: XsdCompositorKind::Sequence,
+            min_occurs: None,
+            max_occurs: None,
+            particles: vec![XsdParticle::Group(reference)],
+        })
+    }
+
+    /// A `key`, `keyref` or `unique` constraint with the attributes of `e` and no XPaths yet.
+    fn empty_identity_constraint<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> XsdIdentityConstraint {
+        let kind = match e.local_name().as_ref() {
+            b"key" => XsdIdentityConstraintKind::Key,
+            b"keyref" => XsdIdentityConstraintKind::KeyRef,
+            _ => XsdIdentityConstraintKind::Unique,
         };
+        XsdIdentityConstraint {
+            kind,
+            name: Self::get_attribute(e, "name", reader).unwrap_or_default(),
+            refer: Self::get_attribute(e, "refer", reader),
+            selector: String::new(),
+            fields: Vec::new(),
+        }
+    }
 
-        let mut buf = Vec::new();
-        let mut depth = 0;
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) => {
-                    depth += 1;
-                    match e.local_name().as_ref() {
-                        b"sequence" => {
-                            complex_type.sequence = Self::parse_sequence(reader)?;
-                        },
-                        b"attribute" => {
-                            if let Ok(attr) = Self::parse_attribute(e, reader) {
-                                complex_type.attributes.push(attr);
-                            }
-                        },
-                        _ => {
-                            println!("Unhandled start element: {:?}", String::from_utf8_lossy(e.local_name().as_ref()));
-                        }
-                    }
-                },
-                Ok(Event::Empty(ref e)) => {
-                    if e.local_name().as_ref() == b"attribute" {
-                        if let Ok(attr) = Self::parse_attribute(e, reader) {
-                            complex_type.attributes.push(attr);
-                        }
-                    }
-                },
-                Ok(Event::End(ref e)) => {
-                    if e.local_name().as_ref() == b"complexType" {
-                        return Ok(complex_type)
-                    }
-                    depth -= 1;
-                },
-                Ok(Event::Eof) => return Err(format!("Unexpected end of file while parsing <complexType> (depth: {})", depth).into()),
-                Err(e) => return Err(Box::new(e)),
-                _ => {}
-            }
-            buf.clear();
+    /// Records the XPath of a `selector` or `field` of an identity constraint.
+    fn record_xpath<R: BufRead>(e: &BytesStart, constraint: &mut XsdIdentityConstraint, reader: &Reader<R>) {
+        let xpath = Self::get_attribute(e, "xpath", reader).unwrap_or_default();
+        match e.local_name().as_ref() {
+            b"selector" => constraint.selector = xpath,
+            b"field" => constraint.fields.push(xpath),
+            _ => {}
         }
-        Ok(complex_type)
     }
 
-    pub(crate) fn parse_sequence<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<XsdElement>, Box<dyn std::error::Error>> {
-        let mut sequence = Vec::new();
-        let mut buf = Vec::new();
-        let mut depth = 0;
+    fn compositor_kind(e: &BytesStart) -> Option<XsdCompositorKind> {
+        match e.local_name().as_ref() {
+            b"sequence" => Some(XsdCompositorKind::Sequence),
+            b"choice" => Some(XsdCompositorKind::Choice),
+            b"all" => Some(XsdCompositorKind::All),
+            _ => None,
+        }
+    }
 
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref start_element)) => {
-                    depth += 1;
-                    if start_element.local_name().as_ref() == b"element" {
-                        if let Ok(element) = Self::parse_element(start_element, reader) {
-                            sequence.push(element);
-                        }
-                    }
-                },
-                Ok(Event::Empty(ref e)) => {
-                  if e.local_name().as_ref() == b"element" {
-                      sequence.push(Self::parse_element(e, reader)?);
-                  }
-                },
-                Ok(Event::End(ref e)) => {
-                    if depth == 0 && e.local_name().as_ref() == b"sequence" {
-                        return Ok(sequence);
-                    }
-                    depth -= 1;
-                },
-                Ok(Event::Eof) => {
-                    return Err(format!("Unexpected end of file while parsing <sequence> (depth: {})", depth).into());
-                },
-                Err(e) => return Err(Box::new(e)),
-                _ => {}
-            }
-            buf.clear();
+    /// A compositor with the occurrence bounds of `e` and no particles yet.
+    fn empty_compositor<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> XsdCompositor {
+        XsdCompositor {
+            kind: Self::compositor_kind(e).unwrap_or(XsdCompositorKind::Sequence),
+            min_occurs: Self::get_attribute(e, "minOccurs", reader),
+            max_occurs: Self::get_attribute(e, "maxOccurs", reader),
+            particles: Vec::new(),
         }
-        Ok(sequence)
     }
 
-    pub(crate) fn parse_attribute<R: BufRead>(e: &BytesStart, reader: &mut Reader<R>) -> Result<XsdAttribute, Box<dyn std::error::Error>> {
-        Ok(XsdAttribute {
+    pub(crate) fn empty_attribute<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> XsdAttribute {
+        XsdAttribute {
             name: Self::get_attribute(e, "name", reader).unwrap_or_default(),
             type_name: Self::get_attribute(e, "type", reader).unwrap_or_default(),
             use_type: Self::get_attribute(e, "use", reader).unwrap_or_default(),
             default: Self::get_attribute(e, "default", reader),
             fixed: Self::get_attribute(e, "fixed", reader),
-        })
+            ..Default::default()
+        }
     }
 
-    pub(crate) fn parse_simple_type<R: BufRead>(e: &BytesStart, reader: &mut Reader<R>) -> Result<XsdSimpleType, Box<dyn std::error::Error>> {
-        let mut simple_type = XsdSimpleType {
-            name: Self::get_attribute(e, "name", reader),
-            restriction: None,
-            list: None,
-            union: None,
-        };
+    /// Records the `itemType` of a `list` or the `memberTypes` of a `union`.
+    fn parse_member_types<R: BufRead>(e: &BytesStart, simple_type: &mut XsdSimpleType, reader: &Reader<R>) {
+        if e.local_name().as_ref() == b"list" {
+            simple_type.list = Self::get_attribute(e, "itemType", reader);
+        } else {
+            simple_type.union = Self::get_attribute(e, "memberTypes", reader)
+                .map(|v| v.split_whitespace().map(String::from).collect());
+        }
+    }
 
-        let mut buf = Vec::new();
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) => {
-                    match e.local_name().as_ref() {
-                        b"restriction" => {
-                            simple_type.restriction = Some(Self::parse_restriction(e, reader)?);
-                        },
-                        b"list" => {
-                          simple_type.list = Self::get_attribute(e, "itemType", reader);
-                        },
-                        b"union" => {
-                            simple_type.union = Self::get_attribute(e, "memberTypes", reader)
-                                .map(|v| v.split_whitespace().map(String::from).collect());
-                        },
-                        _ => {}
-                    }
-                },
-                Ok(Event::End(ref e)) if e.local_name().as_ref() == b"simpleType" => break,
-                Ok(Event::Eof) => return Err("Unexpected end of file while parsing <simpleType>".into()),
-                Err(e) => return Err(Box::new(e)),
-                _ => {}
+    /// Records a facet of a restriction other than an enumeration.
+    fn record_facet<R: BufRead>(e: &BytesStart, restriction: &mut XsdRestriction, reader: &Reader<R>) {
+        let value = Self::get_attribute(e, "value", reader);
+        let size = || value.as_deref().and_then(|v| v.parse().ok());
+        match e.local_name().as_ref() {
+            b"pattern" => restriction.pattern = value.clone(),
+            b"minInclusive" => restriction.min_inclusive = value.clone(),
+            b"maxInclusive" => restriction.max_inclusive = value.clone(),
+            b"minExclusive" => restriction.min_exclusive = value.clone(),
+            b"maxExclusive" => restriction.max_exclusive = value.clone(),
+            b"length" => restriction.length = size(),
+            b"minLength" => restriction.min_length = size(),
+            b"maxLength" => restriction.max_length = size(),
+            b"totalDigits" => restriction.total_digits = size(),
+            b"fractionDigits" => restriction.fraction_digits = size(),
+            _ => {}
+        }
+    }
+
+    /// The text of the `documentation`s of an annotation, separated by blank lines.
+    fn join_documentation(mut documentation: Vec<String>) -> Option<String> {
+        documentation.retain(|text| !text.is_empty());
+        (!documentation.is_empty()).then(|| documentation.join("\n\n"))
+    }
+
+    /// Removes the indentation shared by all lines but the first, which starts right after the
+    /// opening tag, and collapses runs of blank lines.
+    fn dedent(text: &str) -> String {
+        let lines: Vec<&str> = text.trim().lines().map(str::trim_end).collect();
+        let indent = lines.iter().skip(1)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        let mut out: Vec<&str> = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let line = if i == 0 || line.is_empty() { line } else { &line[indent..] };
+            if !(line.is_empty() && out.last().is_some_and(|last| last.is_empty())) {
+                out.push(line);
             }
-            buf.clear();
         }
-        Ok(simple_type)
-    }
-
-    pub(crate) fn parse_restriction<R: BufRead>(e: &BytesStart, reader: &mut Reader<R>) -> Result<XsdRestriction, Box<dyn std::error::Error>> {
-        let mut restriction = XsdRestriction {
-            base: Self::get_attribute(e, "base", reader).unwrap_or_default(),
-            enumeration: Vec::new(),
-            pattern: None,
-            min_inclusive: None,
-            max_inclusive: None,
-            min_exclusive: None,
-            max_exclusive: None,
-            length: None,
-            min_length: None,
-            max_length: None,
-            total_digits: None,
-            fraction_digits: None,
-        };
+        out.join("\n")
+    }
+
+    /// Byte range of the tag that was just read. quick-xml only reports the position after an
+    /// event, so the start is derived from the tag length.
+    fn tag_span<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> Span {
+        let end = reader.buffer_position();
+        Span::new(end.saturating_sub(e.len() + 2), end)
+    }
+
+    fn unexpected_eof(context: &str, opened_at: Span) -> Box<dyn std::error::Error> {
+        Box::new(XsdParsingError {
+            message: format!("Unexpected end of file while parsing <{}>", context),
+            context: format!("<{}>", context),
+            span: opened_at,
+            help: Some(format!("check that this <{}> has a matching closing tag", context)),
+        })
+    }
+
+    fn xml_error<R: BufRead>(error: quick_xml::Error, reader: &Reader<R>) -> Box<dyn std::error::Error> {
+        let position = reader.buffer_position();
+        Box::new(XsdParsingError {
+            message: format!("Malformed XML: {}", error),
+            context: "XML".to_string(),
+            span: Span::new(position, position + 1),
+            help: None,
+        })
+    }
+
+    fn log_parsing_progress(element: &str, details: Option<&str>) {
+        let detail_str = details.unwrap_or("No details");
+        log::debug!("Parsing {}: {}", element, detail_str);
+    }
+}
+
+/// What the content of an open tag is parsed into.
+enum Frame {
+    /// The document, which the `schema` element passes its components on to
+    Schema(XsdSchema),
+    Element(Box<XsdElement>),
+    /// A complex type and whether a `simpleContent` (true) or `complexContent` (false)
+    /// declaration of it was opened
+    ComplexType(XsdComplexType, Option<bool>),
+    Compositor(XsdCompositor),
+    Group(XsdGroup),
+    AttributeGroup(XsdAttributeGroup),
+    Attribute(XsdAttribute),
+    SimpleType(XsdSimpleType),
+    Restriction(XsdRestriction),
+    /// The value of an enumeration and its documentation
+    Enumeration(Option<String>, Option<String>),
+    /// The text of the `documentation`s and the `appinfo`s of an annotation
+    Annotation(Vec<String>, Vec<XsdAppInfo>),
+    /// The pieces of text of a `documentation`
+    Documentation(Vec<String>),
+    /// The `source` and the pieces of text of an `appinfo`
+    AppInfo(Option<String>, Vec<String>),
+    IdentityConstraint(XsdIdentityConstraint),
+    /// A tag whose content belongs to the frame it is in, like `complexContent` or markup in a
+    /// `documentation`
+    Transparent,
+    /// A tag whose content is ignored, like the annotation of a `group ref`
+    Skip,
+}
+
+/// A tag that was opened and not closed yet.
+struct OpenTag {
+    frame: Frame,
+    /// The local name of the tag, which an unexpected end of file reports
+    name: String,
+    opened_at: Span,
+}
+
+/// Builds the components of a document in one pass over its events. Every open tag has a frame
+/// on the stack, which its closing tag pops and attaches to the frame below. Only the
+/// components on the path to the current tag are being built at any time, so the stack grows
+/// with the nesting depth of the document and nothing else.
+struct SchemaBuilder {
+    /// The open tags, the document at the bottom
+    stack: Vec<OpenTag>,
+}
+
+impl SchemaBuilder {
+    fn new() -> Self {
+        let document = OpenTag { frame: Frame::Schema(XsdSchema::default()), name: String::new(), opened_at: Span::new(0, 0) };
+        Self { stack: vec![document] }
+    }
+
+    fn finish(mut self) -> XsdSchema {
+        match self.stack.swap_remove(0).frame {
+            Frame::Schema(schema) => schema,
+            _ => unreachable!("the document is at the bottom of the stack"),
+        }
+    }
 
+    /// Reads events until the tags opened in the document so far are closed, or the end of file.
+    fn run<R: BufRead>(&mut self, reader: &mut Reader<R>) -> Result<(), Box<dyn std::error::Error>> {
         let mut buf = Vec::new();
         loop {
             match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => self.start(e, reader),
                 Ok(Event::Empty(ref e)) => {
-                    match e.local_name().as_ref() {
-                        b"enumeration" => {
-                            if let Some(value) = Self::get_attribute(e, "value", reader) {
-                                restriction.enumeration.push(value);
-                            }
-                        },
-                        b"pattern" => {
-                            restriction.pattern = Self::get_attribute(e, "value", reader);
-                        },
-                        b"minInclusive" => {
-                            restriction.min_inclusive = Self::get_attribute(e, "value", reader);
-                        },
-                        b"maxInclusive" => {
-                            restriction.max_inclusive = Self::get_attribute(e, "value", reader);
-                        },
-                        b"minExclusive" => {
-                            restriction.min_exclusive = Self::get_attribute(e, "value", reader);
-                        },
-                        b"maxExclusive" => {
-                            restriction.max_exclusive = Self::get_attribute(e, "value", reader);
-                        },
-                        b"length" => {
-                            restriction.length = Self::get_attribute(e, "value", reader).and_then(|v| v.parse().ok());
-                        },
-                        b"minLength" => {
-                            restriction.min_length = Self::get_attribute(e, "value", reader).and_then(|v| v.parse().ok());
-                        },
-                        b"maxLength" => {
-                            restriction.max_length = Self::get_attribute(e, "value", reader).and_then(|v| v.parse().ok());
-                        },
-                        b"totalDigits" => {
-                            restriction.total_digits = Self::get_attribute(e, "value", reader).and_then(|v| v.parse().ok());
-                        },
-                        b"fractionDigits" => {
-                            restriction.fraction_digits = Self::get_attribute(e, "value", reader).and_then(|v| v.parse().ok());
-                        },
-                        _ => {}
+                    let frame = self.open(e, reader);
+                    self.close(frame);
+                    if self.stack.len() == 1 {
+                        return Ok(());
                     }
                 },
-                Ok(Event::End(ref e)) if e.local_name().as_ref() == b"restriction" => break,
-                Ok(Event::Eof) => return Err("Unexpected end of file".into()),
-                Err(e) => return Err(Box::new(e)),
+                Ok(Event::End(_)) => {
+                    if self.stack.len() > 1 {
+                        let tag = self.stack.pop().expect("a tag is open");
+                        self.close(tag.frame);
+                    }
+                    if self.stack.len() == 1 {
+                        return Ok(());
+                    }
+                },
+                Ok(Event::Text(ref text)) => {
+                    if let Some(pieces) = self.text_pieces() {
+                        pieces.push(text.unescape().map_err(|err| XsdParser::xml_error(err, reader))?.into_owned());
+                    }
+                },
+                Ok(Event::CData(ref data)) => {
+                    if let Some(pieces) = self.text_pieces() {
+                        pieces.push(String::from_utf8_lossy(data).into_owned());
+                    }
+                },
+                Ok(Event::Eof) => {
+                    let innermost = self.stack.last().expect("the document is on the stack");
+                    if self.stack.len() > 1 {
+                        return Err(XsdParser::unexpected_eof(&innermost.name, innermost.opened_at));
+                    }
+                    return Ok(());
+                },
+                Err(err) => return Err(XsdParser::xml_error(err, reader)),
                 _ => {}
             }
             buf.clear();
         }
-        Ok(restriction)
     }
 
-    pub(crate) fn extract_comment<R: BufRead>(reader: &mut Reader<R>) -> Option<String> {
-        // TODO: Implement extraction logic
-        None
+    /// Opens the tag `e`, which was just read.
+    fn start<R: BufRead>(&mut self, e: &BytesStart, reader: &Reader<R>) {
+        let opened_at = XsdParser::tag_span(e, reader);
+        let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+        let frame = self.open(e, reader);
+        self.stack.push(OpenTag { frame, name, opened_at });
     }
 
-    fn log_parsing_progress(element: &str, details: Option<&str>) {
-        if cfg!(debug_assertions) {
-            let detail_str = details.unwrap_or("No details");
-            println!("Parsing {}: {}", element, detail_str);
+    /// The frame the content of the innermost open tag belongs to.
+    fn context(&mut self) -> &mut Frame {
+        self.stack.iter_mut().rev()
+            .map(|tag| &mut tag.frame)
+            .find(|frame| !matches!(frame, Frame::Transparent))
+            .expect("the document is never transparent")
+    }
+
+    /// Where text read now goes, if it is the text of a `documentation` or `appinfo`.
+    fn text_pieces(&mut self) -> Option<&mut Vec<String>> {
+        match self.context() {
+            Frame::Documentation(pieces) | Frame::AppInfo(_, pieces) => Some(pieces),
+            _ => None,
         }
     }
-}
 
-trait XsdParserError: std::error::Error {
-    fn get_context(&self) -> String;
+    /// The frame of the tag `e` in the current context. What the attributes of a reference or
+    /// facet say is recorded right away, and the content of the tag is skipped.
+    fn open<R: BufRead>(&mut self, e: &BytesStart, reader: &Reader<R>) -> Frame {
+        let local_name = e.local_name();
+        match (self.context(), local_name.as_ref()) {
+            (Frame::Schema(schema), b"schema") => {
+                XsdParser::parse_schema_attributes(e, schema, reader);
+                Frame::Transparent
+            },
+            (Frame::Schema(schema), b"include" | b"import") => {
+                XsdParser::record_location(e, schema, reader);
+                Frame::Skip
+            },
+            (Frame::Schema(_) | Frame::Compositor(_), b"element") => Frame::Element(Box::new(XsdParser::empty_element(e, reader))),
+            (Frame::Schema(_) | Frame::Element(_), b"complexType") => Frame::ComplexType(XsdParser::empty_complex_type(e, reader), None),
+            (Frame::Schema(_) | Frame::Element(_) | Frame::Attribute(_), b"simpleType") => {
+                Frame::SimpleType(XsdSimpleType { name: XsdParser::get_attribute(e, "name", reader), ..Default::default() })
+            },
+            (Frame::Schema(_), b"group") => {
+                Frame::Group(XsdGroup { name: XsdParser::get_attribute(e, "name", reader).unwrap_or_default(), ..Default::default() })
+            },
+            (Frame::Schema(_), b"attributeGroup") => {
+                Frame::AttributeGroup(XsdAttributeGroup { name: XsdParser::get_attribute(e, "name", reader).unwrap_or_default(), ..Default::default() })
+            },
+            (Frame::Schema(_), b"annotation") => Frame::Annotation(Vec::new(), Vec::new()),
+            (Frame::Schema(_), name) => {
+                log::debug!("Unhandled top-level element: {:?}", String::from_utf8_lossy(name));
+                Frame::Skip
+            },
+
+            (Frame::Element(_), b"key" | b"keyref" | b"unique") => Frame::IdentityConstraint(XsdParser::empty_identity_constraint(e, reader)),
+            (Frame::IdentityConstraint(constraint), _) => {
+                XsdParser::record_xpath(e, constraint, reader);
+                Frame::Skip
+            },
+
+            (Frame::ComplexType(..) | Frame::Group(_) | Frame::Compositor(_), b"sequence" | b"choice" | b"all") => {
+                Frame::Compositor(XsdParser::empty_compositor(e, reader))
+            },
+            (Frame::Compositor(compositor), b"group") => {
+                // The annotation of a reference or wildcard is skipped
+                compositor.particles.extend(XsdParser::group_ref(e, reader).map(XsdParticle::Group));
+                Frame::Skip
+            },
+            (Frame::Compositor(compositor), b"any") => {
+                compositor.particles.push(XsdParticle::Any(XsdParser::wildcard(e, reader)));
+                Frame::Skip
+            },
+
+            // The extension or restriction of a `simpleContent` or `complexContent` records the
+            // base type; the content and attributes in it are the complex type's
+            (Frame::ComplexType(_, content_model), b"simpleContent" | b"complexContent") => {
+                *content_model = Some(local_name.as_ref() == b"simpleContent");
+                Frame::Transparent
+            },
+            (Frame::ComplexType(complex_type, Some(simple_content)), b"extension" | b"restriction") => {
+                if complex_type.derivation.is_none() {
+                    complex_type.derivation = XsdParser::parse_derivation(e, *simple_content, reader);
+                }
+                Frame::Transparent
+            },
+            (Frame::ComplexType(complex_type, _), b"group") => {
+                complex_type.content = XsdParser::group_content(e, reader);
+                Frame::Skip
+            },
+            (Frame::ComplexType(complex_type, _), b"attributeGroup") => {
+                complex_type.attribute_groups.extend(XsdParser::get_attribute(e, "ref", reader));
+                Frame::Skip
+            },
+            (Frame::ComplexType(complex_type, _), b"anyAttribute") => {
+                // Its annotation does not document the type
+                complex_type.any_attribute = Some(XsdParser::wildcard(e, reader));
+                Frame::Skip
+            },
+            (Frame::ComplexType(..) | Frame::AttributeGroup(_), b"attribute") => Frame::Attribute(XsdParser::empty_attribute(e, reader)),
+            (Frame::AttributeGroup(attribute_group), b"attributeGroup") => {
+                attribute_group.attribute_groups.extend(XsdParser::get_attribute(e, "ref", reader));
+                Frame::Skip
+            },
+            (Frame::AttributeGroup(attribute_group), b"anyAttribute") => {
+                attribute_group.any_attribute = Some(XsdParser::wildcard(e, reader));
+                Frame::Skip
+            },
+
+            (Frame::SimpleType(_), b"restriction") => {
+                Frame::Restriction(XsdRestriction { base: XsdParser::get_attribute(e, "base", reader).unwrap_or_default(), ..Default::default() })
+            },
+            (Frame::SimpleType(simple_type), b"list" | b"union") => {
+                XsdParser::parse_member_types(e, simple_type, reader);
+                Frame::Skip
+            },
+            (Frame::Restriction(_), b"enumeration") => Frame::Enumeration(XsdParser::get_attribute(e, "value", reader), None),
+            (Frame::Restriction(restriction), _) => {
+                XsdParser::record_facet(e, restriction, reader);
+                Frame::Skip
+            },
+
+            (Frame::Element(_) | Frame::ComplexType(..) | Frame::Group(_) | Frame::AttributeGroup(_)
+                | Frame::Attribute(_) | Frame::SimpleType(_) | Frame::Enumeration(..), b"annotation") => {
+                Frame::Annotation(Vec::new(), Vec::new())
+            },
+            (Frame::Annotation(..), b"documentation") => Frame::Documentation(Vec::new()),
+            (Frame::Annotation(..), b"appinfo") => Frame::AppInfo(XsdParser::get_attribute(e, "source", reader), Vec::new()),
+            // Markup in documentation only contributes its text
+            (Frame::Documentation(_) | Frame::AppInfo(..), _) => Frame::Transparent,
+
+            (Frame::ComplexType(..), name) => {
+                log::debug!("Unhandled start element: {:?}", String::from_utf8_lossy(name));
+                Frame::Skip
+            },
+            _ => Frame::Skip,
+        }
+    }
+
+    /// Attaches what the tag that was just closed was parsed into to the frame it belongs to.
+    fn close(&mut self, frame: Frame) {
+        match (self.context(), frame) {
+            (Frame::Schema(schema), Frame::Element(element)) => schema.elements.push(*element),
+            (Frame::Schema(schema), Frame::ComplexType(complex_type, _)) => schema.complex_types.push(complex_type),
+            (Frame::Schema(schema), Frame::SimpleType(simple_type)) => schema.simple_types.push(simple_type),
+            (Frame::Schema(schema), Frame::Group(group)) => schema.groups.push(group),
+            (Frame::Schema(schema), Frame::AttributeGroup(attribute_group)) => schema.attribute_groups.push(attribute_group),
+            (Frame::Schema(schema), Frame::Annotation(documentation, _)) => {
+                schema.comment = XsdParser::join_documentation(schema.comment.take().into_iter().chain(documentation).collect());
+            },
+
+            (Frame::Element(element), Frame::ComplexType(complex_type, _)) => element.complex_type = Some(complex_type),
+            (Frame::Element(element), Frame::SimpleType(simple_type)) => element.simple_type = Some(simple_type),
+            (Frame::Element(element), Frame::IdentityConstraint(constraint)) => element.identity_constraints.push(constraint),
+            (Frame::Element(element), Frame::Annotation(documentation, appinfo)) => {
+                element.comment = XsdParser::join_documentation(documentation);
+                element.appinfo = appinfo;
+            },
+
+            (Frame::ComplexType(complex_type, _), Frame::Compositor(compositor)) => complex_type.content = Some(compositor),
+            (Frame::ComplexType(complex_type, _), Frame::Attribute(attribute)) => complex_type.attributes.push(attribute),
+            (Frame::ComplexType(complex_type, _), Frame::Annotation(documentation, appinfo)) => {
+                // Only the first annotation documents the type itself
+                if complex_type.comment.is_none() {
+                    complex_type.comment = XsdParser::join_documentation(documentation);
+                }
+                complex_type.appinfo.extend(appinfo);
+            },
+
+            (Frame::Compositor(parent), Frame::Compositor(compositor)) => parent.particles.push(XsdParticle::Compositor(compositor)),
+            (Frame::Compositor(compositor), Frame::Element(element)) => compositor.particles.push(XsdParticle::Element(element)),
+
+            (Frame::Group(group), Frame::Compositor(compositor)) => group.content = Some(compositor),
+            (Frame::Group(group), Frame::Annotation(documentation, appinfo)) => {
+                group.comment = XsdParser::join_documentation(documentation);
+                group.appinfo = appinfo;
+            },
+
+            (Frame::AttributeGroup(attribute_group), Frame::Attribute(attribute)) => attribute_group.attributes.push(attribute),
+            (Frame::AttributeGroup(attribute_group), Frame::Annotation(documentation, appinfo)) => {
+                attribute_group.comment = XsdParser::join_documentation(documentation);
+                attribute_group.appinfo = appinfo;
+            },
+
+            (Frame::Attribute(attribute), Frame::SimpleType(simple_type)) => attribute.simple_type = Some(simple_type),
+            (Frame::Attribute(attribute), Frame::Annotation(documentation, appinfo)) => {
+                attribute.comment = XsdParser::join_documentation(documentation);
+                attribute.appinfo = appinfo;
+            },
+
+            (Frame::SimpleType(simple_type), Frame::Restriction(restriction)) => simple_type.restriction = Some(restriction),
+            // Only the first annotation documents the type itself
+            (Frame::SimpleType(simple_type), Frame::Annotation(documentation, appinfo))
+                if simple_type.comment.is_none() && simple_type.appinfo.is_empty() => {
+                simple_type.comment = XsdParser::join_documentation(documentation);
+                simple_type.appinfo = appinfo;
+            },
+
+            (Frame::Restriction(restriction), Frame::Enumeration(Some(value), comment)) => {
+                if let Some(comment) = comment {
+                    restriction.enumeration_comments.insert(value.clone(), comment);
+                }
+                restriction.enumeration.push(value);
+            },
+            (Frame::Enumeration(_, comment), Frame::Annotation(documentation, _)) => *comment = XsdParser::join_documentation(documentation),
+
+            (Frame::Annotation(documentation, _), Frame::Documentation(pieces)) => documentation.push(XsdParser::dedent(&pieces.join(" "))),
+            (Frame::Annotation(_, appinfo), Frame::AppInfo(source, pieces)) => {
+                // Appinfo is kept on one line
+                let content = pieces.join(" ").split_whitespace().collect::<Vec<_>>().join(" ");
+                if source.is_some() || !content.is_empty() {
+                    appinfo.push(XsdAppInfo { source, content });
+                }
+            },
+            _ => {}
+        }
+    }
 }
 
+/// An error in an XSD document, located by byte offset in the input.
 #[derive(Debug)]
-struct XsdParsingError {
-    message: String,
-    context: String,
+pub struct XsdParsingError {
+    pub message: String,
+    /// The XSD construct being parsed when the error occurred, e.g. `<complexType>`.
+    pub context: String,
+    pub span: Span,
+    pub help: Option<String>,
 }
 
 impl std::fmt::Display for XsdParsingError {
@@ -374,4 +645,16 @@ impl std::fmt::Display for XsdParsingError {
     }
 }
 
-impl std::error::Error for XsdParsingError {}
\ No newline at end of file
+impl std::error::Error for XsdParsingError {}
+
+impl ToDiagnostic for XsdParsingError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = Diagnostic::error(&self.message)
+            .with_span(self.span)
+            .with_label(format!("while parsing {}", self.context));
+        if let Some(help) = &self.help {
+            diagnostic = diagnostic.with_help(help);
+        }
+        diagnostic
+    }
+}
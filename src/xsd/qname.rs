@@ -0,0 +1,158 @@
+use super::types::{XsdAttribute, XsdAttributeGroup, XsdComplexType, XsdCompositor, XsdElement, XsdGroup, XsdParticle, XsdSchema, XsdSimpleType};
+
+/// The namespace of the XML Schema built-in types.
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+/// Local names of the built-in simple types, and `anyType`.
+pub const XSD_BUILTIN_TYPES: &[&str] = &[
+    "anyType", "anySimpleType", "anyAtomicType",
+    "string", "normalizedString", "token", "language", "Name", "NCName", "NMTOKEN", "NMTOKENS",
+    "ID", "IDREF", "IDREFS", "ENTITY", "ENTITIES", "QName", "NOTATION", "anyURI",
+    "boolean", "decimal", "float", "double",
+    "integer", "nonPositiveInteger", "negativeInteger", "nonNegativeInteger", "positiveInteger",
+    "long", "int", "short", "byte", "unsignedLong", "unsignedInt", "unsignedShort", "unsignedByte",
+    "dateTime", "dateTimeStamp", "date", "time", "duration", "dayTimeDuration", "yearMonthDuration",
+    "gYear", "gYearMonth", "gMonth", "gMonthDay", "gDay",
+    "base64Binary", "hexBinary",
+];
+
+/// Splits a name written as `{namespace}local` into its namespace and local name. Names that
+/// were not expanded have no namespace.
+pub fn split_expanded_name(name: &str) -> (Option<&str>, &str) {
+    match name.strip_prefix('{').and_then(|rest| rest.split_once('}')) {
+        Some((namespace, local)) => (Some(namespace), local),
+        None => (None, name),
+    }
+}
+
+impl XsdSchema {
+    /// Expands a QName written in this document into `{namespace}local`, using the prefixes
+    /// the document declares.
+    ///
+    /// Unprefixed names are in the default namespace. Documents often leave it undeclared and
+    /// still write built-in types without a prefix, so those are taken as built-in types and
+    /// other unprefixed names as components of the target namespace. Names with an undeclared
+    /// prefix are left as their local name.
+    pub fn expand_qname(&self, qname: &str) -> String {
+        let (prefix, local) = qname.split_once(':').unwrap_or(("", qname));
+        let namespace = match self.namespaces.get(prefix) {
+            Some(namespace) => Some(namespace.as_str()),
+            None if prefix.is_empty() && XSD_BUILTIN_TYPES.contains(&local) => Some(XSD_NAMESPACE),
+            None if prefix.is_empty() => self.target_namespace.as_deref(),
+            None => {
+                log::warn!("Prefix `{}` of `{}` is not declared", prefix, qname);
+                None
+            },
+        };
+        match namespace {
+            Some(namespace) => format!("{{{}}}{}", namespace, local),
+            None => local.to_string(),
+        }
+    }
+
+    /// Expands every type, element and group reference in the document with
+    /// [`XsdSchema::expand_qname`], so that references read the same whichever document of a
+    /// schema set they come from.
+    pub fn expand_type_names(&mut self) {
+        let mut elements = std::mem::take(&mut self.elements);
+        let mut complex_types = std::mem::take(&mut self.complex_types);
+        let mut simple_types = std::mem::take(&mut self.simple_types);
+        let mut groups = std::mem::take(&mut self.groups);
+        let mut attribute_groups = std::mem::take(&mut self.attribute_groups);
+        elements.iter_mut().for_each(|element| self.expand_element(element));
+        complex_types.iter_mut().for_each(|complex_type| self.expand_complex_type(complex_type));
+        simple_types.iter_mut().for_each(|simple_type| self.expand_simple_type(simple_type));
+        groups.iter_mut().for_each(|group| self.expand_group(group));
+        attribute_groups.iter_mut().for_each(|attribute_group| self.expand_attribute_group(attribute_group));
+        self.elements = elements;
+        self.complex_types = complex_types;
+        self.simple_types = simple_types;
+        self.groups = groups;
+        self.attribute_groups = attribute_groups;
+    }
+
+    fn expand(&self, name: &mut String) {
+        if !name.is_empty() {
+            *name = self.expand_qname(name);
+        }
+    }
+
+    fn expand_element(&self, element: &mut XsdElement) {
+        if let Some(type_name) = &mut element.type_name {
+            self.expand(type_name);
+        }
+        if let Some(reference) = &mut element.reference {
+            self.expand(reference);
+        }
+        if let Some(head) = &mut element.substitution_group {
+            self.expand(head);
+        }
+        if let Some(complex_type) = &mut element.complex_type {
+            self.expand_complex_type(complex_type);
+        }
+        if let Some(simple_type) = &mut element.simple_type {
+            self.expand_simple_type(simple_type);
+        }
+        for constraint in &mut element.identity_constraints {
+            if let Some(refer) = &mut constraint.refer {
+                self.expand(refer);
+            }
+        }
+    }
+
+    fn expand_complex_type(&self, complex_type: &mut XsdComplexType) {
+        if let Some(content) = &mut complex_type.content {
+            self.expand_compositor(content);
+        }
+        for attribute in &mut complex_type.attributes {
+            self.expand_attribute(attribute);
+        }
+        complex_type.attribute_groups.iter_mut().for_each(|reference| self.expand(reference));
+        if let Some(derivation) = &mut complex_type.derivation {
+            self.expand(&mut derivation.base);
+        }
+    }
+
+    fn expand_compositor(&self, compositor: &mut XsdCompositor) {
+        for particle in &mut compositor.particles {
+            match particle {
+                XsdParticle::Element(element) => self.expand_element(element),
+                XsdParticle::Compositor(nested) => self.expand_compositor(nested),
+                XsdParticle::Group(group) => self.expand(&mut group.reference),
+                XsdParticle::Any(_) => {},
+            }
+        }
+    }
+
+    fn expand_group(&self, group: &mut XsdGroup) {
+        if let Some(content) = &mut group.content {
+            self.expand_compositor(content);
+        }
+    }
+
+    fn expand_attribute_group(&self, attribute_group: &mut XsdAttributeGroup) {
+        for attribute in &mut attribute_group.attributes {
+            self.expand_attribute(attribute);
+        }
+        attribute_group.attribute_groups.iter_mut().for_each(|reference| self.expand(reference));
+    }
+
+    fn expand_attribute(&self, attribute: &mut XsdAttribute) {
+        self.expand(&mut attribute.type_name);
+        if let Some(simple_type) = &mut attribute.simple_type {
+            self.expand_simple_type(simple_type);
+        }
+    }
+
+    fn expand_simple_type(&self, simple_type: &mut XsdSimpleType) {
+        if let Some(restriction) = &mut simple_type.restriction {
+            self.expand(&mut restriction.base);
+        }
+        if let Some(item_type) = &mut simple_type.list {
+            self.expand(item_type);
+        }
+        for member in simple_type.union.iter_mut().flatten() {
+            self.expand(member);
+        }
+    }
+}
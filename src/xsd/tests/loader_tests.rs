@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::xsd::{XsdLoadError, XsdLoader};
+
+    /// Writes `files` into a fresh directory and returns its path.
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("schemata-loader-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (file, content) in files {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_follows_includes_and_imports() {
+        let dir = write_files("follow", &[
+            ("order.xsd", r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+                <xs:include schemaLocation="common/address.xsd"/>
+                <xs:import namespace="people" schemaLocation="http://example.com/people.xsd"/>
+                <xs:complexType name="Order"/>
+            </xs:schema>"#),
+            ("common/address.xsd", r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+                <xs:include schemaLocation="../order.xsd"/>
+                <xs:complexType name="Address"/>
+            </xs:schema>"#),
+            ("cache/people.xsd", r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="people">
+                <xs:import namespace="shop" schemaLocation="../order.xsd"/>
+                <xs:complexType name="Person"/>
+            </xs:schema>"#),
+        ]);
+
+        let loader = XsdLoader::new().with_catalog_entry("http://example.com/people.xsd", dir.join("cache/people.xsd"));
+        let schemas = loader.load(&dir.join("order.xsd")).expect("Failed to load schemas");
+
+        let loaded: Vec<(Option<&str>, &str)> = schemas.iter()
+            .map(|s| (s.target_namespace.as_deref(), s.complex_types[0].name.as_deref().unwrap()))
+            .collect();
+        // The included document takes the namespace of the one including it; cycles are loaded once
+        assert_eq!(loaded, vec![(Some("shop"), "Order"), (Some("shop"), "Address"), (Some("people"), "Person")]);
+
+        // Without a catalog entry the remote import is skipped
+        assert_eq!(XsdLoader::new().load(&dir.join("order.xsd")).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_errors_name_the_included_document() {
+        let dir = write_files("errors", &[
+            ("root.xsd", r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+                <xs:include schemaLocation="broken.xsd"/>
+                <xs:include schemaLocation="missing.xsd"/>
+            </xs:schema>"#),
+            ("broken.xsd", r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"><xs:complexType name="A">"#),
+        ]);
+
+        let error = XsdLoader::new().load(&dir.join("root.xsd")).unwrap_err();
+        let error = error.downcast::<XsdLoadError>().expect("Expected an error in the included document");
+        assert!(error.file.ends_with("broken.xsd"));
+        assert!(error.source.contains("name=\"A\""));
+
+        std::fs::write(dir.join("broken.xsd"), r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"/>"#).unwrap();
+        let error = XsdLoader::new().load(&dir.join("root.xsd")).unwrap_err();
+        assert!(error.to_string().contains("referred to as missing.xsd"), "{}", error);
+    }
+}
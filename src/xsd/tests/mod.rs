@@ -1,2 +1,6 @@
 #[cfg(test)]
-mod parser_tests;
\ No newline at end of file
+mod loader_tests;
+#[cfg(test)]
+mod parser_tests;
+#[cfg(test)]
+mod qname_tests;
\ No newline at end of file
@@ -1,6 +1,5 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::xsd::XsdParser;
     use quick_xml::events::Event;
     use quick_xml::reader::Reader;
@@ -39,20 +38,40 @@ mod tests {
 
     #[test]
     fn test_parse_element() {
-        let xml = r#"<xs:element xmlns:xs="http://www.w3.org/2001/XMLSchema" name="test" type="xs:string" minOccurs="0" maxOccurs="unbounded" />"#;
+        let xml = r#"<xs:sequence xmlns:xs="http://www.w3.org/2001/XMLSchema"><xs:element name="test" type="xs:string" minOccurs="0" maxOccurs="unbounded" /><xs:element name="sibling"><xs:annotation><xs:documentation>Next</xs:documentation></xs:annotation></xs:element></xs:sequence>"#;
         let mut reader = create_reader(xml);
         let mut buf = Vec::new();
+        reader.read_event_into(&mut buf).expect("Failed to read event");
 
-        let event = reader.read_event_into(&mut buf).expect("Failed to read event");
+        let event = reader.read_event_into(&mut buf).expect("Failed to read event").into_owned();
+        assert!(matches!(event, Event::Empty(_)));
+        let element = XsdParser::parse_element(&event, &mut reader).expect("Failed to parse element");
+        assert_eq!(element.name, "test");
+        assert_eq!(element.type_name, Some("xs:string".to_string()));
+        assert_eq!(element.min_occurs, Some("0".to_string()));
+        assert_eq!(element.max_occurs, Some("unbounded".to_string()));
 
-        match event {
-            Event::Start(e) | Event::Empty(e) => {
-                let element =
-                    XsdParser::parse_element(&e, &mut reader).expect("Failed to parse element");
-                assert_eq!(element.name, "test");
-                assert_eq!(element.type_name, Some("xs:string".to_string()));
+        // Nothing after a self-closing tag is read, so the sibling is parsed whole
+        let event = reader.read_event_into(&mut buf).expect("Failed to read event").into_owned();
+        assert!(matches!(event, Event::Start(_)));
+        let sibling = XsdParser::parse_element(&event, &mut reader).expect("Failed to parse element");
+        assert_eq!(sibling.name, "sibling");
+        assert_eq!(sibling.comment.as_deref(), Some("Next"));
+        assert!(matches!(reader.read_event_into(&mut buf), Ok(Event::End(_))));
+    }
+
+    #[test]
+    fn test_parse_element_reference() {
+        let xml = r#"<xs:element xmlns:xs="http://www.w3.org/2001/XMLSchema" ref="tns:order" minOccurs="0" />"#;
+        let mut reader = create_reader(xml);
+        let mut buf = Vec::new();
+
+        match reader.read_event_into(&mut buf).expect("Failed to read event") {
+            Event::Empty(e) => {
+                let element = XsdParser::empty_element(&e, &reader);
+                assert_eq!(element.name, "");
+                assert_eq!(element.reference, Some("tns:order".to_string()));
                 assert_eq!(element.min_occurs, Some("0".to_string()));
-                assert_eq!(element.max_occurs, Some("unbounded".to_string()));
             }
             _ => panic!("Unexpected event type"),
         }
@@ -76,30 +95,32 @@ mod tests {
         let event = reader.read_event_into(&mut buf).expect("Failed to read event");
 
         match event {
-            Event::Start(e) => {
-                let complex_type = XsdParser::parse_complex_type(&e, &mut reader)
-                    .expect("Failed to parse complex type");
+            Event::Start(_) => {
+                let complex_type = XsdParser::parse_component(&event, &mut reader)
+                    .expect("Failed to parse complex type")
+                    .complex_types.remove(0);
 
                 // Debug print to understand what's happening
-                println!("Sequence length: {}", complex_type.sequence.len());
+                println!("Sequence length: {}", complex_type.elements().len());
                 println!("Attributes length: {}", complex_type.attributes.len());
 
                 assert_eq!(complex_type.name, Some("TestType".to_string()));
 
                 // Verify sequence
+                let sequence = complex_type.elements();
                 assert_eq!(
-                    complex_type.sequence.len(),
+                    sequence.len(),
                     2,
                     "Sequence should contain 2 elements"
                 );
-                assert_eq!(complex_type.sequence[0].name, "child1");
+                assert_eq!(sequence[0].name, "child1");
                 assert_eq!(
-                    complex_type.sequence[0].type_name,
+                    sequence[0].type_name,
                     Some("xs:string".to_string())
                 );
-                assert_eq!(complex_type.sequence[1].name, "child2");
+                assert_eq!(sequence[1].name, "child2");
                 assert_eq!(
-                    complex_type.sequence[1].type_name,
+                    sequence[1].type_name,
                     Some("xs:int".to_string())
                 );
 
@@ -113,6 +134,296 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_compositor_tree() {
+        use crate::xsd::types::{XsdCompositorKind, XsdParticle};
+
+        let xml = r#"
+        <xs:complexType xmlns:xs="http://www.w3.org/2001/XMLSchema" name="Payment">
+            <xs:sequence>
+                <xs:element name="amount" type="xs:decimal"/>
+                <xs:choice minOccurs="0">
+                    <xs:element name="card" type="xs:string"/>
+                    <xs:sequence>
+                        <xs:element name="iban" type="xs:string"/>
+                        <xs:element name="bic" type="xs:string"/>
+                    </xs:sequence>
+                </xs:choice>
+                <xs:all/>
+            </xs:sequence>
+        </xs:complexType>
+        "#.trim();
+        let mut reader = create_reader(xml);
+        let mut buf = Vec::new();
+
+        let Ok(event @ Event::Start(_)) = reader.read_event_into(&mut buf) else {
+            panic!("Unexpected event type");
+        };
+        let complex_type = XsdParser::parse_component(&event, &mut reader).expect("Failed to parse complex type").complex_types.remove(0);
+        let content = complex_type.content.as_ref().expect("Content should be parsed");
+
+        assert_eq!(content.kind, XsdCompositorKind::Sequence);
+        assert_eq!(content.particles.len(), 3);
+        let XsdParticle::Compositor(choice) = &content.particles[1] else {
+            panic!("Expected a choice");
+        };
+        assert_eq!(choice.kind, XsdCompositorKind::Choice);
+        assert_eq!(choice.min_occurs.as_deref(), Some("0"));
+        assert!(matches!(&choice.particles[1], XsdParticle::Compositor(c) if c.particles.len() == 2));
+        assert!(matches!(&content.particles[2], XsdParticle::Compositor(c) if c.kind == XsdCompositorKind::All));
+
+        let names: Vec<&str> = complex_type.elements().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["amount", "card", "iban", "bic"]);
+    }
+
+    #[test]
+    fn test_parse_derived_complex_types() {
+        use crate::xsd::types::XsdDerivationMethod;
+
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:complexType name="Manager">
+                <xs:complexContent>
+                    <xs:extension base="tns:Employee">
+                        <xs:sequence>
+                            <xs:element name="reports" type="xs:int"/>
+                        </xs:sequence>
+                        <xs:attribute name="level">
+                            <xs:simpleType>
+                                <xs:restriction base="xs:int"/>
+                            </xs:simpleType>
+                        </xs:attribute>
+                    </xs:extension>
+                </xs:complexContent>
+            </xs:complexType>
+            <xs:complexType name="Price">
+                <xs:simpleContent>
+                    <xs:restriction base="xs:decimal"/>
+                </xs:simpleContent>
+            </xs:complexType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+
+        let manager = &schema.complex_types[0];
+        let derivation = manager.derivation.as_ref().expect("Derivation should be parsed");
+        assert_eq!(derivation.base, "tns:Employee");
+        assert_eq!(derivation.method, XsdDerivationMethod::Extension);
+        assert!(!derivation.simple_content);
+        assert_eq!(manager.elements()[0].name, "reports");
+        assert_eq!(manager.attributes[0].name, "level");
+
+        let price = schema.complex_types[1].derivation.as_ref().expect("Derivation should be parsed");
+        assert_eq!(price.base, "xs:decimal");
+        assert_eq!(price.method, XsdDerivationMethod::Restriction);
+        assert!(price.simple_content);
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        use crate::xsd::types::{XsdCompositorKind, XsdParticle};
+
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:group name="Contact">
+                <xs:annotation><xs:documentation>Ways to reach someone</xs:documentation></xs:annotation>
+                <xs:choice>
+                    <xs:element name="email" type="xs:string"/>
+                    <xs:element name="phone" type="xs:string"/>
+                </xs:choice>
+            </xs:group>
+            <xs:attributeGroup name="Audit">
+                <xs:attribute name="created" type="xs:dateTime"/>
+                <xs:attributeGroup ref="tns:Source">
+                    <xs:annotation><xs:documentation>Where it came from</xs:documentation></xs:annotation>
+                </xs:attributeGroup>
+            </xs:attributeGroup>
+            <xs:complexType name="Customer">
+                <xs:sequence>
+                    <xs:element name="name" type="xs:string"/>
+                    <xs:group ref="tns:Contact" minOccurs="0">
+                        <xs:annotation><xs:documentation>Optional</xs:documentation></xs:annotation>
+                    </xs:group>
+                    <xs:element name="note" type="xs:string"/>
+                </xs:sequence>
+                <xs:attributeGroup ref="tns:Audit"/>
+            </xs:complexType>
+            <xs:complexType name="Supplier">
+                <xs:group ref="tns:Contact"/>
+            </xs:complexType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+
+        let contact = &schema.groups[0];
+        assert_eq!(contact.name, "Contact");
+        assert_eq!(contact.comment.as_deref(), Some("Ways to reach someone"));
+        assert_eq!(contact.content.as_ref().map(|content| content.kind), Some(XsdCompositorKind::Choice));
+        let audit = &schema.attribute_groups[0];
+        assert_eq!(audit.attributes[0].name, "created");
+        assert_eq!(audit.attribute_groups, vec!["tns:Source"]);
+
+        let customer = &schema.complex_types[0];
+        let particles = &customer.content.as_ref().unwrap().particles;
+        assert_eq!(particles.len(), 3);
+        match &particles[1] {
+            XsdParticle::Group(reference) => {
+                assert_eq!(reference.reference, "tns:Contact");
+                assert_eq!(reference.min_occurs.as_deref(), Some("0"));
+            },
+            particle => panic!("Expected a group reference, found {:?}", particle),
+        }
+        assert_eq!(customer.elements().len(), 2);
+        assert_eq!(customer.attribute_groups, vec!["tns:Audit"]);
+        // A reference alone is the type's content
+        let supplier = schema.complex_types[1].content.as_ref().unwrap();
+        assert!(matches!(&supplier.particles[..], [XsdParticle::Group(reference)] if reference.reference == "tns:Contact"));
+    }
+
+    #[test]
+    fn test_parse_substitution_groups_and_wildcards() {
+        use crate::xsd::types::XsdParticle;
+
+        let xsd = r###"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:element name="item" type="tns:Item" abstract="true"/>
+            <xs:element name="book" type="tns:Book" substitutionGroup="tns:item" nillable="true"/>
+            <xs:complexType name="Envelope">
+                <xs:sequence>
+                    <xs:element name="id" type="xs:string"/>
+                    <xs:any namespace="##other" processContents="lax" minOccurs="0">
+                        <xs:annotation><xs:documentation>Extensions</xs:documentation></xs:annotation>
+                    </xs:any>
+                </xs:sequence>
+                <xs:anyAttribute processContents="skip">
+                    <xs:annotation><xs:documentation>Any attribute</xs:documentation></xs:annotation>
+                </xs:anyAttribute>
+            </xs:complexType>
+            <xs:attributeGroup name="Open">
+                <xs:anyAttribute/>
+            </xs:attributeGroup>
+        </xs:schema>
+        "###;
+        let mut schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        schema.expand_type_names();
+
+        assert!(schema.elements[0].is_abstract);
+        assert!(!schema.elements[0].nillable);
+        assert_eq!(schema.elements[1].substitution_group.as_deref(), Some("{shop}item"));
+        assert!(schema.elements[1].nillable);
+
+        let envelope = &schema.complex_types[0];
+        match &envelope.content.as_ref().unwrap().particles[1] {
+            XsdParticle::Any(wildcard) => {
+                assert_eq!(wildcard.namespace.as_deref(), Some("##other"));
+                assert_eq!(wildcard.process_contents.as_deref(), Some("lax"));
+                assert_eq!(wildcard.min_occurs.as_deref(), Some("0"));
+            },
+            particle => panic!("Expected a wildcard, found {:?}", particle),
+        }
+        assert_eq!(envelope.elements().len(), 1);
+        assert_eq!(envelope.any_attribute.as_ref().and_then(|w| w.process_contents.as_deref()), Some("skip"));
+        // The annotations of the wildcards do not document the type
+        assert_eq!(envelope.comment, None);
+        assert!(schema.attribute_groups[0].any_attribute.is_some());
+    }
+
+    #[test]
+    fn test_parse_identity_constraints() {
+        use crate::xsd::types::XsdIdentityConstraintKind;
+
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:tns="shop" targetNamespace="shop">
+            <xs:element name="catalog" type="tns:Catalog">
+                <xs:key name="productKey">
+                    <xs:annotation><xs:documentation>Products by SKU</xs:documentation></xs:annotation>
+                    <xs:selector xpath="tns:product"/>
+                    <xs:field xpath="@sku"/>
+                </xs:key>
+                <xs:keyref name="offerProduct" refer="tns:productKey">
+                    <xs:selector xpath="tns:offer"/>
+                    <xs:field xpath="tns:sku"/>
+                </xs:keyref>
+                <xs:unique name="productName">
+                    <xs:selector xpath="tns:product"/>
+                    <xs:field xpath="tns:name"/>
+                    <xs:field xpath="tns:brand"/>
+                </xs:unique>
+            </xs:element>
+        </xs:schema>
+        "#;
+        let mut schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        schema.expand_type_names();
+
+        let constraints = &schema.elements[0].identity_constraints;
+        assert_eq!(constraints.len(), 3);
+        assert_eq!(constraints[0].kind, XsdIdentityConstraintKind::Key);
+        assert_eq!(constraints[0].name, "productKey");
+        assert_eq!(constraints[0].selector, "tns:product");
+        assert_eq!(constraints[0].fields, vec!["@sku"]);
+        assert_eq!(constraints[1].kind, XsdIdentityConstraintKind::KeyRef);
+        assert_eq!(constraints[1].refer.as_deref(), Some("{shop}productKey"));
+        assert_eq!(constraints[2].kind, XsdIdentityConstraintKind::Unique);
+        assert_eq!(constraints[2].fields, vec!["tns:name", "tns:brand"]);
+    }
+
+    #[test]
+    fn test_parse_annotations() {
+        let xsd = r#"<?xml version="1.0"?>
+        <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="shop">
+            <xs:complexType name="Order">
+                <xs:annotation>
+                    <xs:documentation>
+                        An order.
+
+                        Lines keep their
+                          relative indentation &amp; entities.
+                    </xs:documentation>
+                    <xs:appinfo source="urn:table">
+                        orders
+                    </xs:appinfo>
+                </xs:annotation>
+                <xs:sequence>
+                    <xs:element name="id" type="xs:string">
+                        <xs:annotation><xs:documentation><![CDATA[Primary <key>]]></xs:documentation></xs:annotation>
+                    </xs:element>
+                    <xs:element name="total" type="xs:decimal"/>
+                </xs:sequence>
+                <xs:attribute name="currency" type="xs:string">
+                    <xs:annotation><xs:documentation>ISO code</xs:documentation></xs:annotation>
+                </xs:attribute>
+            </xs:complexType>
+            <xs:simpleType name="Status">
+                <xs:annotation><xs:documentation>Order state</xs:documentation></xs:annotation>
+                <xs:restriction base="xs:string">
+                    <xs:enumeration value="OPEN">
+                        <xs:annotation><xs:documentation>Not paid</xs:documentation></xs:annotation>
+                    </xs:enumeration>
+                    <xs:enumeration value="PAID"/>
+                </xs:restriction>
+            </xs:simpleType>
+        </xs:schema>
+        "#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+
+        let order = &schema.complex_types[0];
+        assert_eq!(order.comment.as_deref(), Some("An order.\n\nLines keep their\n  relative indentation & entities."));
+        assert_eq!(order.appinfo[0].source.as_deref(), Some("urn:table"));
+        assert_eq!(order.appinfo[0].content, "orders");
+        let elements = order.elements();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].comment.as_deref(), Some("Primary <key>"));
+        assert_eq!(elements[1].comment, None);
+        assert_eq!(order.attributes[0].comment.as_deref(), Some("ISO code"));
+
+        let status = &schema.simple_types[0];
+        assert_eq!(status.comment.as_deref(), Some("Order state"));
+        let restriction = status.restriction.as_ref().unwrap();
+        assert_eq!(restriction.enumeration, vec!["OPEN", "PAID"]);
+        assert_eq!(restriction.enumeration_comments.get("OPEN").map(String::as_str), Some("Not paid"));
+        assert!(!restriction.enumeration_comments.contains_key("PAID"));
+    }
+
     #[test]
     fn test_parse_simple_type_with_complex_restriction() {
         let xml = r#"
@@ -129,8 +440,8 @@ mod tests {
         .trim();
         let mut reader = create_reader(xml);
         let mut buf = Vec::new();
-        if let Ok(Event::Start(e)) = reader.read_event_into(&mut buf) {
-            let simple_type = XsdParser::parse_simple_type(&e, &mut reader).unwrap();
+        if let Ok(event @ Event::Start(_)) = reader.read_event_into(&mut buf) {
+            let simple_type = XsdParser::parse_component(&event, &mut reader).unwrap().simple_types.remove(0);
             assert_eq!(simple_type.name, Some("TestEnum".to_string()));
             assert!(simple_type.restriction.is_some());
 
@@ -174,4 +485,274 @@ mod tests {
             Some(&"http://www.w3.org/2001/XMLSchema".to_string())
         );
     }
+
+    #[test]
+    fn test_unexpected_eof_diagnostic() {
+        use crate::diagnostics::ToDiagnostic;
+        use crate::xsd::XsdParsingError;
+
+        let xml = "<xs:schema xmlns:xs=\"http://www.w3.org/2001/XMLSchema\">\n  <xs:complexType name=\"Broken\">\n    <xs:attribute name=\"a\" type=\"xs:string\"/>\n";
+        let error = XsdParser::parse(BufReader::new(xml.as_bytes())).expect_err("Parsing should fail");
+        let error = error.downcast_ref::<XsdParsingError>().expect("Expected an XsdParsingError");
+        assert_eq!(error.context, "<complexType>");
+
+        let diagnostic = error.to_diagnostic().with_source("broken.xsd", xml);
+        assert_eq!(diagnostic.line, Some(2));
+        assert_eq!(diagnostic.column, Some(3));
+
+        let rendered = diagnostic.render(xml);
+        assert!(rendered.starts_with("error: Unexpected end of file while parsing <complexType>"), "{}", rendered);
+        assert!(rendered.contains("--> broken.xsd:2:3"), "{}", rendered);
+        assert!(rendered.contains("2 |   <xs:complexType name=\"Broken\">"), "{}", rendered);
+        assert!(rendered.contains("= help: check that this <complexType> has a matching closing tag"), "{}", rendered);
+    }
+
+    /// A xorshift generator, so that the random schemas of the property tests replay by seed.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+
+        fn chance(&mut self, percent: u64) -> bool {
+            self.below(100) < percent
+        }
+    }
+
+    /// A component of a random schema, which renders to XSD and describes where everything in
+    /// it belongs.
+    enum Node {
+        Element { name: String, comment: Option<String>, attributes: Vec<(String, bool)>, content: Option<Box<Node>>, key: bool },
+        Compositor(&'static str, Vec<Node>),
+        Any,
+        Group(String),
+    }
+
+    /// Names components uniquely and builds random trees of them.
+    struct Generator {
+        rng: Rng,
+        count: usize,
+    }
+
+    impl Generator {
+        fn name(&mut self, prefix: &str) -> String {
+            self.count += 1;
+            format!("{}{}", prefix, self.count)
+        }
+
+        fn element(&mut self, depth: usize) -> Node {
+            let name = self.name("e");
+            let comment = self.rng.chance(40).then(|| format!("About {}", name));
+            let attributes = (0..self.rng.below(3)).map(|_| (self.name("a"), self.rng.chance(50))).collect();
+            let content = (depth < 4 && self.rng.chance(50)).then(|| Box::new(self.compositor(depth + 1)));
+            Node::Element { name, comment, attributes, content, key: self.rng.chance(20) }
+        }
+
+        fn compositor(&mut self, depth: usize) -> Node {
+            let kind = ["sequence", "choice", "all"][self.rng.below(3) as usize];
+            let particles = (0..self.rng.below(5))
+                .map(|_| match self.rng.below(10) {
+                    0..=5 => self.element(depth),
+                    6 | 7 if depth < 4 => self.compositor(depth + 1),
+                    8 => Node::Any,
+                    _ => Node::Group(self.name("g")),
+                })
+                .collect();
+            Node::Compositor(kind, particles)
+        }
+
+        /// Content that is not a component of its parent, with components in it that must not
+        /// land anywhere.
+        fn noise(&mut self, xsd: &mut String) {
+            match self.rng.below(6) {
+                0 => xsd.push_str("<xs:annotation><xs:documentation>Ghost</xs:documentation></xs:annotation>"),
+                1 => xsd.push_str(r#"<xs:unknown><xs:element name="ghost"/><xs:sequence><xs:element name="ghost"/></xs:sequence></xs:unknown>"#),
+                2 => xsd.push_str("<!-- <xs:element name=\"ghost\"/> -->"),
+                _ => {},
+            }
+        }
+
+        fn render(&mut self, node: &Node, xsd: &mut String) {
+            match node {
+                Node::Element { name, comment: None, attributes, content: None, key: false } if attributes.is_empty() && self.rng.chance(50) => {
+                    xsd.push_str(&format!(r#"<xs:element name="{}" type="xs:string"/>"#, name));
+                },
+                Node::Element { name, comment, attributes, content, key } => {
+                    xsd.push_str(&format!(r#"<xs:element name="{}">"#, name));
+                    if let Some(comment) = comment {
+                        xsd.push_str(&format!("<xs:annotation><xs:documentation>{}</xs:documentation></xs:annotation>", comment));
+                    }
+                    if content.is_some() || !attributes.is_empty() {
+                        xsd.push_str("<xs:complexType>");
+                        if let Some(content) = content {
+                            self.render(content, xsd);
+                        }
+                        for (attribute, enumerated) in attributes {
+                            self.render_attribute(attribute, *enumerated, xsd);
+                        }
+                        xsd.push_str("</xs:complexType>");
+                    }
+                    if *key {
+                        xsd.push_str(&format!(r#"<xs:key name="{}Key"><xs:selector xpath="*"/><xs:field xpath="@id"/></xs:key>"#, name));
+                    }
+                    xsd.push_str("</xs:element>");
+                },
+                Node::Compositor(kind, particles) if particles.is_empty() && self.rng.chance(50) => {
+                    xsd.push_str(&format!("<xs:{}/>", kind));
+                },
+                Node::Compositor(kind, particles) => {
+                    xsd.push_str(&format!("<xs:{}>", kind));
+                    self.noise(xsd);
+                    for particle in particles {
+                        self.render(particle, xsd);
+                    }
+                    xsd.push_str(&format!("</xs:{}>", kind));
+                },
+                Node::Any if self.rng.chance(50) => xsd.push_str("<xs:any/>"),
+                Node::Any => xsd.push_str("<xs:any><xs:annotation><xs:documentation>Ghost</xs:documentation></xs:annotation></xs:any>"),
+                Node::Group(name) if self.rng.chance(50) => xsd.push_str(&format!(r#"<xs:group ref="{}"/>"#, name)),
+                Node::Group(name) => {
+                    xsd.push_str(&format!(r#"<xs:group ref="{}"><xs:annotation><xs:documentation>Ghost</xs:documentation></xs:annotation></xs:group>"#, name));
+                },
+            }
+        }
+
+        fn render_attribute(&mut self, name: &str, enumerated: bool, xsd: &mut String) {
+            if !enumerated {
+                xsd.push_str(&format!(r#"<xs:attribute name="{}" type="xs:string"/>"#, name));
+                return;
+            }
+            xsd.push_str(&format!(r#"<xs:attribute name="{}"><xs:simpleType><xs:restriction base="xs:string">"#, name));
+            xsd.push_str(r#"<xs:enumeration value="x"/><xs:enumeration value="y"><xs:annotation><xs:documentation>Why</xs:documentation></xs:annotation></xs:enumeration>"#);
+            xsd.push_str("</xs:restriction></xs:simpleType></xs:attribute>");
+        }
+    }
+
+    /// Where every component of a random schema belongs, as it was generated.
+    fn expected(node: &Node) -> String {
+        match node {
+            Node::Element { name, comment, attributes, content, key } => {
+                let mut description = name.clone();
+                if let Some(comment) = comment {
+                    description.push_str(&format!(" {:?}", comment));
+                }
+                for (attribute, enumerated) in attributes {
+                    description.push_str(&format!(" @{}{}", attribute, if *enumerated { "[x|y]" } else { "" }));
+                }
+                if *key {
+                    description.push_str(" key");
+                }
+                if let Some(content) = content {
+                    description.push_str(&format!(" {}", expected(content)));
+                }
+                description
+            },
+            Node::Compositor(kind, particles) => format!("{}({})", kind, particles.iter().map(expected).collect::<Vec<_>>().join(", ")),
+            Node::Any => "any".to_string(),
+            Node::Group(name) => format!("group {}", name),
+        }
+    }
+
+    /// Where every component of a parsed element landed, described like `expected` does.
+    fn described(element: &crate::xsd::types::XsdElement) -> String {
+        use crate::xsd::types::{XsdCompositor, XsdCompositorKind, XsdParticle};
+
+        fn described_compositor(compositor: &XsdCompositor) -> String {
+            let kind = match compositor.kind {
+                XsdCompositorKind::Sequence => "sequence",
+                XsdCompositorKind::Choice => "choice",
+                XsdCompositorKind::All => "all",
+            };
+            let particles: Vec<String> = compositor.particles.iter()
+                .map(|particle| match particle {
+                    XsdParticle::Element(element) => described(element),
+                    XsdParticle::Compositor(nested) => described_compositor(nested),
+                    XsdParticle::Any(_) => "any".to_string(),
+                    XsdParticle::Group(reference) => format!("group {}", reference.reference),
+                })
+                .collect();
+            format!("{}({})", kind, particles.join(", "))
+        }
+
+        let mut description = element.name.clone();
+        if let Some(comment) = &element.comment {
+            description.push_str(&format!(" {:?}", comment));
+        }
+        if let Some(complex_type) = &element.complex_type {
+            for attribute in &complex_type.attributes {
+                let enumeration = attribute.simple_type.as_ref()
+                    .and_then(|simple_type| simple_type.restriction.as_ref())
+                    .map(|restriction| format!("[{}]", restriction.enumeration.join("|")))
+                    .unwrap_or_default();
+                description.push_str(&format!(" @{}{}", attribute.name, enumeration));
+            }
+        }
+        if !element.identity_constraints.is_empty() {
+            description.push_str(" key");
+        }
+        if let Some(content) = element.complex_type.as_ref().and_then(|complex_type| complex_type.content.as_ref()) {
+            description.push_str(&format!(" {}", described_compositor(content)));
+        }
+        description
+    }
+
+    #[test]
+    fn test_random_schemas_nest_every_component_in_its_parent() {
+        for seed in 1..=300u64 {
+            let mut generator = Generator { rng: Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15)), count: 0 };
+            let elements: Vec<Node> = (0..1 + generator.rng.below(4)).map(|_| generator.element(0)).collect();
+
+            let mut xsd = String::from(r#"<?xml version="1.0"?><xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">"#);
+            for element in &elements {
+                generator.noise(&mut xsd);
+                generator.render(element, &mut xsd);
+            }
+            xsd.push_str("</xs:schema>");
+
+            let schema = XsdParser::parse(xsd.as_bytes()).unwrap_or_else(|error| panic!("seed {}: {}\n{}", seed, error, xsd));
+            let expected: Vec<String> = elements.iter().map(expected).collect();
+            let parsed: Vec<String> = schema.elements.iter().map(described).collect();
+            assert_eq!(parsed, expected, "seed {}:\n{}", seed, xsd);
+            assert!(schema.complex_types.is_empty() && schema.groups.is_empty(), "seed {}:\n{}", seed, xsd);
+        }
+    }
+
+    #[test]
+    fn test_deeply_nested_schemas_parse() {
+        use crate::xsd::types::XsdParticle;
+
+        let depth = 1000;
+        let xsd = format!(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"><xs:complexType name="Deep">{}<xs:element name="leaf"/>{}</xs:complexType><xs:element name="after"/></xs:schema>"#,
+            "<xs:sequence>".repeat(depth),
+            "</xs:sequence>".repeat(depth),
+        );
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+
+        let mut compositor = schema.complex_types[0].content.as_ref().expect("Content should be parsed");
+        for _ in 1..depth {
+            match &compositor.particles[..] {
+                [XsdParticle::Compositor(nested)] => compositor = nested,
+                particles => panic!("Expected one nested compositor, found {:?}", particles),
+            }
+        }
+        assert!(matches!(&compositor.particles[..], [XsdParticle::Element(leaf)] if leaf.name == "leaf"));
+        assert_eq!(schema.elements.len(), 1);
+        assert_eq!(schema.elements[0].name, "after");
+    }
+
+    #[test]
+    fn test_unexpected_eof_reports_the_innermost_open_tag() {
+        use crate::xsd::XsdParsingError;
+
+        let xml = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"><xs:element name="a"><xs:complexType><xs:sequence><xs:element name="b"/>"#;
+        let error = XsdParser::parse(xml.as_bytes()).expect_err("Parsing should fail");
+        let error = error.downcast_ref::<XsdParsingError>().expect("Expected an XsdParsingError");
+        assert_eq!(error.context, "<sequence>");
+        assert_eq!(&xml[error.span.start..error.span.end], "<xs:sequence>");
+    }
 }
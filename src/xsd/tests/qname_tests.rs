@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::xsd::qname::split_expanded_name;
+    use crate::xsd::types::XsdParticle;
+    use crate::xsd::XsdParser;
+
+    #[test]
+    fn test_expand_qname() {
+        let xsd = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:p="urn:people" targetNamespace="urn:shop"/>"#;
+        let schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+
+        assert_eq!(schema.expand_qname("xsd:int"), "{http://www.w3.org/2001/XMLSchema}int");
+        assert_eq!(schema.expand_qname("p:Person"), "{urn:people}Person");
+        // Without a default namespace, unprefixed built-in types are still built-in types
+        assert_eq!(schema.expand_qname("string"), "{http://www.w3.org/2001/XMLSchema}string");
+        assert_eq!(schema.expand_qname("Order"), "{urn:shop}Order");
+        assert_eq!(schema.expand_qname("q:Unknown"), "Unknown");
+
+        assert_eq!(split_expanded_name("{urn:people}Person"), (Some("urn:people"), "Person"));
+        assert_eq!(split_expanded_name("Person"), (None, "Person"));
+    }
+
+    #[test]
+    fn test_expand_type_names() {
+        let xsd = r#"<schema xmlns="http://www.w3.org/2001/XMLSchema" xmlns:tns="urn:shop" targetNamespace="urn:shop">
+            <complexType name="Order">
+                <sequence>
+                    <element name="id" type="long"/>
+                    <element name="address" type="tns:Address"/>
+                    <group ref="tns:Contact"/>
+                </sequence>
+                <attributeGroup ref="tns:Audit"/>
+                <attribute name="code">
+                    <simpleType><restriction base="token"/></simpleType>
+                </attribute>
+            </complexType>
+        </schema>"#;
+        let mut schema = XsdParser::parse(xsd.as_bytes()).expect("Failed to parse XSD");
+        schema.expand_type_names();
+
+        let order = &schema.complex_types[0];
+        let types: Vec<Option<&str>> = order.content.as_ref().unwrap().particles.iter()
+            .map(|particle| match particle {
+                XsdParticle::Element(element) => element.type_name.as_deref(),
+                XsdParticle::Compositor(_) | XsdParticle::Any(_) => None,
+                XsdParticle::Group(group) => Some(group.reference.as_str()),
+            })
+            .collect();
+        assert_eq!(types, vec![Some("{http://www.w3.org/2001/XMLSchema}long"), Some("{urn:shop}Address"), Some("{urn:shop}Contact")]);
+        assert_eq!(order.attribute_groups, vec!["{urn:shop}Audit"]);
+
+        let restriction = order.attributes[0].simple_type.as_ref().unwrap().restriction.as_ref().unwrap();
+        assert_eq!(restriction.base, "{http://www.w3.org/2001/XMLSchema}token");
+    }
+}
@@ -1,56 +1,239 @@
 use std::collections::HashMap;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct XsdSchema {
     pub target_namespace: Option<String>,
+    /// The `documentation` of the annotations directly under `schema`
+    pub comment: Option<String>,
     pub elements: Vec<XsdElement>,
     pub complex_types: Vec<XsdComplexType>,
     pub simple_types: Vec<XsdSimpleType>,
+    /// Named model groups, whose content `group` references reuse
+    pub groups: Vec<XsdGroup>,
+    /// Named attribute groups, whose attributes `attributeGroup` references reuse
+    pub attribute_groups: Vec<XsdAttributeGroup>,
     // Add other top-level components as needed
+    /// Namespace URIs by prefix; the default namespace has an empty prefix
     pub namespaces: HashMap<String, String>,
+    /// `schemaLocation`s of `import`s, which bring in components of other namespaces
     pub imported_schemas: Vec<String>,
+    /// `schemaLocation`s of `include`s, whose components belong to this schema's namespace
+    pub included_schemas: Vec<String>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct XsdElement {
     pub name: String,
     pub type_name: Option<String>,
+    /// The global element a `ref` declares this one as, in place of a name and a type
+    pub reference: Option<String>,
     pub min_occurs: Option<String>,
     pub max_occurs: Option<String>,
     pub complex_type: Option<XsdComplexType>,
     pub simple_type: Option<XsdSimpleType>,
+    /// The head of the substitution group of a global element: the element it may stand for
+    pub substitution_group: Option<String>,
+    /// Whether a global element only heads a substitution group and cannot appear itself
+    pub is_abstract: bool,
+    /// Whether the element may be empty with `xsi:nil`
+    pub nillable: bool,
+    /// Text of the `documentation` in the element's `annotation`
     pub comment: Option<String>,
+    pub appinfo: Vec<XsdAppInfo>,
+    /// The `key`, `keyref` and `unique` constraints declared on the element
+    pub identity_constraints: Vec<XsdIdentityConstraint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XsdIdentityConstraintKind {
+    Key,
+    KeyRef,
+    Unique,
+}
+
+/// A `key`, `keyref` or `unique` constraint. Within the element declaring it, the `fields` of
+/// every element its `selector` picks, XPaths relative to that element, identify it among the
+/// others, or, for a `keyref`, name an element identified by the `refer`red constraint.
+#[derive(Debug, Clone)]
+pub struct XsdIdentityConstraint {
+    pub kind: XsdIdentityConstraintKind,
+    pub name: String,
+    pub refer: Option<String>,
+    pub selector: String,
+    pub fields: Vec<String>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct XsdAttribute {
     pub name: String,
     pub type_name: String,
     pub use_type: String,
     pub default: Option<String>,
     pub fixed: Option<String>,
+    /// The attribute's inline simple type, if it has no `type`
+    pub simple_type: Option<XsdSimpleType>,
+    pub comment: Option<String>,
+    pub appinfo: Vec<XsdAppInfo>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct XsdComplexType {
     pub name: Option<String>,
-    pub sequence: Vec<XsdElement>,
+    /// The top-level `sequence`, `choice` or `all` compositor, if the type has child elements.
+    pub content: Option<XsdCompositor>,
     pub attributes: Vec<XsdAttribute>,
+    /// Names of the attribute groups whose attributes the type has besides its own
+    pub attribute_groups: Vec<String>,
+    /// The `anyAttribute` allowing attributes the type does not declare
+    pub any_attribute: Option<XsdWildcard>,
     pub mixed: bool,
+    /// The base type, for types declared with `complexContent` or `simpleContent`.
+    pub derivation: Option<XsdDerivation>,
+    pub comment: Option<String>,
+    pub appinfo: Vec<XsdAppInfo>,
+}
+
+/// An `appinfo` of an `annotation`, with its content as text.
+#[derive(Debug, Clone, Default)]
+pub struct XsdAppInfo {
+    /// The `source` URI identifying what the information is for
+    pub source: Option<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XsdDerivationMethod {
+    Extension,
+    Restriction,
+}
+
+/// The `extension` or `restriction` of a `complexContent` or `simpleContent` declaration.
+///
+/// An extension's `content` and `attributes` are the ones it adds to the base type; a
+/// restriction restates the whole content model it keeps.
+#[derive(Debug, Clone)]
+pub struct XsdDerivation {
+    pub base: String,
+    pub method: XsdDerivationMethod,
+    /// `simpleContent`: the type has a text value of the base type instead of child elements.
+    pub simple_content: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XsdCompositorKind {
+    Sequence,
+    Choice,
+    All,
+}
+
+/// A `sequence`, `choice` or `all` group and the elements and groups nested in it.
+#[derive(Debug, Clone)]
+pub struct XsdCompositor {
+    pub kind: XsdCompositorKind,
+    pub min_occurs: Option<String>,
+    pub max_occurs: Option<String>,
+    pub particles: Vec<XsdParticle>,
+}
+
+#[derive(Debug, Clone)]
+pub enum XsdParticle {
+    Element(Box<XsdElement>),
+    Compositor(XsdCompositor),
+    Group(XsdGroupRef),
+    Any(XsdWildcard),
 }
 
-#[derive(Debug, Default)]
+/// An `any` element or an `anyAttribute`, allowing content the schema does not declare.
+#[derive(Debug, Clone, Default)]
+pub struct XsdWildcard {
+    /// The namespaces the content may come from, like `##other`, if restricted
+    pub namespace: Option<String>,
+    /// `strict`, `lax` or `skip` validation of the content
+    pub process_contents: Option<String>,
+    pub min_occurs: Option<String>,
+    pub max_occurs: Option<String>,
+}
+
+/// A `group ref`, standing for the content of a named model group.
+#[derive(Debug, Clone)]
+pub struct XsdGroupRef {
+    pub reference: String,
+    pub min_occurs: Option<String>,
+    pub max_occurs: Option<String>,
+}
+
+/// A named model group: a `sequence`, `choice` or `all` that complex types and other groups
+/// reuse by reference. The bounds of a reference apply to the compositor.
+#[derive(Debug, Clone, Default)]
+pub struct XsdGroup {
+    pub name: String,
+    pub content: Option<XsdCompositor>,
+    pub comment: Option<String>,
+    pub appinfo: Vec<XsdAppInfo>,
+}
+
+/// A named attribute group: attributes that complex types and other attribute groups reuse by
+/// reference.
+#[derive(Debug, Clone, Default)]
+pub struct XsdAttributeGroup {
+    pub name: String,
+    pub attributes: Vec<XsdAttribute>,
+    /// Names of the attribute groups this one refers to
+    pub attribute_groups: Vec<String>,
+    pub any_attribute: Option<XsdWildcard>,
+    pub comment: Option<String>,
+    pub appinfo: Vec<XsdAppInfo>,
+}
+
+impl XsdComplexType {
+    /// Every element of the content model, in document order, whatever group it is nested in.
+    /// Model groups referred to are not looked into.
+    pub fn elements(&self) -> Vec<&XsdElement> {
+        fn collect<'a>(compositor: &'a XsdCompositor, elements: &mut Vec<&'a XsdElement>) {
+            for particle in &compositor.particles {
+                match particle {
+                    XsdParticle::Element(element) => elements.push(element.as_ref()),
+                    XsdParticle::Compositor(nested) => collect(nested, elements),
+                    XsdParticle::Group(_) | XsdParticle::Any(_) => {},
+                }
+            }
+        }
+
+        let mut elements = Vec::new();
+        if let Some(content) = &self.content {
+            collect(content, &mut elements);
+        }
+        elements
+    }
+}
+
+impl XsdCompositor {
+    pub fn sequence(elements: Vec<XsdElement>) -> Self {
+        Self {
+            kind: XsdCompositorKind::Sequence,
+            min_occurs: None,
+            max_occurs: None,
+            particles: elements.into_iter().map(|element| XsdParticle::Element(Box::new(element))).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct XsdSimpleType {
     pub name: Option<String>,
     pub restriction: Option<XsdRestriction>,
     pub list: Option<String>,
     pub union: Option<Vec<String>>,
+    pub comment: Option<String>,
+    pub appinfo: Vec<XsdAppInfo>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct XsdRestriction {
     pub base: String,
     pub enumeration: Vec<String>,
+    /// Documentation of enumeration values, keyed by value
+    pub enumeration_comments: HashMap<String, String>,
     pub pattern: Option<String>,
     pub min_inclusive: Option<String>,
     pub max_inclusive: Option<String>,